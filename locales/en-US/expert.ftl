@@ -0,0 +1,558 @@
+# English messages of the expert system
+#
+# Every message needs a counterpart in zh-CN/expert.ftl, variables are
+# written { $name }.
+
+## General
+
+header = Expert system v{ $version } by { $authors }
+    Use { $help } { $command } for more information
+header-command = <command>
+unknown-command = Unknown command: { $command }
+unknown-command-hint = Unknown command: { $command }, see help for every command
+unknown-subcommand = Unknown subcommand: { $subcommand }
+unknown-option = Unknown option: { $option }
+error-json = Error while writing json: { $error }
+error = Error: { $error }
+yes = yes
+no = no
+
+## Help
+
+help-commands =
+    Commands:
+      help: print this help
+      quit: leave the program
+      rule: show or change the rules of the rule base
+      test: deduce from a list of facts
+      batch: deduce the cases of a file and compare with the expected conclusions
+      coverage: show the coverage of rules and condition branches
+      proof: show the reasoning tree of a conclusion
+      label: set the words explanations use for a fact
+      tui: enter the full-screen mode
+      debug: step through deduction, with step, next-cycle, continue, break and watch
+      load: load a rule base from a .rules file
+      save: save the rule base as a .rules file
+      scenario: save and run fact scenarios
+      runs: show recorded deductions
+      db: show database information
+      kb: manage the knowledge bases of the database
+      set: show or change settings
+      undo/redo: undo or redo changes to the rules, history lists them
+help-help =
+    Prints help
+    Usage: help <command>
+help-quit =
+    Leaves the program
+    Usage: quit
+help-rule =
+    Shows or changes the rules of the rule base
+    Usage: rule <subcommand>
+    Subcommands:
+      list [--long]: list every rule, --long also shows tags, enabled state, author, times, description
+            and explanation, disabled rules are dimmed
+      add <rule> <output>: add a rule
+      remove <rule id>: remove a rule
+      edit <rule id>: put the condition and output of a rule on the command line, Enter saves the change
+      edit <rule id> <condition> <output>: replace the condition and output of a rule in place
+      set-condition <rule id> <condition>: change the condition of a rule
+      set-output <rule id> <output>: change the output of a rule
+      rename <rule id> <name>: change the name of a rule
+      set <rule id> description|explanation|template|author|tags <value>: change the description,
+            explanation, explanation template, author or tags of a rule, {"{"}condition{"}"} and {"{"}output{"}"}
+            in a template stand for the words of the condition and output
+      enable|disable <rule id>: enable or disable a rule, disabled rules stay but deduction skips them
+      find [condition] [--fact <fact>] [--output <fact>] [--tag <tag>] [--negation|--no-negation]:
+            find rules by the facts of their condition, output, tag, use of negation
+            and a regex on the condition text ([--regex] <regex>), every filter has to match,
+            matches are highlighted
+      shuffle: shuffle the order of the rules
+      graph [--dot|--mermaid] [--goal <fact>] [file]: draw the dependency graph of facts and rules,
+            negated dependencies are dashed, --goal keeps only what can lead to the fact,
+            without a format the extension decides (.mmd/.md for Mermaid, DOT otherwise)
+      confluence [n]: deduce under n random rule orders (every permutation for few rules),
+                      finding inputs whose conclusions depend on the order, minimal counterexamples
+                      and the rule pairs involved
+      export <file>: export the rules, the extension picks the format (json/jsonl/yaml/csv/toml)
+                     or CLIPS (.clp), Prolog (.pl), Drools (.drl) and JSON Logic (.jsonlogic)
+      import <file> [--merge|--replace]: import rules, merging by default, .clp and .jsonlogic work too
+help-test =
+    Deduces from a list of facts, the run is recorded when a database is connected
+    Usage: test [facts]
+    Example: test fact1 fact2
+    Without facts the last input or loaded scenario is used
+help-load =
+    Loads the rules and test cases of a .rules file, replacing the current ones
+    Usage: load <file>
+    Format: rule [name] ["description"] [tags]: IF <condition> THEN <output>, <output>  # comment
+            test <name>: GIVEN <fact>, <fact> EXPECT <fact> NOT <fact>
+            include "other.rules"
+            label <fact>: "words"
+    Tags go in brackets like [cats, big], conditions may span lines, include paths are relative to the file
+    @author, @explain, @template, @created, @updated and @disabled lines before a rule set its metadata
+help-save =
+    Saves the rules and test cases as a .rules file
+    Usage: save <file>
+help-scenario =
+    Saves and runs named fact scenarios
+    Usage: scenario <subcommand>
+    Subcommands:
+      save <name> [facts]: save a scenario, the last input without facts
+      load <name>: use a scenario as the input of the next test
+      list: list the scenarios of the current knowledge base
+      run <name>: run a scenario and compare with its last run
+help-runs =
+    Shows recorded deductions
+    Usage: runs <subcommand>
+    Subcommands:
+      list: list the runs of the current knowledge base
+      show <run id>: show the details of a run
+help-db =
+    Shows database information
+    Usage: db <subcommand>
+    Subcommands:
+      connect <path>: connect to a database
+        sqlite:<path> or a .db/.sqlite file: an SQLite database
+        toml:<dir>, json:<dir> or an existing directory: a directory of text files
+        memory: an in-memory database, lost on exit
+      close: close the connection
+      status: show the database status
+      load: load the rules and test cases of the current knowledge base
+      sync: save the rules and test cases to the current knowledge base
+      reset: reset the database
+      add <rule> <output>: insert a rule into the current knowledge base directly
+      remove <db id>: delete a rule from the current knowledge base directly
+      history: show the changes made to the current knowledge base
+      find <filters>: find rules in the current knowledge base, filters as in rule find,
+            SQLite uses a full text index
+help-batch =
+    Deduces each case of a case file with the current rules and compares with the expected conclusions
+    Usage: batch <case file> [--output <result file>]
+    Case files are .csv (columns name, input, expected, facts split by spaces or commas) or .jsonl
+    Reports whether each case passed and a confusion matrix per conclusion
+    --output writes the results to a .csv or .json file
+help-debug =
+    Steps through deduction
+    Usage:
+      debug <facts>: start debugging from these facts, the last input without facts
+      debug stop: stop debugging
+      step: try the next rule
+      next-cycle: run to the end of the cycle
+      continue: run to the end of deduction
+      break rule <rule id>: stop before the rule fires
+      break fact <fact>: stop after the fact is derived
+      break [clear]: list or clear breakpoints
+      watch <fact> | watch [clear]: watch a fact, reported when derived and shown at every stop
+    Every stop shows the current facts, the rules about to fire and why they hold
+help-tui =
+    Enters the full-screen mode, sharing rules, facts and the database connection with the command line
+    Tab switches panes, in the rule list / searches, Enter edits, n adds, d deletes, q quits
+    While editing a rule Up and Down switch between condition and output, syntax is checked as you type,
+    Enter saves
+    Needs a build with --features tui
+help-journal =
+    Undoes or redoes the changes made to rules and test cases in this session
+    Usage:
+      undo: undo the last change
+      redo: redo the last undone change
+      history: list the changes of this session, undone ones are marked undone
+    Records rule add/remove/edit/set-condition/set-output/rename/set/enable/disable/shuffle/import,
+    load, db load and kb test add/remove, keeping up to 100 entries
+help-proof =
+    Shows how a fact of the last test or scenario run was derived, down to the input facts
+    Usage: proof <fact> [--dot|--json] [file]
+    Draws the reasoning tree in the terminal by default, --dot or --json write DOT or JSON,
+    a file is written when given, without a format its extension decides (.dot/.gv or .json)
+help-label =
+    Sets the words explanations use for a fact, the deduction log, proof trees and results show them
+    Usage: label <subcommand>
+    Subcommands:
+      list: list every label, also without a subcommand
+      <fact> <words>: set the label of a fact, !<fact> sets the words for its absence
+      remove <fact>: remove the label of a fact
+    Example: label mammal it is a mammal
+help-coverage =
+    Shows rule coverage over every test, scenario run, batch and kb test since the last reset
+    Usage: coverage [reset]
+    Lists how often each rule was checked, held and fired, and the true and false branch coverage
+    of every subexpression of its condition
+    Rules that never fired are red, subexpressions that were only true, only false or never
+    evaluated are listed in the last column
+    reset clears the counts
+help-set =
+    Shows or changes settings, lists the current settings without arguments
+    Usage: set [setting] [value]
+    Settings:
+      format text|json: output format, with json every command writes one JSON document
+      lang zh-CN|en-US: interface language, taken from LC_ALL, LC_MESSAGES or LANG by default
+      max-cycles <n>|none: cycle limit of test and scenario run
+      strategy all|first|specificity: which of the matching rules fire in a cycle
+    Defaults are read from the config file, see --help
+help-kb =
+    Manages the knowledge bases of the database
+    Usage: kb <subcommand>
+    Subcommands:
+      list: list every knowledge base
+      create <name>: create a knowledge base
+      use <name> [--force]: switch to a knowledge base and load its rules
+      copy <from> <to>: copy a knowledge base
+      delete <name>: delete a knowledge base
+      test: run every test case, showing the deduction of failures
+      test add <name> <given facts> <expected facts> [facts not expected]: add a test case
+        facts are separated by commas, - for none
+      test list: list the test cases
+      test remove <number>: remove a test case
+
+## Command line
+
+cli-about = Expert system, interactive without arguments
+cli-eval = Run a command, may be repeated to run several in order
+cli-eval-value = COMMAND
+cli-format = Output format, with json every command writes one JSON document to stdout and logs go to stderr
+cli-format-text = Tables and plain text for people
+cli-format-json = One JSON document per command
+cli-tui = Start in the full-screen mode
+cli-run = Run the commands of a script file line by line, `-` reads stdin
+cli-run-script = SCRIPT
+script-read-error = Cannot read { $source }: { $error }
+script-open-error = Cannot open script { $path }: { $error }
+script-command-failed = { $source }:{ $line }: command failed: { $command }
+repl-no-history = No previous history
+repl-exit-interrupt = Exiting due to CTRL-C
+repl-exit-eof = Exiting due to CTRL-D
+tui-error = Error while running the full-screen mode: { $error }
+tui-missing = Built without full-screen mode, rebuild with --features tui
+
+## General
+
+option-missing-value = { $option } needs a value
+invalid-regex = Invalid regex { $pattern }: { $error }
+parse-error-at = { $error } at position { $position } of { $condition }
+file-write-error = Error while writing { $path }: { $error }
+file-read-error = Error while reading { $path }: { $error }
+
+## Table headers
+
+col-id = id
+col-name = name
+col-condition = condition
+col-output = output
+col-tags = tags
+col-enabled = enabled
+col-author = author
+col-created = created
+col-updated = updated
+col-description = description
+col-explanation = explanation
+
+## Rules
+
+rule-missing = rule { $id } does not exist
+rule-found = Found { $found } of { $total } rules
+rule-shuffled = Successfully shuffled rules
+rule-add-error = Error while adding new rules: { $error }
+rule-added = Successfully added rule with condition { $condition } and output { $output }
+rule-remove-error = Error while removing rules: { $error }
+rule-removed = Successfully removed rule with idx { $id }
+rule-edit-error = Error while editing rule: { $error }
+rule-edited = Successfully edited rule { $id }: { $rule }
+rule-editing = Editing rule { $id }, press Enter to apply
+rule-unknown-field = Unknown field: { $field }, use description, explanation, template, author or tags
+rule-export-error = Error while exporting rules: { $error }
+rule-exported = Successfully exported { $count } rules to { $path }
+rule-import-unknown-mode = Unknown option: { $option }, use --merge or --replace
+rule-import-error = Error while importing { $path }: { $error }
+rule-imported-replacing = Successfully imported { $count } rules, replacing { $replaced } rules
+rule-imported-merging = Successfully imported { $count } rules, { $existing } already existed
+usage-rule-add = Usage: add <rule> <output>
+example-rule-add = Example: rule add fact1|(fact2&fact3) output1,output2
+usage-rule-remove = Usage: remove <rule id>
+usage-rule-set-condition = Usage: set-condition <rule id> <condition>
+usage-rule-set-output = Usage: set-output <rule id> <output>
+usage-rule-rename = Usage: rename <rule id> <name>
+usage-rule-enable = Usage: enable|disable <rule id>
+usage-rule-set = Usage: set <rule id> description|explanation|template|author|tags <value>
+example-rule-set = Example: rule set 1 template {"{"}output{"}"} because {"{"}condition{"}"}
+usage-rule-export = Usage: export <file>
+usage-rule-import = Usage: import <file> [--merge|--replace]
+usage-rule-confluence = Usage: rule confluence [orders]
+usage-rule-graph = Usage: rule graph [--dot|--mermaid] [--goal <fact>] [file]
+confluence-ran = Ran { $inputs } inputs under { $orders } rule orders
+confluence-diverging = { $count } inputs reach different conclusions depending on rule order
+confluence-confluent = Conclusions do not depend on rule order
+confluence-divergences = { $count } inputs reach different conclusions:
+confluence-more = ... and { $count } more
+confluence-minimal = Minimal counterexample: {"{"}{ $input }{"}"}
+confluence-order = order { $order } derives {"{"}{ $derived }{"}"}
+confluence-no-pair = No single pair of rules explains the difference
+confluence-pairs = Responsible rules:
+confluence-pair = #{ $first } { $first_rule } depends on #{ $second } { $second_rule } not having fired yet
+graph-unknown-goal = No rule tests or derives { $fact }
+graph-written = Successfully wrote { $format } graph to { $path }
+
+## Settings
+
+set-format = Output format set to { $format }
+set-unknown-format = Unknown format: { $format }, use text or json
+usage-set-format = Usage: set format text|json
+set-lang = Language set to { $lang }
+set-unknown-lang = Unknown language: { $lang }, use zh-CN or en-US
+usage-set-lang = Usage: set lang zh-CN|en-US
+set-max-cycles = Cycle limit set to { $max }
+usage-set-max-cycles = Usage: set max-cycles <n>|none
+set-strategy = Strategy set to { $strategy }
+usage-set-strategy = Usage: set strategy all|first|specificity
+set-unknown = Unknown setting: { $setting }
+
+## Database
+
+usage-db-connect = Usage: connect <path>
+usage-db-add = Usage: db add <rule> <output>
+usage-db-remove = Usage: db remove <db id>
+db-already-connected = db connection is already established, close it with db close
+db-connecting = Initializing database { $path }
+db-connect-error = Error while creating connection: { $error }
+db-connected = Successfully established connection to { $path }
+db-not-connected = No established db connection, use db connect first
+db-closed = Successfully closed connection
+db-read-error = Error while reading db: { $error }
+db-status = Knowledge base { $kb } has { $count } rules
+db-search-error = Error while searching db: { $error }
+db-found = Found { $count } rules in knowledge base { $kb }
+db-resetting = Resetting database
+db-reset-error = Error while resetting db: { $error }
+db-reset = Database reset complete
+db-kb-loading = Loading knowledge base { $kb }
+db-kb-loaded = Successfully loaded { $rules } rules and { $tests } tests
+db-syncing = Syncing knowledge base { $kb }
+db-save-rules-error = Error while saving rules: { $error }
+db-save-tests-error = Error while saving tests: { $error }
+db-save-labels-error = Error while saving fact labels: { $error }
+db-synced = Database sync complete
+db-rule-inserted = Successfully inserted rule #{ $id } into knowledge base { $kb }
+db-insert-error = Error while inserting rule: { $error }
+db-rule-deleted = Successfully deleted rule #{ $id } from knowledge base { $kb }
+db-delete-error = Error while deleting rule: { $error }
+
+## General (continued)
+
+rule-parse-error = Error while parsing rule: { $error }
+invalid-rule-id = Invalid rule id { $id }: { $error }
+col-action = action
+col-detail = detail
+col-time = time
+
+## Knowledge bases
+
+usage-kb-create = Usage: kb create <name>
+usage-kb-use = Usage: kb use <name> [--force]
+usage-kb-copy = Usage: kb copy <from> <to>
+usage-kb-delete = Usage: kb delete <name>
+kb-create-error = Error while creating knowledge base: { $error }
+kb-created = Successfully created knowledge base { $kb }
+kb-missing = Knowledge base { $kb } does not exist, create it with kb create
+kb-using = Now using knowledge base { $kb }
+kb-unsynced = Knowledge base { $kb } has changes not synced yet, run db sync first or kb use --force to drop them
+kb-copy-error = Error while copying knowledge base: { $error }
+kb-copied = Successfully copied knowledge base { $from } to { $to }
+kb-in-use = Knowledge base { $kb } is in use, switch to another one with kb use first
+kb-delete-error = Error while deleting knowledge base: { $error }
+kb-deleted = Successfully deleted knowledge base { $kb }
+col-rules = rules
+col-in-use = in use
+
+## Test cases
+
+usage-kb-test-add = Usage: kb test add <name> <given facts> <expected facts> [facts not expected]
+usage-kb-test-remove = Usage: kb test remove <number>
+invalid-fact = Invalid fact { $fact }
+test-needs-expectation = A test needs facts that should or should not be derived
+test-added = Added test { $name }, { $count } tests in total
+test-removed = Removed test { $name }
+test-missing = Test { $id } does not exist
+invalid-test-id = Invalid test id { $id }: { $error }
+test-none = No tests, add some with kb test add or load a .rules file
+test-pass = PASS { $name }
+test-fail = FAIL { $name }
+test-summary = { $passed } passed, { $failed } failed
+test-failed-count = { $failed } of { $total } tests failed
+test-not-derived = not derived: { $facts }
+test-unwanted = derived but not expected: { $facts }
+test-derived = derived facts: { $facts }
+test-no-rule-concludes = no rule concludes { $fact }
+test-could-come-from = { $fact } could come from: { $rules }
+col-given = given
+col-expect = expect
+col-not = not
+col-cycle = cycle
+col-rule = rule
+col-added = added
+
+## Scenarios and runs
+
+usage-scenario-save = Usage: scenario save <name> [facts]
+usage-scenario-load = Usage: scenario load <name>
+usage-scenario-run = Usage: scenario run <name>
+usage-runs-show = Usage: runs show <run id>
+scenario-no-facts = No facts to save, give them as arguments or run test first
+scenario-save-error = Error while saving scenario: { $error }
+scenario-saved = Successfully saved scenario { $name } with facts { $facts }
+scenario-loaded = Loaded scenario { $name } with facts { $facts }, run it with test
+scenario-missing = Scenario { $name } does not exist
+scenario-first-run = First run of scenario { $name }, derived: { $derived }
+scenario-unchanged = Conclusions unchanged since run #{ $id }
+scenario-changed = Conclusions changed since run #{ $id } ({ $time }):
+invalid-run-id = Invalid run id { $id }: { $error }
+run-missing = Run #{ $id } does not exist
+run-title = Run #{ $id } on knowledge base { $kb } at { $time }
+run-scenario = Scenario: { $scenario }
+run-input = Input facts: { $facts }
+derived-facts = Derived facts: { $facts }
+run-cycles = Cycles: { $cycles }
+run-recorded = Recorded run #{ $id }
+run-record-error = Error while recording run: { $error }
+col-facts = facts
+col-scenario = scenario
+col-input = input
+col-derived = derived
+col-cycles = cycles
+col-rules-fired = rules fired
+
+## Batch
+
+usage-batch = Usage: batch <case file> [--output <result file>]
+batch-unknown-option = Unknown option: { $option }, use --output <file>
+batch-read-error = Error while reading cases: { $error }
+batch-write-error = Error while writing results: { $error }
+batch-written = Successfully wrote results to { $path }
+batch-failed-count = { $failed } of { $total } cases failed
+batch-pass = pass
+batch-fail = fail
+batch-missing = missing { $facts }
+batch-unexpected = unexpected { $facts }
+batch-summary = { $passed } passed, { $failed } failed, { $unlabelled } without expected conclusions
+col-line = line
+col-expected = expected
+col-result = result
+col-conclusion = conclusion
+col-precision = precision
+col-recall = recall
+
+## Files
+
+file-load-error = Error while loading { $path }: { $error }
+file-loaded = Successfully loaded { $rules } rules and { $tests } tests from { $path }
+file-save-error = Error while saving { $path }: { $error }
+file-saved = Successfully saved { $rules } rules and { $tests } tests to { $path }
+
+## Undo and redo
+
+journal-undid = Undid { $action }
+journal-nothing-to-undo = Nothing to undo
+journal-redid = Redid { $action }
+journal-nothing-to-redo = Nothing to redo
+journal-empty = No edits in this session yet
+journal-undone = undone
+col-state = state
+
+## Proofs
+
+usage-proof = Usage: proof <fact> [--dot|--json] [file]
+proof-nothing-deduced = Nothing deduced yet, run test first
+proof-not-reached = { $fact } was not reached by the last deduction
+proof-written = Successfully wrote proof of { $fact } to { $path }
+
+## Coverage
+
+coverage-reset = Coverage reset
+coverage-empty = Nothing recorded yet, run test, scenario run, batch or kb test first
+coverage-not-evaluated = not evaluated
+coverage-summary = { $never_fired } of { $total } rules never fired
+coverage-only-true = { $condition }: only true
+coverage-only-false = { $condition }: only false
+coverage-never-evaluated = { $condition }: never evaluated
+col-evaluated = evaluated
+col-matched = matched
+col-fired = fired
+col-branches = branches
+col-uncovered = uncovered
+
+## Debugging
+
+usage-break = Usage: break rule <rule id> | break fact <fact> | break clear
+debug-stopped = Debugging stopped
+debug-inactive = Not debugging, start with debug <facts>
+debug-started = Debugging from facts { $facts }, use step, next-cycle or continue
+debug-finished = Deduction is complete, start again with debug <facts>
+debug-breakpoints-cleared = Breakpoints cleared
+debug-breakpoint-set = Breakpoint set: { $breakpoint }
+debug-breakpoint-rule = rule #{ $id }
+debug-breakpoint-fact = fact { $fact }
+debug-watches-cleared = Watches cleared
+debug-watching = Watching { $fact }
+debug-fired = cycle { $cycle }: rule #{ $rule } fired, added { $added }
+debug-watch-derived = watch: { $fact } derived by rule #{ $rule }
+debug-skipped = rule #{ $rule } added nothing
+debug-cycle-changed = cycle { $cycle } complete, facts were added
+debug-cycle-unchanged = cycle { $cycle } complete, nothing new
+debug-stopped-at = Stopped at breakpoint { $breakpoint }
+debug-complete = Deduction complete
+debug-facts = Cycle { $cycle }, facts: { $facts }
+debug-watch-known = watch: { $fact } is known
+debug-watch-unknown = watch: { $fact } is unknown
+debug-nothing-pending = No rule is about to fire
+debug-pending = About to fire:
+col-why = why
+
+## Full-screen mode
+
+tui-keys = Tab: switch pane  /: search  Enter: edit  n: new  d: delete  q: quit
+tui-removed = Removed rule #{ $id }
+tui-not-saved = Not saved: { $error }
+tui-updated = Updated rule #{ $id }
+tui-added = Added rule #{ $id }
+tui-facts = Facts
+tui-derived = Derived
+tui-db = db: { $kb }
+tui-no-db = no db
+tui-rule-count = { $count } rules
+tui-rules = Rules
+tui-edit = Edit rule #{ $id }
+tui-new = New rule
+tui-valid = ok, Enter to save
+tui-parse-error = at { $position }: { $error }
+tui-cycle = cycle { $cycle }: { $firings }
+tui-nothing-derived = nothing derived
+
+## Explanations
+
+explain-fired = Because { $condition } holds, { $outputs } follows
+explain-done = Deduction complete after { $cycles } cycles, facts: { $facts }
+explain-given = { $fact } (given)
+explain-absent = not { $fact } (unknown when the rule fired)
+explain-derived = { $fact } (rule #{ $rule }, cycle { $cycle }: IF { $condition })
+explain-known = { $facts } known
+explain-unknown = { $facts } not known
+explain-and = {" and "}
+explain-or = {" or "}
+explain-not = {"not "}
+
+## Fact labels
+
+label-set = Label of { $fact } set to: { $label }
+label-removed = Label of { $fact } removed
+label-missing = { $fact } has no label
+label-invalid = Invalid fact name: { $fact }
+label-none = No labels
+col-fact = fact
+col-label = label
+col-template = template
+
+## Configuration file
+
+config-read-error = Cannot read the configuration file { $path }
+config-parse-error = Invalid configuration file { $path }
+config-unknown-lang = Unknown language in the configuration file: { $lang }, use zh-CN or en-US
+config-invalid-log-level = Invalid log level in the configuration file: { $level }
+cli-config = Configuration file, { $path } by default
+cli-config-value = file
@@ -0,0 +1,550 @@
+# 专家系统的中文消息
+#
+# 每条消息在 en-US/expert.ftl 中都要有对应的一条，变量写作 { $name }。
+
+## 通用
+
+header = 专家系统 v{ $version } by { $authors }
+    使用 { $help } { $command } 可以查询更多信息
+header-command = <命令>
+unknown-command = 未知命令: { $command }
+unknown-command-hint = 未知命令: { $command }，使用 help 查看所有命令
+unknown-subcommand = 未知子命令: { $subcommand }
+unknown-option = 未知选项: { $option }
+error-json = 写出 JSON 时出错: { $error }
+error = 错误: { $error }
+yes = 是
+no = 否
+
+## 帮助
+
+help-commands =
+    命令:
+      help: 输出此帮助信息
+      quit: 退出程序
+      rule: 查看或修改规则库中的规则
+      test: 输入一系列的事实进行推论
+      batch: 批量推论案例文件并和预期结论比较
+      coverage: 查看规则和条件分支的覆盖率
+      proof: 显示结论的推理树
+      label: 给事实设置解释中使用的说法
+      tui: 进入全屏界面
+      debug: 逐步调试推论，配合 step、next-cycle、continue、break、watch
+      load: 从 .rules 文件加载规则库
+      save: 把规则库保存为 .rules 文件
+      scenario: 保存和运行事实场景
+      runs: 查看记录的推论
+      db: 查看数据库信息
+      kb: 管理数据库中的知识库
+      set: 查看或修改设置
+      undo/redo: 撤销或重做对规则的修改，history 查看修改记录
+help-help =
+    输出帮助信息
+    用法: help <命令>
+help-quit =
+    退出程序
+    用法: quit
+help-rule =
+    查看或修改规则库中的规则
+    用法: rule <子命令>
+    子命令:
+      list [--long]: 列出所有规则，--long 同时显示标签、启用状态、作者、时间、描述和解释，
+            停用的规则显示为灰色
+      add <规则> <输出>: 添加新规则
+      remove <规则ID>: 删除指定规则
+      edit <规则ID>: 把规则的条件和输出填入命令行，修改后回车保存
+      edit <规则ID> <条件> <输出>: 原地替换规则的条件和输出
+      set-condition <规则ID> <条件>: 修改规则的条件
+      set-output <规则ID> <输出>: 修改规则的输出
+      rename <规则ID> <名称>: 修改规则的名称
+      set <规则ID> description|explanation|template|author|tags <值>: 修改规则的描述、解释、解释模板、
+            作者或标签，模板中的 {"{"}condition{"}"} 和 {"{"}output{"}"} 换成条件和输出的说法
+      enable|disable <规则ID>: 启用或停用规则，停用的规则保留但推论时跳过
+      find [条件] [--fact <事实>] [--output <事实>] [--tag <标签>] [--negation|--no-negation]:
+            查找规则，条件中的事实、输出、标签、是否使用否定，
+            以及对条件文本的正则 ([--regex] <正则>)，所有条件都要满足，匹配部分高亮
+      shuffle: 随机打乱规则顺序
+      graph [--dot|--mermaid] [--goal <事实>] [文件]: 输出事实和规则的依赖图，
+            否定的依赖用虚线表示，--goal 只保留能推出该事实的部分，
+            不指定格式时按扩展名 (.mmd/.md 为 Mermaid，否则 DOT)
+      confluence [n]: 在 n 种随机规则顺序下推论 (规则较少时默认全部排列)，
+                      找出结论随顺序变化的输入、最小反例和相关的规则对
+      export <文件>: 导出规则，格式由扩展名决定 (json/jsonl/yaml/csv/toml)
+                     也可导出为 CLIPS (.clp)、Prolog (.pl)、Drools (.drl) 或 JSON Logic (.jsonlogic)
+      import <文件> [--merge|--replace]: 导入规则，默认合并，支持 .clp 和 .jsonlogic
+help-test =
+    输入一系列的事实进行推论，连接数据库时会记录本次推论
+    用法: test [事实]
+    示例: test fact1 fact2
+    不带事实时使用上一次输入或载入的场景
+help-load =
+    从 .rules 文件加载规则库和测试用例，替换当前规则和测试
+    用法: load <文件>
+    格式: rule [名称] ["描述"] [标签]: IF <条件> THEN <输出>, <输出>  # 注释
+          test <名称>: GIVEN <事实>, <事实> EXPECT <事实> NOT <事实>
+          include "其他文件.rules"
+          label <事实>: "说法"
+    标签写在方括号中，如 [cats, big]，条件可以跨越多行，include 的路径相对于当前文件
+    规则前的 @author、@explain、@template、@created、@updated 和 @disabled 行设置规则的元数据
+help-save =
+    把规则库和测试用例保存为 .rules 文件
+    用法: save <文件>
+help-scenario =
+    保存和运行命名的事实场景
+    用法: scenario <子命令>
+    子命令:
+      save <名称> [事实]: 保存场景，不带事实时保存上一次输入
+      load <名称>: 载入场景作为下一次 test 的输入
+      list: 列出当前知识库的场景
+      run <名称>: 运行场景并与上一次结果比较
+help-runs =
+    查看记录的推论
+    用法: runs <子命令>
+    子命令:
+      list: 列出当前知识库的推论记录
+      show <运行ID>: 查看推论详情
+help-db =
+    查看数据库信息
+    用法: db <子命令>
+    子命令:
+      connect <路径>: 连接数据库
+        sqlite:<路径> 或 .db/.sqlite 文件: SQLite 数据库
+        toml:<目录>、json:<目录> 或已存在的目录: 文本文件目录
+        memory: 内存数据库，退出后丢失
+      close: 断开数据库连接
+      status: 查看数据库状态
+      load: 从数据库加载当前知识库的规则和测试用例
+      sync: 保存规则库和测试用例到数据库的当前知识库
+      reset: 重置数据库
+      add <规则> <输出>: 直接向当前知识库插入规则
+      remove <数据库ID>: 直接从当前知识库删除规则
+      history: 查看当前知识库的修改记录
+      find <条件>: 在当前知识库中查找规则，条件同 rule find，SQLite 使用全文索引
+help-batch =
+    用当前规则库逐个推论案例文件中的案例，并和预期结论比较
+    用法: batch <案例文件> [--output <结果文件>]
+    案例文件为 .csv (列 name, input, expected，事实用空格或逗号分隔) 或 .jsonl
+    报告每个案例是否通过，以及每个结论的混淆矩阵
+    --output 把结果写入 .csv 或 .json 文件
+help-debug =
+    逐步调试推论过程
+    用法:
+      debug <事实>: 从这些事实开始调试，不带事实时使用上一次输入
+      debug stop: 结束调试
+      step: 尝试下一条规则
+      next-cycle: 运行到本轮结束
+      continue: 运行到推论结束
+      break rule <规则ID>: 在规则触发前停下
+      break fact <事实>: 在推出事实后停下
+      break [clear]: 列出或清除断点
+      watch <事实> | watch [clear]: 监视事实，推出时提示，每次停下时显示
+    每次停下时显示当前事实、即将触发的规则以及它们满足的原因
+help-tui =
+    进入全屏界面，规则、事实和数据库连接与命令行共享，退出后回到命令行
+    Tab 切换窗格，规则列表中 / 搜索、Enter 编辑、n 新建、d 删除、q 退出
+    编辑规则时上下键切换条件和输出，输入时即时检查语法，Enter 保存
+    需要用 --features tui 编译
+help-journal =
+    撤销或重做本次会话中对规则和测试用例的修改
+    用法:
+      undo: 撤销上一次修改
+      redo: 重做上一次撤销的修改
+      history: 列出本次会话的修改，已撤销的标为 undone
+    记录 rule add/remove/edit/set-condition/set-output/rename/set/enable/disable/shuffle/import、
+    load、db load 以及 kb test add/remove，最多保留 100 条
+help-proof =
+    显示上一次 test 或 scenario run 中某个事实是怎样推出的，一直追溯到输入事实
+    用法: proof <事实> [--dot|--json] [文件]
+    默认在终端画出推理树，--dot 或 --json 输出 DOT 或 JSON，
+    指定文件时写入文件，不指定格式时按扩展名 (.dot/.gv 或 .json)
+help-label =
+    给事实设置解释中使用的说法，推论过程、推理树和推论结果都用这些说法显示
+    用法: label <子命令>
+    子命令:
+      list: 列出所有说法，不带子命令时也是列出
+      <事实> <说法>: 设置事实的说法，!<事实> 设置事实不成立时的说法
+      remove <事实>: 删除事实的说法
+    用例: label mammal 它是哺乳动物
+help-coverage =
+    查看规则覆盖率，统计自上次 reset 以来 test、scenario run、batch 和 kb test 的所有推论
+    用法: coverage [reset]
+    列出每条规则被检查、满足和触发的次数，以及条件中每个子表达式的真假分支覆盖
+    从未触发的规则标为红色，只为真、只为假或从未求值的子表达式列在最后一列
+    reset 清空统计
+help-set =
+    查看或修改设置，不带参数时列出当前设置
+    用法: set [设置] [值]
+    设置:
+      format text|json: 输出格式，json 时每条命令输出一个 JSON 文档
+      lang zh-CN|en-US: 界面语言，默认取自 LC_ALL、LC_MESSAGES 或 LANG
+      max-cycles <n>|none: test 与 scenario run 的轮数上限
+      strategy all|first|specificity: 每轮触发哪些条件成立的规则
+    默认值取自配置文件，见 --help
+help-kb =
+    管理数据库中的多个知识库
+    用法: kb <子命令>
+    子命令:
+      list: 列出所有知识库
+      create <名称>: 创建新知识库
+      use <名称> [--force]: 切换当前知识库并加载它的规则
+      copy <源> <目标>: 复制知识库
+      delete <名称>: 删除知识库
+      test: 运行所有测试用例，失败时显示推论过程
+      test add <名称> <给定事实> <应推出事实> [不应推出事实]: 添加测试用例
+        事实用逗号分隔，- 表示没有
+      test list: 列出测试用例
+      test remove <序号>: 删除测试用例
+
+## 命令行
+
+cli-about = 专家系统，不带参数时进入交互模式
+cli-eval = 执行一条命令，可以重复使用，按顺序执行
+cli-eval-value = 命令
+cli-format = 输出格式，json 时每条命令在标准输出写一个 JSON 文档，日志写到标准错误
+cli-format-text = 供人阅读的表格和文本
+cli-format-json = 每条命令一个 JSON 文档
+cli-tui = 以全屏界面启动
+cli-run = 逐行执行脚本文件中的命令，`-` 表示标准输入
+cli-run-script = 脚本
+script-read-error = 无法读取 { $source }: { $error }
+script-open-error = 无法打开脚本 { $path }: { $error }
+script-command-failed = { $source }:{ $line }: 命令执行失败: { $command }
+repl-no-history = 没有历史记录
+repl-exit-interrupt = 收到 CTRL-C，退出
+repl-exit-eof = 收到 CTRL-D，退出
+tui-error = 运行全屏界面时出错: { $error }
+tui-missing = 编译时没有启用全屏界面，请用 --features tui 重新编译
+
+## 通用
+
+option-missing-value = { $option } 后面需要一个值
+invalid-regex = 无效的正则 { $pattern }: { $error }
+parse-error-at = { $error }，位于 { $condition } 的第 { $position } 个字符
+file-write-error = 写入 { $path } 时出错: { $error }
+file-read-error = 读取 { $path } 时出错: { $error }
+
+## 表头
+
+col-id = 编号
+col-name = 名称
+col-condition = 条件
+col-output = 输出
+col-tags = 标签
+col-enabled = 启用
+col-author = 作者
+col-created = 创建时间
+col-updated = 修改时间
+col-description = 描述
+col-explanation = 解释
+
+## 规则
+
+rule-missing = 规则 { $id } 不存在
+rule-found = 在 { $total } 条规则中找到 { $found } 条
+rule-shuffled = 已打乱规则顺序
+rule-add-error = 添加规则时出错: { $error }
+rule-added = 已添加规则，条件 { $condition }，输出 { $output }
+rule-remove-error = 删除规则时出错: { $error }
+rule-removed = 已删除规则 { $id }
+rule-edit-error = 修改规则时出错: { $error }
+rule-edited = 已修改规则 { $id }: { $rule }
+rule-editing = 正在修改规则 { $id }，回车保存
+rule-unknown-field = 未知字段: { $field }，可选 description、explanation、template、author、tags
+rule-export-error = 导出规则时出错: { $error }
+rule-exported = 已把 { $count } 条规则导出到 { $path }
+rule-import-unknown-mode = 未知选项: { $option }，可用 --merge 或 --replace
+rule-import-error = 导入 { $path } 时出错: { $error }
+rule-imported-replacing = 已导入 { $count } 条规则，替换了原有的 { $replaced } 条
+rule-imported-merging = 已导入 { $count } 条规则，{ $existing } 条已经存在
+usage-rule-add = 用法：add <规则> <输出>
+example-rule-add = 用例：rule add fact1|(fact2&fact3) output1,output2
+usage-rule-remove = 用法：remove <规则ID>
+usage-rule-set-condition = 用法：set-condition <规则ID> <条件>
+usage-rule-set-output = 用法：set-output <规则ID> <输出>
+usage-rule-rename = 用法：rename <规则ID> <名称>
+usage-rule-enable = 用法：enable|disable <规则ID>
+usage-rule-set = 用法：set <规则ID> description|explanation|template|author|tags <值>
+example-rule-set = 用例：rule set 1 template 因为{"{"}condition{"}"}，所以{"{"}output{"}"}
+usage-rule-export = 用法：export <文件>
+usage-rule-import = 用法：import <文件> [--merge|--replace]
+usage-rule-confluence = 用法：rule confluence [排列数]
+usage-rule-graph = 用法：rule graph [--dot|--mermaid] [--goal <事实>] [文件]
+confluence-ran = 在 { $orders } 种规则顺序下推论了 { $inputs } 组输入
+confluence-diverging = { $count } 组输入的结论随规则顺序变化
+confluence-confluent = 结论与规则顺序无关
+confluence-divergences = { $count } 组输入得出不同的结论:
+confluence-more = ... 以及另外 { $count } 组
+confluence-minimal = 最小反例: {"{"}{ $input }{"}"}
+confluence-order = 顺序 { $order } 推出 {"{"}{ $derived }{"}"}
+confluence-no-pair = 没有单独一对规则能解释这一差异
+confluence-pairs = 相关的规则:
+confluence-pair = #{ $first } { $first_rule } 依赖于 #{ $second } { $second_rule } 尚未触发
+graph-unknown-goal = 没有规则检查或推出 { $fact }
+graph-written = 已把 { $format } 图写入 { $path }
+
+## 设置
+
+set-format = 输出格式设为 { $format }
+set-unknown-format = 未知格式: { $format }，可用 text 或 json
+usage-set-format = 用法：set format text|json
+set-lang = 界面语言设为 { $lang }
+set-unknown-lang = 未知语言: { $lang }，可用 zh-CN 或 en-US
+usage-set-lang = 用法：set lang zh-CN|en-US
+set-max-cycles = 轮数上限设为 { $max }
+usage-set-max-cycles = 用法：set max-cycles <n>|none
+set-strategy = 策略设为 { $strategy }
+usage-set-strategy = 用法：set strategy all|first|specificity
+set-unknown = 未知设置: { $setting }
+
+## 数据库
+
+usage-db-connect = 用法：connect <路径>
+usage-db-add = 用法：db add <规则> <输出>
+usage-db-remove = 用法：db remove <数据库ID>
+db-already-connected = 已经连接了数据库，请先用 db close 断开
+db-connecting = 正在初始化数据库 { $path }
+db-connect-error = 连接数据库时出错: { $error }
+db-connected = 已连接到 { $path }
+db-not-connected = 没有连接数据库，请先用 db connect 连接
+db-closed = 已断开数据库连接
+db-read-error = 读取数据库时出错: { $error }
+db-status = 知识库 { $kb } 有 { $count } 条规则
+db-search-error = 查找数据库时出错: { $error }
+db-found = 在知识库 { $kb } 中找到 { $count } 条规则
+db-resetting = 正在重置数据库
+db-reset-error = 重置数据库时出错: { $error }
+db-reset = 数据库已重置
+db-kb-loading = 正在加载知识库 { $kb }
+db-kb-loaded = 已加载 { $rules } 条规则和 { $tests } 个测试用例
+db-syncing = 正在同步知识库 { $kb }
+db-save-rules-error = 保存规则时出错: { $error }
+db-save-tests-error = 保存测试用例时出错: { $error }
+db-save-labels-error = 保存事实说法时出错: { $error }
+db-synced = 数据库同步完成
+db-rule-inserted = 已把规则 #{ $id } 插入知识库 { $kb }
+db-insert-error = 插入规则时出错: { $error }
+db-rule-deleted = 已从知识库 { $kb } 删除规则 #{ $id }
+db-delete-error = 删除规则时出错: { $error }
+
+## 通用 (续)
+
+rule-parse-error = 解析规则时出错: { $error }
+invalid-rule-id = 无效的规则 ID { $id }: { $error }
+col-action = 操作
+col-detail = 详情
+col-time = 时间
+
+## 知识库
+
+usage-kb-create = 用法：kb create <名称>
+usage-kb-use = 用法：kb use <名称> [--force]
+usage-kb-copy = 用法：kb copy <源> <目标>
+usage-kb-delete = 用法：kb delete <名称>
+kb-create-error = 创建知识库时出错: { $error }
+kb-created = 已创建知识库 { $kb }
+kb-missing = 知识库 { $kb } 不存在，请用 kb create 创建
+kb-using = 正在使用知识库 { $kb }
+kb-unsynced = 知识库 { $kb } 有尚未同步的修改，请先运行 db sync，或用 kb use --force 放弃这些修改
+kb-copy-error = 复制知识库时出错: { $error }
+kb-copied = 已把知识库 { $from } 复制到 { $to }
+kb-in-use = 知识库 { $kb } 正在使用，请先用 kb use 切换到其他知识库
+kb-delete-error = 删除知识库时出错: { $error }
+kb-deleted = 已删除知识库 { $kb }
+col-rules = 规则数
+col-in-use = 使用中
+
+## 测试用例
+
+usage-kb-test-add = 用法：kb test add <名称> <给定事实> <应推出事实> [不应推出事实]
+usage-kb-test-remove = 用法：kb test remove <序号>
+invalid-fact = 无效的事实 { $fact }
+test-needs-expectation = 测试用例需要应推出或不应推出的事实
+test-added = 已添加测试用例 { $name }，共 { $count } 个
+test-removed = 已删除测试用例 { $name }
+test-missing = 测试用例 { $id } 不存在
+invalid-test-id = 无效的测试用例序号 { $id }: { $error }
+test-none = 没有测试用例，可以用 kb test add 添加或加载 .rules 文件
+test-pass = PASS { $name }
+test-fail = FAIL { $name }
+test-summary = { $passed } 个通过，{ $failed } 个失败
+test-failed-count = { $total } 个测试用例中有 { $failed } 个失败
+test-not-derived = 没有推出: { $facts }
+test-unwanted = 推出了不应推出的事实: { $facts }
+test-derived = 推出的事实: { $facts }
+test-no-rule-concludes = 没有规则能推出 { $fact }
+test-could-come-from = { $fact } 可以由这些规则推出: { $rules }
+col-given = 给定
+col-expect = 应推出
+col-not = 不应推出
+col-cycle = 轮次
+col-rule = 规则
+col-added = 新增
+
+## 场景和推论记录
+
+usage-scenario-save = 用法：scenario save <名称> [事实]
+usage-scenario-load = 用法：scenario load <名称>
+usage-scenario-run = 用法：scenario run <名称>
+usage-runs-show = 用法：runs show <运行ID>
+scenario-no-facts = 没有可保存的事实，请在参数中给出或先运行 test
+scenario-save-error = 保存场景时出错: { $error }
+scenario-saved = 已保存场景 { $name }，事实 { $facts }
+scenario-loaded = 已载入场景 { $name }，事实 { $facts }，用 test 运行
+scenario-missing = 场景 { $name } 不存在
+scenario-first-run = 第一次运行场景 { $name }，推出: { $derived }
+scenario-unchanged = 结论与运行 #{ $id } 相同
+scenario-changed = 结论与运行 #{ $id } ({ $time }) 相比发生了变化:
+invalid-run-id = 无效的运行 ID { $id }: { $error }
+run-missing = 运行 #{ $id } 不存在
+run-title = 运行 #{ $id }，知识库 { $kb }，时间 { $time }
+run-scenario = 场景: { $scenario }
+run-input = 输入事实: { $facts }
+derived-facts = 推出的事实: { $facts }
+run-cycles = 轮数: { $cycles }
+run-recorded = 已记录运行 #{ $id }
+run-record-error = 记录运行时出错: { $error }
+col-facts = 事实
+col-scenario = 场景
+col-input = 输入
+col-derived = 推出
+col-cycles = 轮数
+col-rules-fired = 触发规则数
+
+## 批量推论
+
+usage-batch = 用法：batch <案例文件> [--output <结果文件>]
+batch-unknown-option = 未知选项: { $option }，可用 --output <文件>
+batch-read-error = 读取案例时出错: { $error }
+batch-write-error = 写入结果时出错: { $error }
+batch-written = 已把结果写入 { $path }
+batch-failed-count = { $total } 个案例中有 { $failed } 个失败
+batch-pass = 通过
+batch-fail = 失败
+batch-missing = 缺少 { $facts }
+batch-unexpected = 多出 { $facts }
+batch-summary = { $passed } 个通过，{ $failed } 个失败，{ $unlabelled } 个没有预期结论
+col-line = 行
+col-expected = 预期
+col-result = 结果
+col-conclusion = 结论
+col-precision = 精确率
+col-recall = 召回率
+
+## 文件
+
+file-load-error = 加载 { $path } 时出错: { $error }
+file-loaded = 已从 { $path } 加载 { $rules } 条规则和 { $tests } 个测试用例
+file-save-error = 保存 { $path } 时出错: { $error }
+file-saved = 已把 { $rules } 条规则和 { $tests } 个测试用例保存到 { $path }
+
+## 撤销和重做
+
+journal-undid = 已撤销 { $action }
+journal-nothing-to-undo = 没有可以撤销的修改
+journal-redid = 已重做 { $action }
+journal-nothing-to-redo = 没有可以重做的修改
+journal-empty = 本次会话还没有修改
+journal-undone = 已撤销
+col-state = 状态
+
+## 推理树
+
+usage-proof = 用法：proof <事实> [--dot|--json] [文件]
+proof-nothing-deduced = 还没有推论，请先运行 test
+proof-not-reached = 上一次推论没有推出 { $fact }
+proof-written = 已把 { $fact } 的推理树写入 { $path }
+
+## 覆盖率
+
+coverage-reset = 覆盖率已清空
+coverage-empty = 还没有记录，请先运行 test、scenario run、batch 或 kb test
+coverage-not-evaluated = 从未求值
+coverage-summary = { $total } 条规则中有 { $never_fired } 条从未触发
+coverage-only-true = { $condition }: 只为真
+coverage-only-false = { $condition }: 只为假
+coverage-never-evaluated = { $condition }: 从未求值
+col-evaluated = 检查
+col-matched = 满足
+col-fired = 触发
+col-branches = 分支
+col-uncovered = 未覆盖
+
+## 调试
+
+usage-break = 用法：break rule <规则ID> | break fact <事实> | break clear
+debug-stopped = 已结束调试
+debug-inactive = 没有在调试，用 debug <事实> 开始
+debug-started = 从事实 { $facts } 开始调试，使用 step、next-cycle 或 continue
+debug-finished = 推论已经结束，用 debug <事实> 重新开始
+debug-breakpoints-cleared = 已清除断点
+debug-breakpoint-set = 已设置断点: { $breakpoint }
+debug-breakpoint-rule = 规则 #{ $id }
+debug-breakpoint-fact = 事实 { $fact }
+debug-watches-cleared = 已清除监视
+debug-watching = 正在监视 { $fact }
+debug-fired = 第 { $cycle } 轮: 规则 #{ $rule } 触发，新增 { $added }
+debug-watch-derived = 监视: { $fact } 由规则 #{ $rule } 推出
+debug-skipped = 规则 #{ $rule } 没有新增事实
+debug-cycle-changed = 第 { $cycle } 轮结束，新增了事实
+debug-cycle-unchanged = 第 { $cycle } 轮结束，没有新事实
+debug-stopped-at = 停在断点 { $breakpoint }
+debug-complete = 推论完成
+debug-facts = 第 { $cycle } 轮，事实: { $facts }
+debug-watch-known = 监视: { $fact } 已知
+debug-watch-unknown = 监视: { $fact } 未知
+debug-nothing-pending = 没有即将触发的规则
+debug-pending = 即将触发:
+col-why = 原因
+
+## 全屏界面
+
+tui-keys = Tab: 切换窗格  /: 搜索  Enter: 编辑  n: 新建  d: 删除  q: 退出
+tui-removed = 已删除规则 #{ $id }
+tui-not-saved = 未保存: { $error }
+tui-updated = 已修改规则 #{ $id }
+tui-added = 已添加规则 #{ $id }
+tui-facts = 事实
+tui-derived = 推出
+tui-db = 数据库: { $kb }
+tui-no-db = 未连接数据库
+tui-rule-count = { $count } 条规则
+tui-rules = 规则
+tui-edit = 修改规则 #{ $id }
+tui-new = 新规则
+tui-valid = 正确，Enter 保存
+tui-parse-error = 第 { $position } 个字符: { $error }
+tui-cycle = 第 { $cycle } 轮: { $firings }
+tui-nothing-derived = 没有推出任何事实
+
+## 解释
+
+explain-fired = 因为 { $condition } 成立，所以得出 { $outputs }
+explain-done = 推理完成，共 { $cycles } 轮，事实: { $facts }
+explain-given = { $fact }（已知）
+explain-absent = 没有 { $fact }（规则触发时未知）
+explain-derived = { $fact }（规则 #{ $rule }，第 { $cycle } 轮: 如果 { $condition }）
+explain-known = 已知 { $facts }
+explain-unknown = 未知 { $facts }
+explain-and = 并且
+explain-or = 或者
+explain-not = 不是
+
+## 事实说法
+
+label-set = 已设置 { $fact } 的说法: { $label }
+label-removed = 已删除 { $fact } 的说法
+label-missing = { $fact } 没有说法
+label-invalid = 无效的事实名: { $fact }
+label-none = 没有任何说法
+col-fact = 事实
+col-label = 说法
+col-template = 解释模板
+
+## 配置文件
+
+config-read-error = 无法读取配置文件 { $path }
+config-parse-error = 配置文件 { $path } 有误
+config-unknown-lang = 配置文件中的语言未知: { $lang }，可选 zh-CN 或 en-US
+config-invalid-log-level = 配置文件中的日志级别无效: { $level }
+cli-config = 配置文件，默认为 { $path }
+cli-config-value = 文件
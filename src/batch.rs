@@ -0,0 +1,358 @@
+//! Evaluation of a rule base against labelled cases.
+//!
+//! Cases are read from CSV with `input` and optional `name` and `expected`
+//! columns holding facts separated by spaces or commas, or from JSON Lines
+//! with the same fields as lists. Only facts expected by at least one case
+//! count as conclusions, so intermediate facts a case doesn't mention don't
+//! make it fail.
+
+use crate::coverage::Coverage;
+use crate::error::{Error, ImportError, LineError, Result};
+use crate::rule::{Facts, Options, Rule};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Case {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(deserialize_with = "facts")]
+    pub input: Vec<String>,
+    /// `None` when the case isn't labelled, it is then run but not judged.
+    #[serde(default, deserialize_with = "optional_facts")]
+    pub expected: Option<Vec<String>>,
+}
+
+/// The outcome of one case.
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseResult {
+    pub line: usize,
+    pub name: Option<String>,
+    pub input: Vec<String>,
+    pub expected: Option<Vec<String>>,
+    pub derived: Vec<String>,
+    /// `None` for cases without expected conclusions.
+    pub passed: Option<bool>,
+    /// Expected conclusions that weren't derived.
+    pub missing: Vec<String>,
+    /// Conclusions derived without being expected.
+    pub unexpected: Vec<String>,
+}
+
+/// How one conclusion fared over all labelled cases.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Confusion {
+    pub conclusion: String,
+    pub true_positive: usize,
+    pub false_positive: usize,
+    pub false_negative: usize,
+    pub true_negative: usize,
+}
+
+impl Confusion {
+    pub fn precision(&self) -> Option<f64> {
+        let predicted = self.true_positive + self.false_positive;
+        (predicted > 0).then(|| self.true_positive as f64 / predicted as f64)
+    }
+
+    pub fn recall(&self) -> Option<f64> {
+        let actual = self.true_positive + self.false_negative;
+        (actual > 0).then(|| self.true_positive as f64 / actual as f64)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub cases: Vec<CaseResult>,
+    pub confusion: Vec<Confusion>,
+    /// Rule coverage over all cases.
+    #[serde(skip)]
+    pub coverage: Coverage,
+}
+
+impl Report {
+    pub fn passed(&self) -> usize {
+        self.cases.iter().filter(|case| case.passed == Some(true)).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.cases.iter().filter(|case| case.passed == Some(false)).count()
+    }
+}
+
+/// Reads cases with the line each one starts on.
+pub fn read_cases(path: &Path) -> Result<Vec<(usize, Case)>> {
+    let content = fs::read_to_string(path).map_err(|e| Error::from(e).in_file(path))?;
+    let ext = path.extension().and_then(|ext| ext.to_str()).map(|ext| ext.to_lowercase());
+    let cases = match ext.as_deref() {
+        Some("csv") => csv_cases(&content),
+        Some("jsonl") => jsonl_cases(&content),
+        _ => {
+            return Err(Error::Unsupported(format!(
+                "unknown case file format {}, use .csv or .jsonl",
+                path.display()
+            )))
+        }
+    };
+    cases.map_err(|e| Error::from(e).in_file(path))
+}
+
+fn csv_cases(content: &str) -> Result<Vec<(usize, Case)>, ImportError> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| {
+            ImportError(vec![LineError {
+                line: 1,
+                message: e.to_string(),
+            }])
+        })?
+        .clone();
+    let mut cases = vec![];
+    let mut errors = vec![];
+    for record in reader.records() {
+        let case = record.and_then(|record| {
+            let line = record.position().map(|p| p.line() as usize).unwrap_or(0);
+            Ok((line, record.deserialize::<Case>(Some(&headers))?))
+        });
+        match case {
+            Ok(case) => cases.push(case),
+            Err(e) => errors.push(LineError {
+                line: e.position().map(|p| p.line() as usize).unwrap_or(0),
+                message: e.to_string(),
+            }),
+        }
+    }
+    if errors.is_empty() {
+        Ok(cases)
+    } else {
+        Err(ImportError(errors))
+    }
+}
+
+fn jsonl_cases(content: &str) -> Result<Vec<(usize, Case)>, ImportError> {
+    let mut cases = vec![];
+    let mut errors = vec![];
+    for (idx, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Case>(line) {
+            Ok(case) => cases.push((idx + 1, case)),
+            Err(e) => errors.push(LineError {
+                line: idx + 1,
+                message: e.to_string(),
+            }),
+        }
+    }
+    if errors.is_empty() {
+        Ok(cases)
+    } else {
+        Err(ImportError(errors))
+    }
+}
+
+/// Runs every case through [`Facts::deduce_with`] and compares the
+/// conclusions.
+pub fn evaluate(rules: &[Rule], cases: &[(usize, Case)], options: &Options) -> Report {
+    let conclusions = cases
+        .iter()
+        .filter_map(|(_, case)| case.expected.as_ref())
+        .flatten()
+        .cloned()
+        .collect::<BTreeSet<_>>();
+    let mut confusion = conclusions
+        .iter()
+        .map(|conclusion| Confusion {
+            conclusion: conclusion.clone(),
+            ..Confusion::default()
+        })
+        .collect::<Vec<_>>();
+
+    let mut results = vec![];
+    let mut coverage = Coverage::default();
+    for (line, case) in cases {
+        let mut facts = Facts::from(case.input.clone());
+        facts.record_coverage();
+        facts.deduce_with(rules, options);
+        if let Some(case_coverage) = facts.coverage() {
+            coverage.merge(case_coverage);
+        }
+        let mut result = CaseResult {
+            line: *line,
+            name: case.name.clone(),
+            input: case.input.clone(),
+            expected: case.expected.clone(),
+            derived: facts.derived().to_vec(),
+            passed: None,
+            missing: vec![],
+            unexpected: vec![],
+        };
+        if let Some(expected) = &case.expected {
+            for entry in confusion.iter_mut() {
+                let want = expected.contains(&entry.conclusion);
+                let got = facts.contains(&entry.conclusion);
+                match (want, got) {
+                    (true, true) => entry.true_positive += 1,
+                    (false, true) => {
+                        entry.false_positive += 1;
+                        result.unexpected.push(entry.conclusion.clone());
+                    }
+                    (true, false) => {
+                        entry.false_negative += 1;
+                        result.missing.push(entry.conclusion.clone());
+                    }
+                    (false, false) => entry.true_negative += 1,
+                }
+            }
+            result.passed = Some(result.missing.is_empty() && result.unexpected.is_empty());
+        }
+        results.push(result);
+    }
+    Report {
+        cases: results,
+        confusion,
+        coverage,
+    }
+}
+
+#[derive(Serialize)]
+struct CsvResult<'a> {
+    line: usize,
+    name: &'a str,
+    input: String,
+    expected: String,
+    derived: String,
+    result: &'a str,
+    missing: String,
+    unexpected: String,
+}
+
+/// Writes the per-case results as CSV or JSON, chosen by extension.
+pub fn write_results(path: &Path, report: &Report) -> Result<()> {
+    let ext = path.extension().and_then(|ext| ext.to_str()).map(|ext| ext.to_lowercase());
+    let content = match ext.as_deref() {
+        Some("json") => serde_json::to_string_pretty(report)? + "\n",
+        Some("csv") => {
+            let mut writer = csv::Writer::from_writer(vec![]);
+            for case in &report.cases {
+                writer
+                    .serialize(CsvResult {
+                        line: case.line,
+                        name: case.name.as_deref().unwrap_or_default(),
+                        input: case.input.join(" "),
+                        expected: case.expected.as_ref().map(|e| e.join(" ")).unwrap_or_default(),
+                        derived: case.derived.join(" "),
+                        result: match case.passed {
+                            Some(true) => "pass",
+                            Some(false) => "fail",
+                            None => "",
+                        },
+                        missing: case.missing.join(" "),
+                        unexpected: case.unexpected.join(" "),
+                    })
+                    .map_err(Error::backend)?;
+            }
+            let bytes = writer.into_inner().map_err(|e| Error::backend(e.into_error()))?;
+            String::from_utf8(bytes).map_err(Error::backend)?
+        }
+        _ => {
+            return Err(Error::Unsupported(format!(
+                "unknown result file format {}, use .csv or .json",
+                path.display()
+            )))
+        }
+    };
+    fs::write(path, content).map_err(|e| Error::from(e).in_file(path))?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FactList {
+    Text(String),
+    List(Vec<String>),
+}
+
+impl From<FactList> for Vec<String> {
+    fn from(list: FactList) -> Self {
+        match list {
+            FactList::Text(text) => text
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|fact| !fact.is_empty())
+                .map(|fact| fact.to_string())
+                .collect(),
+            FactList::List(list) => list,
+        }
+    }
+}
+
+fn facts<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(FactList::deserialize(deserializer)?.into())
+}
+
+/// An empty `expected` cell means the case isn't labelled.
+fn optional_facts<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<String>>, D::Error> {
+    Ok(Option::<FactList>::deserialize(deserializer)?
+        .map(Vec::from)
+        .filter(|facts| !facts.is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate() {
+        let rules = vec![
+            Rule::new("fur", "mammal").unwrap(),
+            Rule::new("mammal & stripes", "tiger").unwrap(),
+            Rule::new("mammal & spots", "cheetah").unwrap(),
+        ];
+        let csv = "name,input,expected\ntiger,fur stripes,tiger\nspotted,\"fur,spots\",tiger\nbare,fur,\n";
+        let cases = csv_cases(csv).unwrap();
+        assert_eq!(cases[1], (3, cases[1].1.clone()));
+        assert_eq!(cases[1].1.input, ["fur", "spots"]);
+        assert_eq!(cases[2].1.expected, None);
+
+        let report = evaluate(&rules, &cases, &Options::default());
+        assert_eq!(report.passed(), 1);
+        assert_eq!(report.failed(), 1);
+        assert_eq!(report.cases[1].missing, ["tiger"]);
+        assert!(report.cases[1].unexpected.is_empty());
+        assert_eq!(
+            report.confusion,
+            [Confusion {
+                conclusion: "tiger".to_string(),
+                true_positive: 1,
+                false_positive: 0,
+                false_negative: 1,
+                true_negative: 0,
+            }]
+        );
+
+        let jsonl = "{\"input\": [\"fur\", \"spots\"], \"expected\": [\"cheetah\"]}\n";
+        let report = evaluate(&rules, &jsonl_cases(jsonl).unwrap(), &Options::default());
+        assert_eq!(report.passed(), 1);
+    }
+
+    #[test]
+    fn test_evaluate_with_options() {
+        // With `b -> c` first, `a` needs a second cycle to reach `c`
+        let rules = vec![Rule::new("b", "c").unwrap(), Rule::new("a", "b").unwrap()];
+        let cases = csv_cases("input,expected\nb,c\na,c\n").unwrap();
+        let options = Options {
+            max_cycles: Some(1),
+            ..Options::default()
+        };
+        let report = evaluate(&rules, &cases, &options);
+        assert_eq!(report.passed(), 1);
+        assert_eq!(report.cases[1].missing, ["c"]);
+        assert_eq!(report.cases[1].derived, ["b"]);
+        assert_eq!(evaluate(&rules, &cases, &Options::default()).passed(), 2);
+    }
+}
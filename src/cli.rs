@@ -0,0 +1,218 @@
+use crate::command::handle_command;
+use crate::config;
+use crate::output::OutputFormat;
+use crate::Context;
+use clap::builder::{PossibleValue, PossibleValuesParser, TypedValueParser};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use std::io::{BufRead, IsTerminal};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use tracing::error;
+
+/// 专家系统，不带参数时进入交互模式
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Args {
+    /// 执行一条命令，可以重复使用，按顺序执行
+    #[arg(short, long = "eval", value_name = "命令")]
+    pub eval: Vec<String>,
+
+    /// 输出格式，json 时每条命令在标准输出写一个 JSON 文档，日志写到标准错误
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// 配置文件
+    #[arg(long, value_name = "文件")]
+    pub config: Option<PathBuf>,
+
+    /// 以全屏界面启动
+    #[cfg(feature = "tui")]
+    #[arg(long, conflicts_with = "eval")]
+    pub tui: bool,
+
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CliCommand {
+    /// 逐行执行脚本文件中的命令，`-` 表示标准输入
+    Run {
+        #[arg(value_name = "脚本")]
+        script: String,
+    },
+}
+
+/// Where the commands come from.
+pub enum Mode {
+    Interactive,
+    #[cfg(feature = "tui")]
+    Tui,
+    Script(String),
+    Eval(Vec<String>),
+    Stdin,
+}
+
+impl Args {
+    /// Parses the command line with the help texts in the current language.
+    pub fn parse_localized() -> Args {
+        let formats = PossibleValuesParser::new([
+            PossibleValue::new("text").help(t!("cli-format-text")),
+            PossibleValue::new("json").help(t!("cli-format-json")),
+        ])
+        .map(|format| OutputFormat::parse(&format).unwrap_or_default());
+        let command = Args::command()
+            .about(t!("cli-about"))
+            .mut_arg("eval", |arg| arg.help(t!("cli-eval")).value_name(t!("cli-eval-value")))
+            .mut_arg("format", |arg| arg.help(t!("cli-format")).value_parser(formats))
+            .mut_arg("config", |arg| {
+                let path = config::default_path().unwrap_or_default();
+                arg.help(t!("cli-config", path = path.display())).value_name(t!("cli-config-value"))
+            })
+            .mut_subcommand("run", |run| {
+                run.about(t!("cli-run"))
+                    .mut_arg("script", |arg| arg.value_name(t!("cli-run-script")))
+            });
+        #[cfg(feature = "tui")]
+        let command = command.mut_arg("tui", |arg| arg.help(t!("cli-tui")));
+        let matches = command.get_matches();
+        Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit())
+    }
+
+    pub fn mode(self) -> Mode {
+        #[cfg(feature = "tui")]
+        if self.tui {
+            return Mode::Tui;
+        }
+        match self.command {
+            Some(CliCommand::Run { script }) if script == "-" => Mode::Stdin,
+            Some(CliCommand::Run { script }) => Mode::Script(script),
+            None if !self.eval.is_empty() => Mode::Eval(self.eval),
+            None if !std::io::stdin().is_terminal() => Mode::Stdin,
+            None => Mode::Interactive,
+        }
+    }
+}
+
+/// Exit code of a script whose command failed, usage errors exit with 2.
+const COMMAND_FAILED: u8 = 1;
+
+/// Runs commands without prompts, stopping at the first one that fails.
+/// `source` names where the lines came from in error messages.
+pub async fn run_lines<I>(source: &str, lines: I, ctx: &mut Context) -> ExitCode
+where
+    I: IntoIterator<Item = std::io::Result<String>>,
+{
+    for (line, text) in (1..).zip(lines) {
+        let text = match text {
+            Ok(text) => text,
+            Err(e) => {
+                error!("{}", t!("script-read-error", source = source, error = e));
+                return ExitCode::from(2);
+            }
+        };
+        let text = text.trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+        match handle_command(text.to_string(), ctx).await {
+            Ok(true) => break,
+            Ok(false) => {}
+            Err(_) => {
+                error!("{}", t!("script-command-failed", source = source, line = line, command = text));
+                return ExitCode::from(COMMAND_FAILED);
+            }
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+pub async fn run_script(path: &str, ctx: &mut Context) -> ExitCode {
+    match std::fs::File::open(path) {
+        Ok(file) => run_lines(path, std::io::BufReader::new(file).lines(), ctx).await,
+        Err(e) => {
+            error!("{}", t!("script-open-error", path = path, error = e));
+            ExitCode::from(2)
+        }
+    }
+}
+
+pub async fn run_stdin(ctx: &mut Context) -> ExitCode {
+    run_lines("<stdin>", std::io::stdin().lock().lines(), ctx).await
+}
+
+pub async fn run_eval(commands: Vec<String>, ctx: &mut Context) -> ExitCode {
+    run_lines("-e", commands.into_iter().map(Ok), ctx).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expert::store::DEFAULT_KB;
+
+    #[test]
+    fn test_modes() {
+        let args = Args::try_parse_from(["expert", "-e", "rule list", "--eval", "test a"]).unwrap();
+        assert!(matches!(args.mode(), Mode::Eval(commands) if commands == ["rule list", "test a"]));
+        let args = Args::try_parse_from(["expert", "run", "check.exp"]).unwrap();
+        assert!(matches!(args.mode(), Mode::Script(path) if path == "check.exp"));
+        let args = Args::try_parse_from(["expert", "run", "-"]).unwrap();
+        assert!(matches!(args.mode(), Mode::Stdin));
+    }
+
+    #[tokio::test]
+    async fn test_run_lines_fails_without_logging() {
+        // No subscriber is installed here, a failure is noticed anyway
+        let mut ctx = Context::new();
+        let commands = vec!["rule add a b".to_string(), "rule remove 5".to_string()];
+        assert_eq!(run_eval(commands, &mut ctx).await, ExitCode::from(COMMAND_FAILED));
+        assert_eq!(run_eval(vec!["rule list".to_string()], &mut ctx).await, ExitCode::SUCCESS);
+    }
+
+    #[tokio::test]
+    async fn test_batch_failures() {
+        let path = std::env::temp_dir().join(format!("expert-batch-{}.csv", std::process::id()));
+        let batch = format!("batch {}", path.display());
+        let mut ctx = Context::new();
+        ctx.add_rule("fur", "mammal").unwrap();
+        std::fs::write(&path, "input,expected\nfur,mammal\n").unwrap();
+        let passed = run_eval(vec![batch.clone()], &mut ctx).await;
+        let missing = run_eval(vec![format!("{} --output", batch)], &mut ctx).await;
+        std::fs::write(&path, "input,expected\nfur,mammal\nfeathers,mammal\n").unwrap();
+        let failed = run_eval(vec![batch], &mut ctx).await;
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(passed, ExitCode::SUCCESS);
+        assert_eq!(missing, ExitCode::from(COMMAND_FAILED));
+        assert_eq!(failed, ExitCode::from(COMMAND_FAILED));
+    }
+
+    #[tokio::test]
+    async fn test_kb_use_loads_rules() {
+        let mut ctx = Context::new();
+        let commands = ["db connect memory:", "kb create zoo", "rule add a b"];
+        assert_eq!(run_eval(commands.map(String::from).to_vec(), &mut ctx).await, ExitCode::SUCCESS);
+        // The rule isn't synced yet
+        let unsynced = run_eval(vec!["kb use zoo".to_string()], &mut ctx).await;
+        assert_eq!(unsynced, ExitCode::from(COMMAND_FAILED));
+        let commands = ["db sync", "kb use zoo", "db sync"];
+        assert_eq!(run_eval(commands.map(String::from).to_vec(), &mut ctx).await, ExitCode::SUCCESS);
+        assert!(ctx.rules.is_empty());
+        let db = ctx.db.as_ref().unwrap();
+        assert_eq!(db.load_rules(DEFAULT_KB).await.unwrap().len(), 1);
+        assert!(db.load_rules("zoo").await.unwrap().is_empty());
+
+        let commands = ["rule add c d", "kb use default --force"];
+        assert_eq!(run_eval(commands.map(String::from).to_vec(), &mut ctx).await, ExitCode::SUCCESS);
+        assert_eq!((ctx.kb.as_str(), ctx.rules.len()), (DEFAULT_KB, 1));
+    }
+
+    #[tokio::test]
+    async fn test_reset_uses_default_kb() {
+        let mut ctx = Context::new();
+        let commands = ["db connect memory:", "kb create zoo", "kb use zoo", "db reset"];
+        let commands = commands.map(String::from).to_vec();
+        assert_eq!(run_eval(commands, &mut ctx).await, ExitCode::SUCCESS);
+        assert_eq!(ctx.kb, DEFAULT_KB);
+        assert_eq!(run_eval(vec!["db load".to_string()], &mut ctx).await, ExitCode::SUCCESS);
+    }
+}
@@ -0,0 +1,121 @@
+use expert::batch::{evaluate, read_cases, write_results, Report};
+use crate::command::Outcome;
+use crate::Context;
+use crate::output::render_table;
+use colored::Colorize;
+use std::path::Path;
+use tracing::info;
+
+pub(crate) async fn handle_batch(seg: &[&str], ctx: &mut Context) -> Outcome {
+    match seg {
+        [_, "--output"] | [_, "--output", ""] => {
+            fail!("{}", t!("option-missing-value", option = "--output"))
+        }
+        [path, "--output", output, ..] => batch(path, Some(output), ctx)?,
+        [path] | [path, ""] => batch(path, None, ctx)?,
+        [_, x, ..] => fail!("{}", t!("batch-unknown-option", option = x)),
+        [] => fail!("{}", t!("usage-batch")),
+    }
+    Ok(())
+}
+
+fn batch(path: &str, output: Option<&str>, ctx: &mut Context) -> Outcome {
+    let cases = match read_cases(Path::new(path)) {
+        Ok(cases) => cases,
+        Err(e) => {
+            fail!("{}", t!("batch-read-error", error = e));
+        }
+    };
+    // Every case would log its deduction in English, keep them quiet
+    let report = tracing::subscriber::with_default(
+        tracing::subscriber::NoSubscriber::default(),
+        || evaluate(&ctx.rules, &cases, &ctx.engine),
+    );
+    ctx.coverage.merge(&report.coverage);
+    if let Some(output) = output {
+        if let Err(e) = write_results(Path::new(output), &report) {
+            fail!("{}", t!("batch-write-error", error = e));
+        }
+        info!("{}", t!("batch-written", path = output));
+    }
+    let text = report_text(&report);
+    ctx.print(&report, || text);
+    if report.failed() > 0 {
+        fail!("{}", t!("batch-failed-count", failed = report.failed(), total = report.cases.len()));
+    }
+    Ok(())
+}
+
+fn report_text(report: &Report) -> String {
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record([
+        t!("col-line"),
+        t!("col-name"),
+        t!("col-input"),
+        t!("col-expected"),
+        t!("col-derived"),
+        t!("col-result"),
+    ]);
+    for case in &report.cases {
+        let result = match case.passed {
+            Some(true) => t!("batch-pass").green().to_string(),
+            Some(false) => {
+                let mut reason = vec![];
+                if !case.missing.is_empty() {
+                    reason.push(t!("batch-missing", facts = case.missing.join(" ")));
+                }
+                if !case.unexpected.is_empty() {
+                    reason.push(t!("batch-unexpected", facts = case.unexpected.join(" ")));
+                }
+                format!("{} ({})", t!("batch-fail").red(), reason.join(", "))
+            }
+            None => "-".to_string(),
+        };
+        builder.push_record([
+            case.line.to_string(),
+            case.name.clone().unwrap_or_default(),
+            case.input.join(" "),
+            case.expected.as_ref().map(|e| e.join(" ")).unwrap_or_default(),
+            case.derived.join(" "),
+            result,
+        ]);
+    }
+    let unlabelled = report.cases.len() - report.passed() - report.failed();
+    let mut text = render_table(builder);
+    text.push('\n');
+    text.push_str(&t!(
+        "batch-summary",
+        passed = report.passed(),
+        failed = report.failed(),
+        unlabelled = unlabelled
+    ));
+    text.push('\n');
+    if report.confusion.is_empty() {
+        return text;
+    }
+
+    let ratio = |value: Option<f64>| value.map(|v| format!("{:.2}", v)).unwrap_or("-".into());
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record([
+        t!("col-conclusion"),
+        "TP".to_string(),
+        "FP".to_string(),
+        "FN".to_string(),
+        "TN".to_string(),
+        t!("col-precision"),
+        t!("col-recall"),
+    ]);
+    for entry in &report.confusion {
+        builder.push_record([
+            entry.conclusion.clone(),
+            entry.true_positive.to_string(),
+            entry.false_positive.to_string(),
+            entry.false_negative.to_string(),
+            entry.true_negative.to_string(),
+            ratio(entry.precision()),
+            ratio(entry.recall()),
+        ]);
+    }
+    text.push_str(&render_table(builder));
+    text
+}
@@ -0,0 +1,91 @@
+use crate::command::Outcome;
+use crate::Context;
+use crate::output::render_table;
+use colored::Colorize;
+use expert::coverage::Branch;
+use serde_json::json;
+use tracing::{info, warn};
+
+pub(crate) fn handle_coverage(seg: &[&str], ctx: &mut Context) -> Outcome {
+    match seg {
+        [] | [""] => coverage(ctx),
+        ["reset", ..] => {
+            ctx.coverage = Default::default();
+            info!("{}", t!("coverage-reset"));
+        }
+        [x, ..] => fail!("{}", t!("unknown-subcommand", subcommand = x)),
+    }
+    Ok(())
+}
+
+fn coverage(ctx: &mut Context) {
+    if ctx.coverage.is_empty() {
+        warn!("{}", t!("coverage-empty"));
+    }
+    let mut entries = vec![];
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record([
+        t!("col-id"),
+        t!("col-rule"),
+        t!("col-evaluated"),
+        t!("col-matched"),
+        t!("col-fired"),
+        t!("col-branches"),
+        t!("col-uncovered"),
+    ]);
+    let mut never_fired = 0;
+    for (i, rule) in ctx.rules.iter().enumerate() {
+        let coverage = ctx.coverage.get(i, rule);
+        let (evaluated, matched, fired) = coverage
+            .map(|c| (c.evaluated, c.matched, c.fired))
+            .unwrap_or_default();
+        let (covered, total) = coverage.map(|c| c.branch_coverage()).unwrap_or_default();
+        let uncovered = coverage
+            .map(|c| c.branches.iter().filter_map(uncovered).collect::<Vec<_>>())
+            .unwrap_or_else(|| vec![t!("coverage-not-evaluated")]);
+        entries.push(json!({
+            "id": i,
+            "rule": rule.to_string(),
+            "evaluated": evaluated,
+            "matched": matched,
+            "fired": fired,
+            "never_fired": fired == 0,
+            "branches": coverage.map(|c| c.branches.clone()).unwrap_or_default(),
+            "branches_covered": covered,
+            "branches_total": total,
+        }));
+        let mut text = rule.to_string();
+        if fired == 0 {
+            never_fired += 1;
+            text = text.red().to_string();
+        }
+        let branches = if total == 0 {
+            "-".to_string()
+        } else {
+            format!("{}/{} ({:.0}%)", covered, total, 100.0 * covered as f64 / total as f64)
+        };
+        builder.push_record([
+            i.to_string(),
+            text,
+            evaluated.to_string(),
+            matched.to_string(),
+            fired.to_string(),
+            branches,
+            uncovered.join("\n"),
+        ]);
+    }
+    let table = render_table(builder);
+    let summary = t!("coverage-summary", never_fired = never_fired, total = ctx.rules.len());
+    let value = json!({ "rules": entries, "never_fired": never_fired });
+    ctx.print(value, || format!("{}\n{}", table, summary));
+}
+
+/// Describes a subexpression that didn't take both outcomes.
+fn uncovered(branch: &Branch) -> Option<String> {
+    match (branch.true_count > 0, branch.false_count > 0) {
+        (true, true) => None,
+        (true, false) => Some(t!("coverage-only-true", condition = branch.condition)),
+        (false, true) => Some(t!("coverage-only-false", condition = branch.condition)),
+        (false, false) => Some(t!("coverage-never-evaluated", condition = branch.condition)),
+    }
+}
@@ -1,135 +1,276 @@
-use crate::command::handle_help;
+use crate::command::{handle_help, Outcome};
+use crate::command::rule::{found_table, parse_query};
+use expert::rule::Rule;
+use expert::store::DEFAULT_KB;
 use crate::Context;
-use tabled::settings::object::Rows;
-use tabled::settings::{Alignment, Style};
-use tracing::{error, info};
+use crate::output::render_table;
+use serde_json::json;
+use tracing::info;
 
-pub(crate) async fn handle_db(seg: &[&str], ctx: &mut Context) {
+pub(crate) async fn handle_db(seg: &[&str], ctx: &mut Context) -> Outcome {
     match seg {
         ["connect", ..] => {
             if let Some(path) = seg.get(1) {
-                connect(path, ctx).await;
+                connect(path, ctx).await?;
             } else {
-                error!("用法：connect <路径>")
+                fail!("{}", t!("usage-db-connect"))
             }
         }
         ["close", ..] => {
-            close(ctx).await;
+            close(ctx).await?;
         }
         ["status", ..] => {
-            status(ctx).await;
+            status(ctx).await?;
         }
         ["reset", ..] => {
-            reset(ctx).await;
+            reset(ctx).await?;
         }
         ["load", ..] => {
-            load(ctx).await;
+            load(ctx).await?;
         }
         ["sync", ..] => {
-            sync(ctx).await;
+            sync(ctx).await?;
+        }
+        ["add", condition, output, ..] => {
+            add(condition, output, ctx).await?;
+        }
+        ["add", ..] => {
+            fail!("{}", t!("usage-db-add"));
+        }
+        ["remove", id, ..] => {
+            remove(id, ctx).await?;
+        }
+        ["remove", ..] => {
+            fail!("{}", t!("usage-db-remove"));
+        }
+        ["history", ..] => {
+            history(ctx).await?;
+        }
+        ["find", args @ ..] => {
+            find(args, ctx).await?;
         }
         [] => {
-            handle_help(&["db"]).await;
+            handle_help(&["db"], ctx)?;
         }
         [x, ..] => {
-            error!("未知子命令: {}", x)
+            fail!("{}", t!("unknown-subcommand", subcommand = x))
         }
     }
+    Ok(())
+}
+/// `db connect` followed by `db load`, for the database of the config file.
+pub(crate) async fn connect_and_load(url: &str, ctx: &mut Context) -> Outcome {
+    connect(url, ctx).await?;
+    load(ctx).await
 }
-async fn connect(path: &str, ctx: &mut Context) {
+
+async fn connect(path: &str, ctx: &mut Context) -> Outcome {
     if ctx.db.is_some() {
-        error!("db connection is already established, close it with db close");
-        return;
+        fail!("{}", t!("db-already-connected"));
     }
-    info!("Initializing database {}", path);
+    info!("{}", t!("db-connecting", path = path));
 
     let result = ctx.connect(path).await;
     if let Err(e) = result {
-        error!("Error while creating connection: {}", e)
+        fail!("{}", t!("db-connect-error", error = e))
     } else {
-        info!("Successfully established connection to {}", path);
+        info!("{}", t!("db-connected", path = path));
     }
+    Ok(())
 }
 
-async fn close(ctx: &mut Context) {
+async fn close(ctx: &mut Context) -> Outcome {
     if ctx.db.is_none() {
-        error!("No established db connection, use db connect first");
-        return;
+        fail!("{}", t!("db-not-connected"));
     }
     ctx.db = None;
-    info!("Successfully closed connection");
+    info!("{}", t!("db-closed"));
+    Ok(())
 }
 
-async fn status(ctx: &mut Context) {
+async fn status(ctx: &mut Context) -> Outcome {
     let Some(db) = ctx.db.as_ref() else {
-        error!("No established db connection, use db connect first");
-        return;
+        fail!("{}", t!("db-not-connected"));
     };
-    let rules = db.load_rules_raw().await;
+    let rules = db.load_rules_raw(&ctx.kb).await;
     let rules = match rules {
         Ok(r) => r,
         Err(e) => {
-            error!("Error while reading db: {}", e);
-            return;
+            fail!("{}", t!("db-read-error", error = e));
         }
     };
+    info!("{}", t!("db-status", kb = ctx.kb, count = rules.len()));
+    let value = json!({
+        "kb": ctx.kb,
+        "rules": rules
+            .iter()
+            .map(|(id, condition, output)| {
+                json!({ "id": id, "condition": condition, "output": output.split(',').collect::<Vec<_>>() })
+            })
+            .collect::<Vec<_>>(),
+    });
     let mut builder = tabled::builder::Builder::default();
-    builder.push_record(["id", "condition", "output"]);
+    builder.push_record([t!("col-id"), t!("col-condition"), t!("col-output")]);
     for rule in rules {
         builder.push_record([rule.0.to_string(), rule.1, rule.2]);
     }
-    let table = builder
-        .build()
-        .with(Style::rounded())
-        .modify(Rows::new(1..), Alignment::left())
-        .to_string();
-    println!("{}", table);
+    let table = render_table(builder);
+    ctx.print(value, || table);
+    Ok(())
 }
 
-async fn reset(ctx: &mut Context) {
+async fn find(args: &[&str], ctx: &mut Context) -> Outcome {
+    let query = match parse_query(args) {
+        Ok(query) => query,
+        Err(e) => {
+            fail!("{}", e);
+        }
+    };
     let Some(db) = ctx.db.as_ref() else {
-        error!("No established db connection, use db connect first");
-        return;
+        fail!("{}", t!("db-not-connected"));
+    };
+    let rules = match db.find_rules(&ctx.kb, &query).await {
+        Ok(rules) => rules,
+        Err(e) => {
+            fail!("{}", t!("db-search-error", error = e));
+        }
     };
-    info!("Resetting database");
+    info!("{}", t!("db-found", count = rules.len(), kb = ctx.kb));
+    let value = json!(rules
+        .iter()
+        .map(|(id, rule)| json!({ "id": id, "rule": rule }))
+        .collect::<Vec<_>>());
+    let table = found_table(&query, rules.iter().map(|(id, rule)| (id.to_string(), rule)));
+    ctx.print(value, || table);
+    Ok(())
+}
+
+async fn reset(ctx: &mut Context) -> Outcome {
+    let Some(db) = ctx.db.as_ref() else {
+        fail!("{}", t!("db-not-connected"));
+    };
+    info!("{}", t!("db-resetting"));
     if let Err(e) = db.reset().await {
-        error!("Error while resetting db: {}", e);
+        fail!("{}", t!("db-reset-error", error = e));
     } else {
-        info!("Database reset complete");
+        info!("{}", t!("db-reset"));
     };
+    // Only the default knowledge base is left
+    ctx.kb = DEFAULT_KB.to_string();
+    info!("{}", t!("kb-using", kb = ctx.kb));
+    Ok(())
 }
 
-async fn load(ctx: &mut Context) {
+async fn load(ctx: &mut Context) -> Outcome {
+    let kb = ctx.kb.clone();
+    load_kb(&kb, ctx).await
+}
+
+/// Replaces the rules, tests and labels with those of `kb`, nothing
+/// changes if one of them can't be read.
+pub(crate) async fn load_kb(kb: &str, ctx: &mut Context) -> Outcome {
     let Some(db) = ctx.db.as_ref() else {
-        error!("No established db connection, use db connect first");
-        return;
+        fail!("{}", t!("db-not-connected"));
     };
-    info!("Loading database");
-    let rules = db.load_rules().await;
+    info!("{}", t!("db-kb-loading", kb = kb));
+    let rules = db.load_rules(kb).await;
     let rules = match rules {
         Ok(rules) => {rules}
         Err(e) => {
-            error!("Error while reading db: {}", e);
-            return;
+            fail!("{}", t!("db-read-error", error = e));
         }
     };
+    let tests = match db.load_tests(kb).await {
+        Ok(tests) => tests,
+        Err(e) => {
+            fail!("{}", t!("db-read-error", error = e));
+        }
+    };
+    let labels = match db.load_labels(kb).await {
+        Ok(labels) => labels,
+        Err(e) => {
+            fail!("{}", t!("db-read-error", error = e));
+        }
+    };
+    ctx.record(format!("db load {}", kb));
     ctx.rules = rules;
-    info!("Successfully loaded {} rules", ctx.rules.len());
+    ctx.tests = tests;
+    ctx.labels = labels;
+    ctx.unsynced = false;
+    info!("{}", t!("db-kb-loaded", rules = ctx.rules.len(), tests = ctx.tests.len()));
+    Ok(())
 }
 
-async fn sync(ctx: &mut Context) {
+async fn sync(ctx: &mut Context) -> Outcome {
     let Some(db) = ctx.db.as_ref() else {
-        error!("No established db connection, use db connect first");
-        return;
+        fail!("{}", t!("db-not-connected"));
     };
-    info!("Syncing database");
-    if let Err(e) = db.reset().await {
-        error!("Error while resetting db: {}", e);
-        return;
+    info!("{}", t!("db-syncing", kb = ctx.kb));
+    if let Err(e) = db.save_rules(&ctx.kb, &ctx.rules).await {
+        fail!("{}", t!("db-save-rules-error", error = e));
     }
-    if let Err(e) = db.save_rules(&ctx.rules).await {
-        error!("Error while saving rules: {}", e);
-        return;
+    if let Err(e) = db.save_tests(&ctx.kb, &ctx.tests).await {
+        fail!("{}", t!("db-save-tests-error", error = e));
     }
-    info!("Database sync complete");
-}
\ No newline at end of file
+    if let Err(e) = db.save_labels(&ctx.kb, &ctx.labels).await {
+        fail!("{}", t!("db-save-labels-error", error = e));
+    }
+    ctx.unsynced = false;
+    info!("{}", t!("db-synced"));
+    Ok(())
+}
+
+async fn add(condition: &str, output: &str, ctx: &mut Context) -> Outcome {
+    let Some(db) = ctx.db.as_ref() else {
+        fail!("{}", t!("db-not-connected"));
+    };
+    let rule = match Rule::new(condition, output) {
+        Ok(rule) => rule,
+        Err(e) => {
+            fail!("{}", t!("rule-parse-error", error = e));
+        }
+    };
+    match db.insert_rule(&ctx.kb, &rule).await {
+        Ok(id) => info!("{}", t!("db-rule-inserted", id = id, kb = ctx.kb)),
+        Err(e) => fail!("{}", t!("db-insert-error", error = e)),
+    }
+    Ok(())
+}
+
+async fn remove(id: &str, ctx: &mut Context) -> Outcome {
+    let Some(db) = ctx.db.as_ref() else {
+        fail!("{}", t!("db-not-connected"));
+    };
+    let id = match id.parse::<i64>() {
+        Ok(id) => id,
+        Err(e) => {
+            fail!("{}", t!("invalid-rule-id", id = id, error = e));
+        }
+    };
+    match db.delete_rule(&ctx.kb, id).await {
+        Ok(()) => info!("{}", t!("db-rule-deleted", id = id, kb = ctx.kb)),
+        Err(e) => fail!("{}", t!("db-delete-error", error = e)),
+    }
+    Ok(())
+}
+
+async fn history(ctx: &mut Context) -> Outcome {
+    let Some(db) = ctx.db.as_ref() else {
+        fail!("{}", t!("db-not-connected"));
+    };
+    let history = match db.history(&ctx.kb).await {
+        Ok(history) => history,
+        Err(e) => {
+            fail!("{}", t!("db-read-error", error = e));
+        }
+    };
+    let value = json!(history);
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record([t!("col-id"), t!("col-action"), t!("col-detail"), t!("col-time")]);
+    for entry in history {
+        builder.push_record([entry.id.to_string(), entry.action, entry.detail, entry.created_at]);
+    }
+    let table = render_table(builder);
+    ctx.print(value, || table);
+    Ok(())
+}
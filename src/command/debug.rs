@@ -0,0 +1,216 @@
+use crate::command::Outcome;
+use crate::Context;
+use crate::output::render_table;
+use colored::Colorize;
+use expert::debug::{reasons, Breakpoint, Debugger, Event, Stop};
+use serde_json::json;
+use tracing::info;
+
+pub(crate) fn handle_debug(seg: &[&str], ctx: &mut Context) -> Outcome {
+    match seg {
+        ["stop", ..] => {
+            if ctx.debugger.take().is_some() {
+                info!("{}", t!("debug-stopped"));
+            } else {
+                fail!("{}", t!("debug-inactive"));
+            }
+        }
+        _ => {
+            let input = seg
+                .iter()
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>();
+            if !input.is_empty() {
+                ctx.input = input;
+            }
+            ctx.debugger = Some(Debugger::new(ctx.input.clone(), &ctx.engine));
+            info!("{}", t!("debug-started", facts = format!("{:?}", ctx.input)));
+            show(vec![], None, ctx);
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn handle_resume(command: &str, ctx: &mut Context) -> Outcome {
+    let Some(debugger) = ctx.debugger.as_mut() else {
+        fail!("{}", t!("debug-inactive"));
+    };
+    if debugger.is_finished() {
+        fail!("{}", t!("debug-finished"));
+    }
+    let (events, stop) = match command {
+        "step" => debugger.step(&ctx.rules),
+        "next-cycle" => debugger.next_cycle(&ctx.rules, &ctx.breakpoints),
+        _ => debugger.resume(&ctx.rules, &ctx.breakpoints),
+    };
+    show(events, Some(stop), ctx);
+    Ok(())
+}
+
+pub(crate) fn handle_break(seg: &[&str], ctx: &mut Context) -> Outcome {
+    let breakpoint = match seg {
+        [] | [""] => {
+            let text = ctx
+                .breakpoints
+                .iter()
+                .map(breakpoint_text)
+                .collect::<Vec<_>>()
+                .join("\n");
+            let value = json!(ctx.breakpoints);
+            ctx.print(value, || text);
+            return Ok(());
+        }
+        ["clear", ..] => {
+            ctx.breakpoints.clear();
+            info!("{}", t!("debug-breakpoints-cleared"));
+            return Ok(());
+        }
+        ["rule", id, ..] => match id.parse::<usize>() {
+            Ok(id) if id < ctx.rules.len() => Breakpoint::Rule(id),
+            _ => {
+                fail!("{}", t!("rule-missing", id = id));
+            }
+        },
+        ["fact", fact, ..] if !fact.is_empty() => Breakpoint::Fact(fact.to_string()),
+        _ => {
+            fail!("{}", t!("usage-break"));
+        }
+    };
+    info!("{}", t!("debug-breakpoint-set", breakpoint = breakpoint_text(&breakpoint)));
+    if !ctx.breakpoints.contains(&breakpoint) {
+        ctx.breakpoints.push(breakpoint);
+    }
+    Ok(())
+}
+
+pub(crate) fn handle_watch(seg: &[&str], ctx: &mut Context) -> Outcome {
+    match seg {
+        [] | [""] => {
+            let text = ctx.watches.join("\n");
+            let value = json!(ctx.watches);
+            ctx.print(value, || text);
+        }
+        ["clear", ..] => {
+            ctx.watches.clear();
+            info!("{}", t!("debug-watches-cleared"));
+        }
+        [fact, ..] => {
+            if !ctx.watches.iter().any(|watch| watch == fact) {
+                ctx.watches.push(fact.to_string());
+            }
+            info!("{}", t!("debug-watching", fact = fact));
+        }
+    }
+    Ok(())
+}
+
+fn breakpoint_text(breakpoint: &Breakpoint) -> String {
+    match breakpoint {
+        Breakpoint::Rule(id) => t!("debug-breakpoint-rule", id = id),
+        Breakpoint::Fact(fact) => t!("debug-breakpoint-fact", fact = fact),
+    }
+}
+
+/// Shows what happened since the last stop and where deduction is now.
+fn show(events: Vec<Event>, stop: Option<Stop>, ctx: &mut Context) {
+    let Some(debugger) = ctx.debugger.as_ref() else {
+        return;
+    };
+    let mut text = String::new();
+    for event in &events {
+        match event {
+            Event::Fired(firing) => {
+                text.push_str(&t!(
+                    "debug-fired",
+                    cycle = firing.cycle,
+                    rule = firing.rule,
+                    added = firing.added.join(", ")
+                ));
+                text.push('\n');
+                for fact in firing.added.iter().filter(|fact| ctx.watches.contains(fact)) {
+                    let line = t!("debug-watch-derived", fact = fact, rule = firing.rule);
+                    text.push_str(&format!("{}\n", line.yellow()));
+                }
+            }
+            Event::Skipped { rule } if events.len() == 1 => {
+                text.push_str(&format!("{}\n", t!("debug-skipped", rule = rule)));
+            }
+            Event::Skipped { .. } => {}
+            Event::CycleEnd { cycle, changed } => {
+                let line = if *changed {
+                    t!("debug-cycle-changed", cycle = cycle)
+                } else {
+                    t!("debug-cycle-unchanged", cycle = cycle)
+                };
+                text.push_str(&format!("{}\n", line));
+            }
+        }
+    }
+    match &stop {
+        Some(Stop::Breakpoint(breakpoint)) => {
+            let line = t!("debug-stopped-at", breakpoint = breakpoint_text(breakpoint));
+            text.push_str(&format!("{}\n", line.red()));
+        }
+        Some(Stop::Finished) => text.push_str(&format!("{}\n", t!("debug-complete").green())),
+        _ => {}
+    }
+
+    let facts = debugger.facts();
+    text.push_str(&format!("{}\n", t!("debug-facts", cycle = facts.cycle(), facts = facts.all().join(" "))));
+    let watches = ctx
+        .watches
+        .iter()
+        .map(|fact| (fact.clone(), facts.contains(fact)))
+        .collect::<Vec<_>>();
+    for (fact, known) in &watches {
+        let line = if *known {
+            t!("debug-watch-known", fact = fact)
+        } else {
+            t!("debug-watch-unknown", fact = fact)
+        };
+        text.push_str(&format!("{}\n", line));
+    }
+
+    let pending = debugger.pending(&ctx.rules);
+    let explainer = ctx.explainer();
+    let mut entries = vec![];
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record([t!("col-id"), t!("col-rule"), t!("col-why")]);
+    for &id in &pending {
+        let rule = &ctx.rules[id];
+        let (known, missing) = reasons(rule.condition(), facts.all());
+        let mut why = vec![];
+        if !known.is_empty() {
+            why.push(t!("explain-known", facts = explainer.facts(&known)));
+        }
+        if !missing.is_empty() {
+            why.push(t!("explain-unknown", facts = explainer.facts(&missing)));
+        }
+        let mut marker = id.to_string();
+        if ctx.breakpoints.contains(&Breakpoint::Rule(id)) {
+            marker = format!("{} *", id).red().to_string();
+        }
+        builder.push_record([marker, rule.to_string(), why.join(", ")]);
+        entries.push(json!({ "id": id, "rule": rule.to_string(), "known": known, "missing": missing }));
+    }
+    if pending.is_empty() {
+        text.push_str(&t!("debug-nothing-pending"));
+    } else {
+        text.push_str(&t!("debug-pending"));
+        text.push('\n');
+        text.push_str(&render_table(builder));
+    }
+    let value = json!({
+        "events": events,
+        "stop": stop,
+        "cycle": facts.cycle(),
+        "facts": facts.all(),
+        "watches": watches
+            .iter()
+            .map(|(fact, known)| json!({ "fact": fact, "known": known }))
+            .collect::<Vec<_>>(),
+        "pending": entries,
+    });
+    ctx.print(value, || text);
+}
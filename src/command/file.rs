@@ -0,0 +1,42 @@
+use crate::command::{handle_help, Outcome};
+use expert::dsl::{load_file, save_file};
+use crate::Context;
+use std::path::Path;
+use tracing::info;
+
+pub(crate) async fn handle_load(seg: &[&str], ctx: &mut Context) -> Outcome {
+    match seg {
+        [path, ..] if !path.is_empty() => {
+            let file = match load_file(Path::new(path)) {
+                Ok(file) => file,
+                Err(e) => {
+                    fail!("{}", t!("file-load-error", path = path, error = e));
+                }
+            };
+            ctx.record(format!("load {}", path));
+            ctx.rules = file.rules;
+            ctx.tests = file.tests;
+            ctx.labels = file.labels;
+            info!("{}", t!("file-loaded", rules = ctx.rules.len(), tests = ctx.tests.len(), path = path));
+        }
+        _ => {
+            handle_help(&["load"], ctx)?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) async fn handle_save(seg: &[&str], ctx: &mut Context) -> Outcome {
+    match seg {
+        [path, ..] if !path.is_empty() => {
+            if let Err(e) = save_file(Path::new(path), &ctx.rules, &ctx.tests, &ctx.labels) {
+                fail!("{}", t!("file-save-error", path = path, error = e));
+            }
+            info!("{}", t!("file-saved", rules = ctx.rules.len(), tests = ctx.tests.len(), path = path));
+        }
+        _ => {
+            handle_help(&["save"], ctx)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use crate::command::Outcome;
+use crate::Context;
+use crate::output::render_table;
+use colored::Colorize;
+use serde_json::json;
+use tracing::{info, warn};
+
+pub(crate) fn handle_undo(ctx: &mut Context) -> Outcome {
+    let current = ctx.snapshot();
+    match ctx.journal.undo(current) {
+        Some((action, before)) => {
+            ctx.restore(before);
+            info!("{}", t!("journal-undid", action = action));
+        }
+        None => fail!("{}", t!("journal-nothing-to-undo")),
+    }
+    Ok(())
+}
+
+pub(crate) fn handle_redo(ctx: &mut Context) -> Outcome {
+    let current = ctx.snapshot();
+    match ctx.journal.redo(current) {
+        Some((action, after)) => {
+            ctx.restore(after);
+            info!("{}", t!("journal-redid", action = action));
+        }
+        None => fail!("{}", t!("journal-nothing-to-redo")),
+    }
+    Ok(())
+}
+
+pub(crate) fn handle_history(ctx: &mut Context) -> Outcome {
+    let entries = ctx.journal.entries();
+    if entries.is_empty() {
+        warn!("{}", t!("journal-empty"));
+    }
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record([t!("col-id"), t!("col-action"), t!("col-state")]);
+    for (i, entry) in entries.iter().enumerate() {
+        let (action, state) = if entry.undone {
+            (entry.action.dimmed().to_string(), t!("journal-undone").dimmed().to_string())
+        } else {
+            (entry.action.clone(), String::new())
+        };
+        builder.push_record([(i + 1).to_string(), action, state]);
+    }
+    let table = render_table(builder);
+    let value = json!(entries);
+    ctx.print(value, || table);
+    Ok(())
+}
@@ -0,0 +1,306 @@
+use crate::command::db::load_kb;
+use crate::command::{handle_help, Outcome};
+use crate::Context;
+use crate::output::render_table;
+use expert::testing::{TestCase, TestResult};
+use expert::Rule;
+use serde_json::json;
+use tracing::info;
+
+pub(crate) async fn handle_kb(seg: &[&str], ctx: &mut Context) -> Outcome {
+    match seg {
+        ["list", ..] => {
+            list(ctx).await?;
+        }
+        ["create", name, ..] => {
+            create(name, ctx).await?;
+        }
+        ["create", ..] => {
+            fail!("{}", t!("usage-kb-create"));
+        }
+        ["use", name, flags @ ..] => {
+            use_kb(name, flags.contains(&"--force"), ctx).await?;
+        }
+        ["use", ..] => {
+            fail!("{}", t!("usage-kb-use"));
+        }
+        ["copy", from, to, ..] => {
+            copy(from, to, ctx).await?;
+        }
+        ["copy", ..] => {
+            fail!("{}", t!("usage-kb-copy"));
+        }
+        ["delete", name, ..] => {
+            delete(name, ctx).await?;
+        }
+        ["delete", ..] => {
+            fail!("{}", t!("usage-kb-delete"));
+        }
+        ["test", rest @ ..] => {
+            handle_kb_test(rest, ctx)?;
+        }
+        [] => {
+            handle_help(&["kb"], ctx)?;
+        }
+        [x, ..] => {
+            fail!("{}", t!("unknown-subcommand", subcommand = x))
+        }
+    }
+    Ok(())
+}
+
+async fn list(ctx: &mut Context) -> Outcome {
+    let Some(db) = ctx.db.as_ref() else {
+        fail!("{}", t!("db-not-connected"));
+    };
+    let kbs = match db.list_kbs().await {
+        Ok(kbs) => kbs,
+        Err(e) => {
+            fail!("{}", t!("db-read-error", error = e));
+        }
+    };
+    let value = json!(kbs
+        .iter()
+        .map(|(name, count)| json!({ "name": name, "rules": count, "in_use": *name == ctx.kb }))
+        .collect::<Vec<_>>());
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record([t!("col-name"), t!("col-rules"), t!("col-in-use")]);
+    for (name, count) in kbs {
+        let in_use = if name == ctx.kb { "*" } else { "" };
+        builder.push_record([name, count.to_string(), in_use.to_string()]);
+    }
+    let table = render_table(builder);
+    ctx.print(value, || table);
+    Ok(())
+}
+
+async fn create(name: &str, ctx: &mut Context) -> Outcome {
+    let Some(db) = ctx.db.as_ref() else {
+        fail!("{}", t!("db-not-connected"));
+    };
+    if let Err(e) = db.create_kb(name).await {
+        fail!("{}", t!("kb-create-error", error = e));
+    }
+    info!("{}", t!("kb-created", kb = name));
+    Ok(())
+}
+
+/// Switches to another knowledge base and loads its rules, tests and
+/// labels in place of the current ones.
+async fn use_kb(name: &str, force: bool, ctx: &mut Context) -> Outcome {
+    let Some(db) = ctx.db.as_ref() else {
+        fail!("{}", t!("db-not-connected"));
+    };
+    match db.kb_exists(name).await {
+        Ok(true) => {}
+        Ok(false) => {
+            fail!("{}", t!("kb-missing", kb = name));
+        }
+        Err(e) => {
+            fail!("{}", t!("db-read-error", error = e));
+        }
+    }
+    if ctx.unsynced && !force {
+        fail!("{}", t!("kb-unsynced", kb = ctx.kb));
+    }
+    load_kb(name, ctx).await?;
+    ctx.kb = name.to_string();
+    info!("{}", t!("kb-using", kb = name));
+    Ok(())
+}
+
+async fn copy(from: &str, to: &str, ctx: &mut Context) -> Outcome {
+    let Some(db) = ctx.db.as_ref() else {
+        fail!("{}", t!("db-not-connected"));
+    };
+    if let Err(e) = db.copy_kb(from, to).await {
+        fail!("{}", t!("kb-copy-error", error = e));
+    }
+    info!("{}", t!("kb-copied", from = from, to = to));
+    Ok(())
+}
+
+async fn delete(name: &str, ctx: &mut Context) -> Outcome {
+    let Some(db) = ctx.db.as_ref() else {
+        fail!("{}", t!("db-not-connected"));
+    };
+    if name == ctx.kb {
+        fail!("{}", t!("kb-in-use", kb = name));
+    }
+    if let Err(e) = db.delete_kb(name).await {
+        fail!("{}", t!("kb-delete-error", error = e));
+    }
+    info!("{}", t!("kb-deleted", kb = name));
+    Ok(())
+}
+
+fn handle_kb_test(seg: &[&str], ctx: &mut Context) -> Outcome {
+    match seg {
+        [] | ["run", ..] => {
+            run_tests(ctx)?;
+        }
+        ["add", name, given, expect, rest @ ..] => {
+            add_test(name, given, expect, rest.first().copied().unwrap_or("-"), ctx)?;
+        }
+        ["add", ..] => {
+            fail!("{}", t!("usage-kb-test-add"));
+        }
+        ["list", ..] => {
+            list_tests(ctx);
+        }
+        ["remove", idx, ..] => {
+            remove_test(idx, ctx)?;
+        }
+        ["remove", ..] => {
+            fail!("{}", t!("usage-kb-test-remove"));
+        }
+        [x, ..] => {
+            fail!("{}", t!("unknown-subcommand", subcommand = x))
+        }
+    }
+    Ok(())
+}
+
+/// Facts separated by commas, `-` for none.
+fn split_facts(facts: &str) -> Vec<String> {
+    facts
+        .split(',')
+        .filter(|fact| !fact.is_empty() && *fact != "-")
+        .map(|fact| fact.to_string())
+        .collect()
+}
+
+fn add_test(name: &str, given: &str, expect: &str, expect_not: &str, ctx: &mut Context) -> Outcome {
+    let test = TestCase {
+        name: name.to_string(),
+        given: split_facts(given),
+        expect: split_facts(expect),
+        expect_not: split_facts(expect_not),
+    };
+    if let Some(fact) = test
+        .given
+        .iter()
+        .chain(&test.expect)
+        .chain(&test.expect_not)
+        .find(|fact| !expert::rule::is_fact_name(fact))
+    {
+        fail!("{}", t!("invalid-fact", fact = format!("{:?}", fact)));
+    }
+    if test.expect.is_empty() && test.expect_not.is_empty() {
+        fail!("{}", t!("test-needs-expectation"));
+    }
+    ctx.record(format!("kb test add {}", name));
+    ctx.tests.push(test);
+    info!("{}", t!("test-added", name = name, count = ctx.tests.len()));
+    Ok(())
+}
+
+fn list_tests(ctx: &mut Context) {
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record([t!("col-id"), t!("col-name"), t!("col-given"), t!("col-expect"), t!("col-not")]);
+    for (i, test) in ctx.tests.iter().enumerate() {
+        builder.push_record([
+            i.to_string(),
+            test.name.clone(),
+            test.given.join(","),
+            test.expect.join(","),
+            test.expect_not.join(","),
+        ]);
+    }
+    let table = render_table(builder);
+    let value = json!(ctx.tests);
+    ctx.print(value, || table);
+}
+
+fn remove_test(idx: &str, ctx: &mut Context) -> Outcome {
+    match idx.parse::<usize>() {
+        Ok(idx) if idx < ctx.tests.len() => {
+            ctx.record(format!("kb test remove {}: {}", idx, ctx.tests[idx].name));
+            let test = ctx.tests.remove(idx);
+            info!("{}", t!("test-removed", name = test.name));
+        }
+        Ok(idx) => fail!("{}", t!("test-missing", id = idx)),
+        Err(e) => fail!("{}", t!("invalid-test-id", id = idx, error = e)),
+    }
+    Ok(())
+}
+
+fn run_tests(ctx: &mut Context) -> Outcome {
+    if ctx.tests.is_empty() {
+        info!("{}", t!("test-none"));
+        return Ok(());
+    }
+    // Every test would log its deduction in English, keep them quiet
+    let results = tracing::subscriber::with_default(tracing::subscriber::NoSubscriber::default(), || {
+        ctx.tests
+            .iter()
+            .map(|test| test.run_with(&ctx.rules, &ctx.engine))
+            .collect::<Vec<_>>()
+    });
+    for result in &results {
+        ctx.coverage.merge(&result.coverage);
+    }
+    let failed = results.iter().filter(|result| !result.passed()).count();
+    let mut text = String::new();
+    for result in &results {
+        if result.passed() {
+            text.push_str(&t!("test-pass", name = result.name));
+            text.push('\n');
+        } else {
+            text.push_str(&failure_text(result, &ctx.rules));
+        }
+    }
+    text.push_str(&t!("test-summary", passed = results.len() - failed, failed = failed));
+    let value = json!({
+        "passed": results.len() - failed,
+        "failed": failed,
+        "results": results
+            .iter()
+            .map(|result| json!({ "passed": result.passed(), "result": result }))
+            .collect::<Vec<_>>(),
+    });
+    ctx.print(value, || text);
+    if failed > 0 {
+        fail!("{}", t!("test-failed-count", failed = failed, total = results.len()));
+    }
+    Ok(())
+}
+
+/// Shows what went wrong along with the rules that fired, and for every
+/// missing fact the rules that could have concluded it.
+fn failure_text(result: &TestResult, rules: &[Rule]) -> String {
+    let mut text = t!("test-fail", name = result.name) + "\n";
+    if !result.missing.is_empty() {
+        text.push_str(&format!("  {}\n", t!("test-not-derived", facts = result.missing.join(" "))));
+    }
+    if !result.unwanted.is_empty() {
+        text.push_str(&format!("  {}\n", t!("test-unwanted", facts = result.unwanted.join(" "))));
+    }
+    text.push_str(&format!("  {}\n", t!("test-derived", facts = result.derived.join(" "))));
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record([t!("col-cycle"), t!("col-rule"), t!("col-condition"), t!("col-added")]);
+    for fired in &result.fired {
+        builder.push_record([
+            fired.cycle.to_string(),
+            fired.rule.to_string(),
+            rules[fired.rule].condition().to_string(),
+            fired.added.join(","),
+        ]);
+    }
+    text.push_str(&render_table(builder));
+    text.push('\n');
+    for fact in &result.missing {
+        let candidates = rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.output().contains(fact))
+            .map(|(i, rule)| format!("#{} {}", i, rule))
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            text.push_str(&format!("  {}\n", t!("test-no-rule-concludes", fact = fact)));
+        } else {
+            text.push_str(&format!("  {}\n", t!("test-could-come-from", fact = fact, rules = candidates.join("; "))));
+        }
+    }
+    text
+}
@@ -0,0 +1,46 @@
+use crate::command::{handle_help, Outcome};
+use crate::Context;
+use crate::output::render_table;
+use expert::rule::is_fact_name;
+use serde_json::json;
+use tracing::info;
+
+pub(crate) fn handle_label(seg: &[&str], ctx: &mut Context) -> Outcome {
+    match seg {
+        [] | [""] | ["list", ..] => list(ctx),
+        ["remove", fact, ..] if !fact.is_empty() => {
+            if !ctx.labels.contains_key(*fact) {
+                fail!("{}", t!("label-missing", fact = fact));
+            }
+            ctx.record(format!("label remove {}", fact));
+            ctx.labels.remove(*fact);
+            info!("{}", t!("label-removed", fact = fact));
+        }
+        [fact, words @ ..] if !words.is_empty() => {
+            if !is_fact_name(fact.strip_prefix('!').unwrap_or(fact)) {
+                fail!("{}", t!("label-invalid", fact = fact));
+            }
+            let label = words.join(" ");
+            ctx.record(format!("label {}: {}", fact, label));
+            info!("{}", t!("label-set", fact = fact, label = label));
+            ctx.labels.insert(fact.to_string(), label);
+        }
+        _ => handle_help(&["label"], ctx)?,
+    }
+    Ok(())
+}
+
+fn list(ctx: &mut Context) {
+    if ctx.labels.is_empty() {
+        info!("{}", t!("label-none"));
+        return;
+    }
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record([t!("col-fact"), t!("col-label")]);
+    for (fact, label) in &ctx.labels {
+        builder.push_record([fact.clone(), label.clone()]);
+    }
+    let table = render_table(builder);
+    let value = json!(ctx.labels);
+    ctx.print(value, || table);
+}
@@ -1,92 +1,153 @@
 use colored::Colorize;
+use crate::command::batch::handle_batch;
+use crate::command::coverage::handle_coverage;
 use crate::command::db::handle_db;
+use crate::command::debug::{handle_break, handle_debug, handle_resume, handle_watch};
+use crate::command::file::{handle_load, handle_save};
+use crate::command::journal::{handle_history, handle_redo, handle_undo};
 use crate::Context;
-use tracing::error;
+use crate::command::kb::handle_kb;
+use crate::command::label::handle_label;
+use crate::command::proof::handle_proof;
 use crate::command::rule::handle_rule;
-use crate::rule::Facts;
+use crate::command::scenario::{handle_runs, handle_scenario};
+use crate::command::set::handle_set;
+use crate::command::test::handle_test;
+use crate::output::{OutputFormat, Status};
+use serde_json::json;
 
+pub(crate) use db::connect_and_load;
+
+/// A command that failed, with the message it logged.
+#[derive(Debug)]
+pub(crate) struct Failed(pub String);
+
+/// What a command returns, so scripts and JSON output learn whether it
+/// failed without looking at the logs.
+pub(crate) type Outcome = Result<(), Failed>;
+
+/// Logs an error like `error!` and fails the command with it.
+macro_rules! fail {
+    ($($arg:tt)+) => {{
+        let message = format!($($arg)+);
+        tracing::error!("{}", message);
+        return Err($crate::command::Failed(message));
+    }};
+}
+
+mod batch;
+mod coverage;
 mod db;
+mod debug;
+mod file;
+mod journal;
+mod kb;
+mod label;
+mod proof;
 mod rule;
+mod scenario;
+mod set;
+mod test;
 
 pub fn print_header() {
-    println!(
-        "专家系统 v{} by {}",
-        env!("CARGO_PKG_VERSION"),
-        env!("CARGO_PKG_AUTHORS")
-    );
-    println!("使用 {} {} 可以查询更多信息", "help".cyan(), "<命令>".yellow());
-    println!();
+    println!("{}", header());
 }
 
-async fn handle_help(seg: &[&str]) {
-    print_header();
-    match seg {
-        ["help", ..] => {
-            println!("输出帮助信息");
-            println!("用法: help <命令>");
-        }
-        ["quit", ..] => {
-            println!("退出程序");
-            println!("用法: quit");
-        }
-        ["rules", ..] => {
-            println!("查看或修改规则库中的规则");
-            println!("用法: rule <子命令>");
-            println!("子命令:");
-            println!("  list: 列出所有规则");
-            println!("  add <规则> <输出>: 添加新规则");
-            println!("  remove <规则ID>: 删除指定规则");
-        }
-        ["test", ..] => {
-            println!("输入一系列的事实进行推论");
-            println!("用法: test <事实>");
-            println!("示例: test fact1 fact2");
-        }
-        ["db", ..] => {
-            println!("查看sqlite数据库信息");
-            println!("用法: db <子命令>");
-            println!("子命令:");
-            println!("  connect <路径>: 连接数据库");
-            println!("  close: 断开数据库连接");
-            println!("  status: 查看数据库状态");
-            println!("  load: 从数据库加载规则库");
-            println!("  sync: 保存规则库到数据库");
-            println!("  reset: 重置数据库");
-        }
-        [] => {
-            println!("命令:");
-            println!("  help: 输出此帮助信息");
-            println!("  quit: 退出程序");
-            println!("  rule: 查看或修改规则库中的规则");
-            println!("  test: 输入一系列的事实进行推论");
-            println!("  db: 查看数据库信息");
-        }
-        _ => {
-            error!("未知命令: {}", seg[0])
-        }
-    }
+fn header() -> String {
+    t!(
+        "header",
+        version = env!("CARGO_PKG_VERSION"),
+        authors = env!("CARGO_PKG_AUTHORS"),
+        help = "help".cyan(),
+        command = t!("header-command").yellow()
+    ) + "\n"
+}
+
+/// Prints the help of a command, or the list of commands when `seg` is empty.
+pub(crate) fn handle_help(seg: &[&str], ctx: &mut Context) -> Outcome {
+    let Some(help) = help_text(seg) else {
+        fail!("{}", t!("unknown-command", command = seg[0]));
+    };
+    ctx.print(json!({ "help": help }), || format!("{}\n{}", header(), help));
+    Ok(())
 }
 
-pub async fn handle_command(line: String, ctx: &mut Context) -> anyhow::Result<bool> {
+fn help_text(seg: &[&str]) -> Option<String> {
+    let help = match seg {
+        ["help", ..] => t!("help-help"),
+        ["quit", ..] => t!("help-quit"),
+        ["rule" | "rules", ..] => t!("help-rule"),
+        ["test", ..] => t!("help-test"),
+        ["load", ..] => t!("help-load"),
+        ["save", ..] => t!("help-save"),
+        ["scenario", ..] => t!("help-scenario"),
+        ["runs", ..] => t!("help-runs"),
+        ["db", ..] => t!("help-db"),
+        ["batch", ..] => t!("help-batch"),
+        ["debug" | "step" | "next-cycle" | "continue" | "break" | "watch", ..] => t!("help-debug"),
+        ["tui", ..] => t!("help-tui"),
+        ["undo" | "redo" | "history", ..] => t!("help-journal"),
+        ["proof", ..] => t!("help-proof"),
+        ["label", ..] => t!("help-label"),
+        ["coverage", ..] => t!("help-coverage"),
+        ["set", ..] => t!("help-set"),
+        ["kb", ..] => t!("help-kb"),
+        [] => t!("help-commands"),
+        _ => return None,
+    };
+    Some(help + "\n")
+}
+
+/// Runs one line, `Ok(true)` when it asks to quit.
+pub async fn handle_command(line: String, ctx: &mut Context) -> Result<bool, Failed> {
     let segments: Vec<&str> = line.split(" ").collect();
-    match segments.as_slice() {
-        ["help", ..] => {
-            handle_help(&segments[1..]).await;
-        }
-        ["quit", ..] => {
-            return Ok(true);
-        }
-        ["rule", ..] => {
-            handle_rule(&segments[1..], ctx).await;
-        }
-        ["test", ..] => {
-            let mut facts = Facts::new(&segments[1..]);
-            facts.deduce(&ctx.rules);
-        }
-        ["db", ..] => {
-            handle_db(&segments[1..], ctx).await;
-        }
-        _ => {}
+    ctx.printed = false;
+    let outcome = match segments.as_slice() {
+        ["help", ..] => handle_help(&segments[1..], ctx),
+        ["quit", ..] => return Ok(true),
+        ["rule", ..] => handle_rule(&segments[1..], ctx).await,
+        ["test", ..] => handle_test(&segments[1..], ctx).await,
+        ["load", ..] => handle_load(&segments[1..], ctx).await,
+        ["save", ..] => handle_save(&segments[1..], ctx).await,
+        ["scenario", ..] => handle_scenario(&segments[1..], ctx).await,
+        ["runs", ..] => handle_runs(&segments[1..], ctx).await,
+        ["db", ..] => handle_db(&segments[1..], ctx).await,
+        ["kb", ..] => handle_kb(&segments[1..], ctx).await,
+        ["set", ..] => handle_set(&segments[1..], ctx),
+        ["batch", ..] => handle_batch(&segments[1..], ctx).await,
+        ["coverage", ..] => handle_coverage(&segments[1..], ctx),
+        ["debug", ..] => handle_debug(&segments[1..], ctx),
+        ["proof", ..] => handle_proof(&segments[1..], ctx),
+        ["label", ..] => handle_label(&segments[1..], ctx),
+        ["tui", ..] => run_tui(ctx),
+        [command @ ("step" | "next-cycle" | "continue"), ..] => handle_resume(command, ctx),
+        ["break", ..] => handle_break(&segments[1..], ctx),
+        ["watch", ..] => handle_watch(&segments[1..], ctx),
+        ["undo", ..] => handle_undo(ctx),
+        ["redo", ..] => handle_redo(ctx),
+        ["history", ..] => handle_history(ctx),
+        [] | [""] => return Ok(false),
+        [x, ..] => unknown_command(x),
+    };
+    if ctx.format == OutputFormat::Json && !ctx.printed {
+        ctx.print(Status::new(&line, &outcome), String::new);
+    }
+    outcome.map(|()| false)
+}
+
+#[cfg(feature = "tui")]
+fn run_tui(ctx: &mut Context) -> Outcome {
+    if let Err(e) = crate::tui::run(ctx) {
+        fail!("{}", t!("tui-error", error = e));
     }
-    Ok(false)
+    Ok(())
+}
+
+#[cfg(not(feature = "tui"))]
+fn run_tui(_ctx: &mut Context) -> Outcome {
+    fail!("{}", t!("tui-missing"));
+}
+
+fn unknown_command(command: &str) -> Outcome {
+    fail!("{}", t!("unknown-command-hint", command = command));
 }
@@ -0,0 +1,81 @@
+use crate::command::Outcome;
+use crate::Context;
+use expert::explain::Explainer;
+use expert::proof::Proof;
+use expert::Rule;
+use std::path::Path;
+use tracing::info;
+
+pub(crate) fn handle_proof(seg: &[&str], ctx: &mut Context) -> Outcome {
+    let mut format = None;
+    let mut fact = None;
+    let mut path = None;
+    for &arg in seg.iter().filter(|arg| !arg.is_empty()) {
+        match arg {
+            "--dot" => format = Some("dot"),
+            "--json" => format = Some("json"),
+            _ if arg.starts_with("--") => {
+                fail!("{}", t!("unknown-option", option = arg));
+            }
+            _ if fact.is_none() => fact = Some(arg),
+            _ => path = Some(arg),
+        }
+    }
+    let Some(fact) = fact else {
+        fail!("{}", t!("usage-proof"));
+    };
+    let Some((facts, rules)) = ctx.last_deduction.as_ref() else {
+        fail!("{}", t!("proof-nothing-deduced"));
+    };
+    let Some(proof) = Proof::build(facts, rules, fact) else {
+        fail!("{}", t!("proof-not-reached", fact = fact));
+    };
+    let format = format.or_else(|| {
+        match path.and_then(|path| Path::new(path).extension()).and_then(|ext| ext.to_str()) {
+            Some("dot" | "gv") => Some("dot"),
+            Some("json") => Some("json"),
+            _ => None,
+        }
+    });
+    let explainer = ctx.explainer();
+    let label = |proof: &Proof| label(proof, rules, &explainer);
+    let source = match format {
+        Some("dot") => proof.to_dot_with(&label),
+        Some("json") => match serde_json::to_string_pretty(&proof) {
+            Ok(json) => json + "\n",
+            Err(e) => {
+                fail!("{}", t!("error-json", error = e));
+            }
+        },
+        _ => proof.to_tree_with(&label),
+    };
+    match path {
+        Some(path) => match std::fs::write(path, &source) {
+            Ok(()) => info!("{}", t!("proof-written", fact = fact, path = path)),
+            Err(e) => fail!("{}", t!("file-write-error", path = path, error = e)),
+        },
+        None => ctx.print(&proof, || source.trim_end().to_string()),
+    }
+    Ok(())
+}
+
+/// A step of a proof in the current language, with the fact labels.
+fn label(proof: &Proof, rules: &[Rule], explainer: &Explainer) -> String {
+    match proof {
+        Proof::Given { fact } => t!("explain-given", fact = explainer.fact(fact)),
+        Proof::Absent { fact } => t!("explain-absent", fact = explainer.fact(fact)),
+        Proof::Derived {
+            fact,
+            rule,
+            cycle,
+            condition,
+            ..
+        } => {
+            let condition = match rules.get(*rule) {
+                Some(rule) => explainer.condition(rule.condition()),
+                None => condition.clone(),
+            };
+            t!("explain-derived", fact = explainer.fact(fact), rule = rule, cycle = cycle, condition = condition)
+        }
+    }
+}
@@ -1,44 +1,472 @@
 use log::info;
 use rand::prelude::SliceRandom;
-use tracing::error;
-use crate::command::handle_help;
+use tracing::{error, warn};
+use crate::command::{handle_help, Outcome};
+use expert::convert::{self, Dialect};
+use expert::graph::Graph;
+use expert::exchange::{export_rules, import_rules};
+use expert::confluence::{self, Divergence};
+use expert::rule::{Condition, Rule, RuleBuilder};
+use expert::search::Query;
+use colored::Colorize;
+use regex::Regex;
 use crate::Context;
+use crate::output::render_table;
+use serde::Serialize;
+use serde_json::json;
+use std::path::Path;
 
-pub async fn handle_rule(seg: &[&str], ctx: &mut Context) {
-    match seg { 
-        ["list", ..] => {
-            println!("{}", ctx.list_rules());
+/// A rule as listed in JSON mode, with the index other commands take.
+#[derive(Serialize)]
+struct RuleEntry<'a> {
+    id: usize,
+    #[serde(flatten)]
+    rule: &'a Rule,
+}
+
+pub async fn handle_rule(seg: &[&str], ctx: &mut Context) -> Outcome {
+    match seg {
+        ["list", flags @ ..] => {
+            let long = match flags {
+                [] => false,
+                ["--long" | "-l", ..] => true,
+                [x, ..] => {
+                    fail!("{}", t!("unknown-option", option = x));
+                }
+            };
+            let value = json!(ctx
+                .rules
+                .iter()
+                .enumerate()
+                .map(|(id, rule)| RuleEntry { id, rule })
+                .collect::<Vec<_>>());
+            let table = ctx.list_rules(long);
+            ctx.print(value, || table);
+        }
+        ["find", args @ ..] => {
+            let query = match parse_query(args) {
+                Ok(query) => query,
+                Err(e) => {
+                    fail!("{}", e);
+                }
+            };
+            let found = query.find(&ctx.rules);
+            info!("{}", t!("rule-found", found = found.len(), total = ctx.rules.len()));
+            let value = json!(found
+                .iter()
+                .map(|&id| RuleEntry { id, rule: &ctx.rules[id] })
+                .collect::<Vec<_>>());
+            let table = found_table(&query, found.iter().map(|&id| (id.to_string(), &ctx.rules[id])));
+            ctx.print(value, || table);
+        }
+        ["confluence", rest @ ..] => {
+            confluence(rest.first().filter(|n| !n.is_empty()), ctx)?;
+        }
+        ["graph", rest @ ..] => {
+            graph(rest, ctx)?;
         }
         ["shuffle", ..] => {
+            ctx.record("rule shuffle");
             ctx.rules.shuffle(&mut rand::thread_rng());
-            info!("Successfully shuffled rule");
+            info!("{}", t!("rule-shuffled"));
         }
         ["add", rule, output, ..] => {
             if let Err(e) = ctx.add_rule(rule, output) {
-                error!("Error while adding new rules: {}", e);
-                return;
+                fail!("{}", t!("rule-add-error", error = e));
             }
-            info!("Successfully added rule with condition {} and output {}", rule, output);
+            info!("{}", t!("rule-added", condition = rule, output = output));
         }
         ["add", ..] => {
-            error!("用法：add <规则> <输出>");
-            error!("用例：rule add fact1|(fact2&fact3) output1,output2")
+            error!("{}", t!("usage-rule-add"));
+            fail!("{}", t!("example-rule-add"))
         }
         ["remove", idx, ..] => {
             if let Err(e) = ctx.remove_rule(idx) {
-                error!("Error while removing new rules: {}", e);
-                return;
+                fail!("{}", t!("rule-remove-error", error = e));
             }
-            info!("Successfully removed rule with idx {}", idx);
+            info!("{}", t!("rule-removed", id = idx));
         }
         ["remove", ..] => {
-            error!("用法：remove <规则ID>");
+            fail!("{}", t!("usage-rule-remove"));
+        }
+        ["edit", idx, condition, output, ..] => {
+            edit(idx, ctx, |rule| Ok(rule.condition(parse_condition(condition)?).clear_outputs().outputs(output.split(","))))?;
+        }
+        ["edit", idx, ..] => {
+            let Some(rule) = idx.parse::<usize>().ok().and_then(|idx| ctx.rules.get(idx)) else {
+                fail!("{}", t!("rule-edit-error", error = t!("rule-missing", id = idx)));
+            };
+            // Conditions are rendered without spaces so the line splits
+            // into the same arguments again
+            let line = format!(
+                "rule edit {} {} {}",
+                idx,
+                rule.condition().to_string().replace(' ', ""),
+                rule.output().join(",")
+            );
+            info!("{}", t!("rule-editing", id = idx));
+            ctx.prefill = Some(line);
+        }
+        ["set-condition", idx, condition, ..] => {
+            edit(idx, ctx, |rule| Ok(rule.condition(parse_condition(condition)?)))?;
+        }
+        ["set-condition", ..] => {
+            fail!("{}", t!("usage-rule-set-condition"));
+        }
+        ["set-output", idx, output, ..] => {
+            edit(idx, ctx, |rule| Ok(rule.clear_outputs().outputs(output.split(","))))?;
+        }
+        ["set-output", ..] => {
+            fail!("{}", t!("usage-rule-set-output"));
+        }
+        ["rename", idx, name, ..] => {
+            edit(idx, ctx, |rule| Ok(rule.name(*name)))?;
+        }
+        ["rename", ..] => {
+            fail!("{}", t!("usage-rule-rename"));
+        }
+        ["enable", idx, ..] => {
+            edit(idx, ctx, |rule| Ok(rule.enabled(true)))?;
+        }
+        ["disable", idx, ..] => {
+            edit(idx, ctx, |rule| Ok(rule.enabled(false)))?;
+        }
+        ["enable" | "disable", ..] => {
+            fail!("{}", t!("usage-rule-enable"));
+        }
+        ["set", idx, field, value @ ..] if !value.is_empty() => {
+            let value = value.join(" ");
+            match *field {
+                "description" => edit(idx, ctx, |rule| Ok(rule.description(value)))?,
+                "explanation" => edit(idx, ctx, |rule| Ok(rule.explanation(value)))?,
+                "template" => edit(idx, ctx, |rule| Ok(rule.template(value)))?,
+                "author" => edit(idx, ctx, |rule| Ok(rule.author(value)))?,
+                "tags" => {
+                    edit(idx, ctx, |rule| {
+                        Ok(value
+                            .split([',', ' '])
+                            .filter(|tag| !tag.is_empty())
+                            .fold(rule.clear_tags(), |rule, tag| rule.tag(tag)))
+                    })?;
+                }
+                _ => fail!("{}", t!("rule-unknown-field", field = field)),
+            }
+        }
+        ["set", ..] => {
+            error!("{}", t!("usage-rule-set"));
+            fail!("{}", t!("example-rule-set"))
+        }
+        ["export", path, ..] => {
+            if let Err(e) = export(Path::new(path), &ctx.rules) {
+                fail!("{}", t!("rule-export-error", error = e));
+            }
+            info!("{}", t!("rule-exported", count = ctx.rules.len(), path = path));
+        }
+        ["export", ..] => {
+            fail!("{}", t!("usage-rule-export"));
+        }
+        ["import", path, mode @ ..] => {
+            import(path, mode, ctx)?;
+        }
+        ["import", ..] => {
+            fail!("{}", t!("usage-rule-import"));
         }
         [] => {
-            handle_help(&["rule"]).await;
+            handle_help(&["rule"], ctx)?;
+        }
+        [x, ..] => {
+            fail!("{}", t!("unknown-subcommand", subcommand = x))
+        }
+    }
+    Ok(())
+}
+
+/// The filters of `rule find` and `db find`, a word without a flag is a
+/// regex.
+pub(crate) fn parse_query(args: &[&str]) -> Result<Query, String> {
+    fn value(args: &mut dyn Iterator<Item = &&str>, flag: &str) -> Result<String, String> {
+        args.next()
+            .map(|value| value.to_string())
+            .ok_or_else(|| t!("option-missing-value", option = flag))
+    }
+    let mut query = Query::default();
+    let mut args = args.iter().filter(|arg| !arg.is_empty());
+    while let Some(&arg) = args.next() {
+        let pattern = match arg {
+            "--fact" => {
+                query.facts.push(value(&mut args, arg)?);
+                continue;
+            }
+            "--output" => {
+                query.outputs.push(value(&mut args, arg)?);
+                continue;
+            }
+            "--tag" => {
+                query.tags.push(value(&mut args, arg)?);
+                continue;
+            }
+            "--negation" => {
+                query.negation = Some(true);
+                continue;
+            }
+            "--no-negation" => {
+                query.negation = Some(false);
+                continue;
+            }
+            "--regex" => value(&mut args, arg)?,
+            flag if flag.starts_with("--") => return Err(t!("unknown-option", option = flag)),
+            pattern => pattern.to_string(),
+        };
+        let regex = Regex::new(&pattern).map_err(|e| t!("invalid-regex", pattern = pattern, error = e))?;
+        query.pattern = Some(regex);
+    }
+    Ok(query)
+}
+
+/// The found rules with what matched highlighted.
+pub(crate) fn found_table<'a>(query: &Query, rules: impl Iterator<Item = (String, &'a Rule)>) -> String {
+    let mark = |s: &str, matched: bool| if matched { s.yellow().bold().to_string() } else { s.to_string() };
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record([t!("col-id"), t!("col-name"), t!("col-condition"), t!("col-output"), t!("col-tags")]);
+    for (id, rule) in rules {
+        let condition = rule.condition().to_string();
+        let mut highlighted = String::new();
+        let mut end = 0;
+        for range in query.condition_matches(&condition) {
+            highlighted.push_str(&condition[end..range.start]);
+            highlighted.push_str(&mark(&condition[range.clone()], true));
+            end = range.end;
         }
+        highlighted.push_str(&condition[end..]);
+        let list = |facts: &[String], wanted: &[String]| {
+            facts
+                .iter()
+                .map(|fact| mark(fact, wanted.contains(fact)))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        builder.push_record([
+            id,
+            rule.name().unwrap_or_default().to_string(),
+            highlighted,
+            list(rule.output(), &query.outputs),
+            list(rule.tags(), &query.tags),
+        ]);
+    }
+    render_table(builder)
+}
+
+fn edit(idx: &str, ctx: &mut Context, edit: impl FnOnce(RuleBuilder) -> anyhow::Result<RuleBuilder>) -> Outcome {
+    match ctx.edit_rule(idx, edit) {
+        Ok(idx) => info!("{}", t!("rule-edited", id = idx, rule = ctx.rules[idx])),
+        Err(e) => fail!("{}", t!("rule-edit-error", error = e)),
+    }
+    Ok(())
+}
+
+fn parse_condition(condition: &str) -> anyhow::Result<Condition> {
+    condition
+        .parse::<Condition>()
+        .map_err(|e| anyhow::anyhow!(t!("parse-error-at", error = e, position = e.position(), condition = condition)))
+}
+
+fn import(path: &str, mode: &[&str], ctx: &mut Context) -> Outcome {
+    let replace = match mode {
+        [] | ["--merge", ..] => false,
+        ["--replace", ..] => true,
         [x, ..] => {
-            error!("未知子命令: {}", x)
-        } 
+            fail!("{}", t!("rule-import-unknown-mode", option = x));
+        }
+    };
+    let rules = match read(Path::new(path)) {
+        Ok(rules) => rules,
+        Err(e) => {
+            fail!("{}", t!("rule-import-error", path = path, error = e));
+        }
+    };
+    if replace {
+        info!("{}", t!("rule-imported-replacing", count = rules.len(), replaced = ctx.rules.len()));
+        ctx.record(format!("rule import {} --replace", path));
+        ctx.rules = rules;
+        return Ok(());
+    }
+    let total = rules.len();
+    let mut added = 0;
+    ctx.record(format!("rule import {}", path));
+    for rule in rules {
+        let duplicate = ctx
+            .rules
+            .iter()
+            .any(|r| r.condition() == rule.condition() && r.output() == rule.output());
+        if !duplicate {
+            ctx.rules.push(rule);
+            added += 1;
+        }
+    }
+    info!("{}", t!("rule-imported-merging", count = added, existing = total - added));
+    Ok(())
+}
+
+fn export(path: &Path, rules: &[Rule]) -> expert::Result<()> {
+    let Some(dialect) = Dialect::from_path(path) else {
+        return export_rules(path, rules);
+    };
+    let conversion = convert::export(dialect, rules)?;
+    for issue in &conversion.issues {
+        warn!("{}", issue);
+    }
+    std::fs::write(path, conversion.value)?;
+    Ok(())
+}
+
+fn read(path: &Path) -> expert::Result<Vec<Rule>> {
+    let Some(dialect) = Dialect::from_path(path) else {
+        return import_rules(path);
+    };
+    let conversion = convert::import(dialect, &std::fs::read_to_string(path)?)?;
+    for issue in &conversion.issues {
+        warn!("{}", issue);
+    }
+    Ok(conversion.value)
+}
+
+/// Rule bases up to this size are checked under every order by default.
+const ALL_ORDERS_UP_TO: usize = 6;
+const DEFAULT_ORDERS: usize = 100;
+/// Input fact sets tried, smallest first.
+const MAX_INPUTS: usize = 256;
+
+fn confluence(n: Option<&&str>, ctx: &mut Context) -> Outcome {
+    let n = match n.map(|n| n.parse::<usize>()) {
+        None => None,
+        Some(Ok(n)) if n > 0 => Some(n),
+        Some(_) => {
+            fail!("{}", t!("usage-rule-confluence"));
+        }
+    };
+    let count = ctx.rules.len();
+    let permutations = (1..=count).try_fold(1usize, |acc, k| acc.checked_mul(k));
+    let orders = match (n, permutations) {
+        (None, Some(_)) if count <= ALL_ORDERS_UP_TO => confluence::all_orders(count),
+        (Some(n), Some(all)) if n >= all => confluence::all_orders(count),
+        (n, _) => {
+            let identity = (0..count).collect::<Vec<_>>();
+            let mut orders = vec![identity.clone()];
+            for _ in 1..n.unwrap_or(DEFAULT_ORDERS) {
+                let mut order = identity.clone();
+                order.shuffle(&mut rand::thread_rng());
+                orders.push(order);
+            }
+            orders
+        }
+    };
+    let mut inputs = confluence::subsets(&confluence::input_facts(&ctx.rules), MAX_INPUTS);
+    for given in ctx.tests.iter().map(|test| &test.given).chain([&ctx.input]) {
+        if !given.is_empty() && !inputs.contains(given) {
+            inputs.push(given.clone());
+        }
+    }
+    // Every run would log its deduction, keep them quiet
+    let divergences = tracing::subscriber::with_default(
+        tracing::subscriber::NoSubscriber::default(),
+        || confluence::check(&ctx.rules, &inputs, &orders, &ctx.engine),
+    );
+    info!("{}", t!("confluence-ran", inputs = inputs.len(), orders = orders.len()));
+    let text = confluence_text(&divergences, &ctx.rules);
+    let value = json!({
+        "orders": orders.len(),
+        "inputs": inputs.len(),
+        "divergences": divergences,
+    });
+    ctx.print(value, || text);
+    if !divergences.is_empty() {
+        warn!("{}", t!("confluence-diverging", count = divergences.len()));
+    }
+    Ok(())
+}
+
+fn confluence_text(divergences: &[Divergence], rules: &[Rule]) -> String {
+    let Some(minimal) = divergences.iter().min_by_key(|divergence| divergence.input.len()) else {
+        return t!("confluence-confluent");
+    };
+    let mut text = t!("confluence-divergences", count = divergences.len()) + "\n";
+    for divergence in divergences.iter().take(10) {
+        text.push_str(&format!("  {{{}}}\n", divergence.input.join(", ")));
+    }
+    if divergences.len() > 10 {
+        text.push_str(&format!("  {}\n", t!("confluence-more", count = divergences.len() - 10)));
+    }
+    text.push_str(&t!("confluence-minimal", input = minimal.input.join(", ")));
+    text.push('\n');
+    for outcome in &minimal.outcomes {
+        let order = outcome.order.iter().map(|idx| idx.to_string()).collect::<Vec<_>>();
+        text.push_str(&format!(
+            "  {}\n",
+            t!("confluence-order", order = order.join(" "), derived = outcome.derived.join(", "))
+        ));
+    }
+    if minimal.pairs.is_empty() {
+        text.push_str(&t!("confluence-no-pair"));
+    } else {
+        text.push_str(&t!("confluence-pairs"));
+        for (a, b) in &minimal.pairs {
+            text.push_str(&format!(
+                "\n  {}",
+                t!("confluence-pair", first = a, first_rule = rules[*a], second = b, second_rule = rules[*b])
+            ));
+        }
+    }
+    text
+}
+
+fn graph(seg: &[&str], ctx: &mut Context) -> Outcome {
+    let mut format = None;
+    let mut goal = None;
+    let mut path = None;
+    let mut args = seg.iter().filter(|arg| !arg.is_empty());
+    while let Some(&arg) = args.next() {
+        match arg {
+            "--dot" => format = Some("dot"),
+            "--mermaid" => format = Some("mermaid"),
+            "--goal" => match args.next() {
+                Some(&fact) => goal = Some(fact),
+                None => {
+                    fail!("{}", t!("usage-rule-graph"));
+                }
+            },
+            _ if arg.starts_with("--") => {
+                fail!("{}", t!("unknown-option", option = arg));
+            }
+            _ => path = Some(arg),
+        }
+    }
+    let format = format.unwrap_or_else(|| {
+        let ext = path.and_then(|path| Path::new(path).extension()).and_then(|ext| ext.to_str());
+        match ext {
+            Some("mmd" | "md") => "mermaid",
+            _ => "dot",
+        }
+    });
+    let mut graph = Graph::build(&ctx.rules);
+    if let Some(goal) = goal {
+        if !graph.facts.iter().any(|fact| fact == goal) {
+            fail!("{}", t!("graph-unknown-goal", fact = goal));
+        }
+        graph = graph.reaching(goal);
+    }
+    let source = match format {
+        "mermaid" => graph.to_mermaid(&ctx.rules),
+        _ => graph.to_dot(&ctx.rules),
+    };
+    match path {
+        Some(path) => match std::fs::write(path, &source) {
+            Ok(()) => info!("{}", t!("graph-written", format = format, path = path)),
+            Err(e) => fail!("{}", t!("file-write-error", path = path, error = e)),
+        },
+        None => {
+            let value = json!({ "format": format, "graph": graph, "source": source });
+            ctx.print(value, || source.trim_end().to_string());
+        }
     }
-}
\ No newline at end of file
+    Ok(())
+}
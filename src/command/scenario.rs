@@ -0,0 +1,265 @@
+use crate::command::{handle_help, Outcome};
+use crate::command::test::{infer, record_run};
+use expert::store::Run;
+use crate::Context;
+use crate::output::render_table;
+use colored::Colorize;
+use serde_json::json;
+use tracing::info;
+
+pub(crate) async fn handle_scenario(seg: &[&str], ctx: &mut Context) -> Outcome {
+    match seg {
+        ["save", name, facts @ ..] => {
+            save(name, facts, ctx).await?;
+        }
+        ["save", ..] => {
+            fail!("{}", t!("usage-scenario-save"));
+        }
+        ["load", name, ..] => {
+            load(name, ctx).await?;
+        }
+        ["load", ..] => {
+            fail!("{}", t!("usage-scenario-load"));
+        }
+        ["list", ..] => {
+            list(ctx).await?;
+        }
+        ["run", name, ..] => {
+            run(name, ctx).await?;
+        }
+        ["run", ..] => {
+            fail!("{}", t!("usage-scenario-run"));
+        }
+        [] => {
+            handle_help(&["scenario"], ctx)?;
+        }
+        [x, ..] => {
+            fail!("{}", t!("unknown-subcommand", subcommand = x))
+        }
+    }
+    Ok(())
+}
+
+pub(crate) async fn handle_runs(seg: &[&str], ctx: &mut Context) -> Outcome {
+    match seg {
+        ["list", ..] => {
+            list_runs(ctx).await?;
+        }
+        ["show", id, ..] => {
+            show_run(id, ctx).await?;
+        }
+        ["show", ..] => {
+            fail!("{}", t!("usage-runs-show"));
+        }
+        [] => {
+            handle_help(&["runs"], ctx)?;
+        }
+        [x, ..] => {
+            fail!("{}", t!("unknown-subcommand", subcommand = x))
+        }
+    }
+    Ok(())
+}
+
+async fn save(name: &str, facts: &[&str], ctx: &mut Context) -> Outcome {
+    let Some(db) = ctx.db.as_ref() else {
+        fail!("{}", t!("db-not-connected"));
+    };
+    let facts = facts
+        .iter()
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect::<Vec<_>>();
+    if !facts.is_empty() {
+        ctx.input = facts;
+    }
+    if ctx.input.is_empty() {
+        fail!("{}", t!("scenario-no-facts"));
+    }
+    if let Err(e) = db.save_scenario(&ctx.kb, name, &ctx.input).await {
+        fail!("{}", t!("scenario-save-error", error = e));
+    }
+    info!("{}", t!("scenario-saved", name = name, facts = format!("{:?}", ctx.input)));
+    Ok(())
+}
+
+async fn load(name: &str, ctx: &mut Context) -> Outcome {
+    let Some(db) = ctx.db.as_ref() else {
+        fail!("{}", t!("db-not-connected"));
+    };
+    match db.load_scenario(&ctx.kb, name).await {
+        Ok(Some(facts)) => {
+            info!("{}", t!("scenario-loaded", name = name, facts = format!("{:?}", facts)));
+            ctx.input = facts;
+        }
+        Ok(None) => fail!("{}", t!("scenario-missing", name = name)),
+        Err(e) => fail!("{}", t!("db-read-error", error = e)),
+    }
+    Ok(())
+}
+
+async fn list(ctx: &mut Context) -> Outcome {
+    let Some(db) = ctx.db.as_ref() else {
+        fail!("{}", t!("db-not-connected"));
+    };
+    let scenarios = match db.list_scenarios(&ctx.kb).await {
+        Ok(scenarios) => scenarios,
+        Err(e) => {
+            fail!("{}", t!("db-read-error", error = e));
+        }
+    };
+    let value = json!(scenarios
+        .iter()
+        .map(|(name, facts)| json!({ "name": name, "facts": facts }))
+        .collect::<Vec<_>>());
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record([t!("col-name"), t!("col-facts")]);
+    for (name, facts) in scenarios {
+        builder.push_record([name, facts.join(" ")]);
+    }
+    ctx.print(value, || render_table(builder));
+    Ok(())
+}
+
+async fn run(name: &str, ctx: &mut Context) -> Outcome {
+    let Some(db) = ctx.db.as_ref() else {
+        fail!("{}", t!("db-not-connected"));
+    };
+    let facts = match db.load_scenario(&ctx.kb, name).await {
+        Ok(Some(facts)) => facts,
+        Ok(None) => {
+            fail!("{}", t!("scenario-missing", name = name));
+        }
+        Err(e) => {
+            fail!("{}", t!("db-read-error", error = e));
+        }
+    };
+    let previous = match db.last_scenario_run(&ctx.kb, name).await {
+        Ok(previous) => previous,
+        Err(e) => {
+            fail!("{}", t!("db-read-error", error = e));
+        }
+    };
+
+    ctx.input = facts;
+    let mut run = infer(ctx, Some(name));
+    record_run(&mut run, ctx).await?;
+
+    let Some(previous) = previous else {
+        info!("{}", t!("scenario-first-run", name = name, derived = format!("{:?}", run.derived)));
+        ctx.print(json!({ "run": run, "previous": null }), String::new);
+        return Ok(());
+    };
+    let gained = run
+        .derived
+        .iter()
+        .filter(|fact| !previous.derived.contains(fact))
+        .collect::<Vec<_>>();
+    let lost = previous
+        .derived
+        .iter()
+        .filter(|fact| !run.derived.contains(fact))
+        .collect::<Vec<_>>();
+    let value = json!({
+        "run": run,
+        "previous": previous.id,
+        "gained": gained,
+        "lost": lost,
+    });
+    if gained.is_empty() && lost.is_empty() {
+        info!("{}", t!("scenario-unchanged", id = previous.id));
+        ctx.print(value, String::new);
+        return Ok(());
+    }
+    let mut text = t!("scenario-changed", id = previous.id, time = previous.created_at);
+    for fact in gained {
+        text.push_str(&format!("\n  {}", format!("+ {}", fact).green()));
+    }
+    for fact in lost {
+        text.push_str(&format!("\n  {}", format!("- {}", fact).red()));
+    }
+    ctx.print(value, || text);
+    Ok(())
+}
+
+async fn list_runs(ctx: &mut Context) -> Outcome {
+    let Some(db) = ctx.db.as_ref() else {
+        fail!("{}", t!("db-not-connected"));
+    };
+    let runs = match db.list_runs(&ctx.kb).await {
+        Ok(runs) => runs,
+        Err(e) => {
+            fail!("{}", t!("db-read-error", error = e));
+        }
+    };
+    let value = json!(runs);
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record([
+        t!("col-id"),
+        t!("col-scenario"),
+        t!("col-input"),
+        t!("col-derived"),
+        t!("col-cycles"),
+        t!("col-rules-fired"),
+        t!("col-time"),
+    ]);
+    for run in runs {
+        builder.push_record([
+            run.id.to_string(),
+            run.scenario.unwrap_or_default(),
+            run.input.join(" "),
+            run.derived.join(" "),
+            run.cycles.to_string(),
+            run.fired.len().to_string(),
+            run.created_at,
+        ]);
+    }
+    ctx.print(value, || render_table(builder));
+    Ok(())
+}
+
+async fn show_run(id: &str, ctx: &mut Context) -> Outcome {
+    let Some(db) = ctx.db.as_ref() else {
+        fail!("{}", t!("db-not-connected"));
+    };
+    let id = match id.parse::<i64>() {
+        Ok(id) => id,
+        Err(e) => {
+            fail!("{}", t!("invalid-run-id", id = id, error = e));
+        }
+    };
+    let run = match db.load_run(id).await {
+        Ok(Some(run)) => run,
+        Ok(None) => {
+            fail!("{}", t!("run-missing", id = id));
+        }
+        Err(e) => {
+            fail!("{}", t!("db-read-error", error = e));
+        }
+    };
+    let text = run_text(&run);
+    ctx.print(run, || text);
+    Ok(())
+}
+
+fn run_text(run: &Run) -> String {
+    let mut text = t!("run-title", id = run.id, kb = run.kb, time = run.created_at) + "\n";
+    if let Some(scenario) = &run.scenario {
+        text.push_str(&format!("{}\n", t!("run-scenario", scenario = scenario)));
+    }
+    text.push_str(&format!("{}\n", t!("run-input", facts = run.input.join(" "))));
+    text.push_str(&format!("{}\n", t!("derived-facts", facts = run.derived.join(" "))));
+    text.push_str(&format!("{}\n", t!("run-cycles", cycles = run.cycles)));
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record([t!("col-cycle"), t!("col-rule"), t!("col-condition"), t!("col-added")]);
+    for fired in &run.fired {
+        builder.push_record([
+            fired.cycle.to_string(),
+            fired.rule.to_string(),
+            fired.condition.clone(),
+            fired.added.join(","),
+        ]);
+    }
+    text.push_str(&render_table(builder));
+    text
+}
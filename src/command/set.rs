@@ -0,0 +1,82 @@
+use crate::i18n::{self, Lang};
+use crate::output::OutputFormat;
+use crate::command::Outcome;
+use crate::Context;
+use expert::Strategy;
+use serde_json::json;
+use tracing::info;
+
+pub(crate) fn handle_set(seg: &[&str], ctx: &mut Context) -> Outcome {
+    match seg {
+        ["format", value, ..] => match OutputFormat::parse(value) {
+            Some(format) => {
+                ctx.format = format;
+                info!("{}", t!("set-format", format = format!("{:?}", format).to_lowercase()));
+            }
+            None => fail!("{}", t!("set-unknown-format", format = value)),
+        },
+        ["format"] => {
+            fail!("{}", t!("usage-set-format"));
+        }
+        ["lang", value, ..] => match Lang::parse(value) {
+            Some(lang) => {
+                i18n::set_lang(lang);
+                info!("{}", t!("set-lang", lang = lang.code()));
+            }
+            None => fail!("{}", t!("set-unknown-lang", lang = value)),
+        },
+        ["lang"] => {
+            fail!("{}", t!("usage-set-lang"));
+        }
+        ["max-cycles", "none", ..] => {
+            ctx.engine.max_cycles = None;
+            info!("{}", t!("set-max-cycles", max = "none"));
+        }
+        ["max-cycles", value, ..] => match value.parse::<usize>() {
+            Ok(max) => {
+                ctx.engine.max_cycles = Some(max);
+                info!("{}", t!("set-max-cycles", max = max));
+            }
+            Err(_) => fail!("{}", t!("usage-set-max-cycles")),
+        },
+        ["max-cycles"] => {
+            fail!("{}", t!("usage-set-max-cycles"));
+        }
+        ["strategy", value, ..] => match parse_strategy(value) {
+            Some(strategy) => {
+                ctx.engine.strategy = strategy;
+                info!("{}", t!("set-strategy", strategy = value));
+            }
+            None => fail!("{}", t!("usage-set-strategy")),
+        },
+        ["strategy"] => {
+            fail!("{}", t!("usage-set-strategy"));
+        }
+        [] | [""] => {
+            let format = format!("{:?}", ctx.format).to_lowercase();
+            let lang = i18n::lang().code();
+            let max_cycles = ctx.engine.max_cycles.map_or("none".to_string(), |max| max.to_string());
+            let strategy = format!("{:?}", ctx.engine.strategy).to_lowercase();
+            let value = json!({ "format": format, "lang": lang, "engine": ctx.engine });
+            ctx.print(value, || {
+                format!(
+                    "format = {}\nlang = {}\nmax-cycles = {}\nstrategy = {}",
+                    format, lang, max_cycles, strategy
+                )
+            });
+        }
+        [x, ..] => {
+            fail!("{}", t!("set-unknown", setting = x))
+        }
+    }
+    Ok(())
+}
+
+fn parse_strategy(value: &str) -> Option<Strategy> {
+    match value {
+        "all" => Some(Strategy::All),
+        "first" => Some(Strategy::First),
+        "specificity" => Some(Strategy::Specificity),
+        _ => None,
+    }
+}
@@ -0,0 +1,70 @@
+use expert::rule::Facts;
+use expert::store::Run;
+use crate::command::Outcome;
+use crate::Context;
+use tracing::info;
+
+pub(crate) async fn handle_test(seg: &[&str], ctx: &mut Context) -> Outcome {
+    let input = seg
+        .iter()
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect::<Vec<_>>();
+    if !input.is_empty() {
+        ctx.input = input;
+    }
+    let mut run = infer(ctx, None);
+    let recorded = record_run(&mut run, ctx).await;
+    let derived = if run.derived.iter().any(|fact| ctx.labels.contains_key(fact)) {
+        ctx.explainer().facts(&run.derived)
+    } else {
+        run.derived.join(" ")
+    };
+    ctx.print(&run, || t!("derived-facts", facts = derived));
+    recorded
+}
+
+/// Runs deduction on the current input facts, the returned run has id 0
+/// until [`record_run`] stores it.
+pub(crate) fn infer(ctx: &mut Context, scenario: Option<&str>) -> Run {
+    let mut facts = Facts::from(ctx.input.clone());
+    facts.record_coverage();
+    // The library logs the deduction in English, it is logged from the
+    // catalog below instead
+    let cycles = tracing::subscriber::with_default(tracing::subscriber::NoSubscriber::default(), || {
+        facts.deduce_with(&ctx.rules, &ctx.engine)
+    });
+    let explainer = ctx.explainer();
+    for firing in facts.fired() {
+        let rule = &ctx.rules[firing.rule];
+        let sentence = explainer.sentence(rule, &firing.added).unwrap_or_else(|| {
+            t!(
+                "explain-fired",
+                condition = explainer.condition(rule.condition()),
+                outputs = explainer.facts(&firing.added)
+            )
+        });
+        info!("{}", sentence);
+    }
+    info!("{}", t!("explain-done", cycles = cycles, facts = facts.all().join(", ")));
+    if let Some(coverage) = facts.coverage() {
+        ctx.coverage.merge(coverage);
+    }
+    let run = Run::record(&ctx.kb, scenario, &facts, cycles, &ctx.rules);
+    ctx.last_deduction = Some((facts, ctx.rules.clone()));
+    run
+}
+
+/// Records the run when a database is connected.
+pub(crate) async fn record_run(run: &mut Run, ctx: &Context) -> Outcome {
+    if let Some(db) = ctx.db.as_ref() {
+        match db.insert_run(run).await {
+            Ok(id) => {
+                info!("{}", t!("run-recorded", id = id));
+                run.id = id;
+            }
+            Err(e) => fail!("{}", t!("run-record-error", error = e)),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,164 @@
+//! Settings read at startup from `config.toml` in `$XDG_CONFIG_HOME/expert`
+//! (`~/.config/expert` when it is not set), or from the file given with
+//! `--config`:
+//!
+//! ```toml
+//! lang = "en-US"
+//! color = true
+//! prompt = "expert> "
+//!
+//! [database]
+//! url = "sqlite://rules.db"
+//!
+//! [history]
+//! path = "~/.local/state/expert/history.txt"
+//! size = 1000
+//!
+//! [log]
+//! level = "warn,expert=info"
+//! format = "compact"
+//!
+//! [engine]
+//! max_cycles = 50
+//! strategy = "specificity"
+//! ```
+//!
+//! Everything is optional, `RUST_LOG` still overrides the log level.
+
+use anyhow::{Context as _, Result};
+use expert::Options;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// `zh-CN` or `en-US`, taken from the environment when not set.
+    pub lang: Option<String>,
+    /// Colored output and logs, on in the REPL and off otherwise when not
+    /// set.
+    pub color: Option<bool>,
+    pub prompt: String,
+    pub database: Database,
+    pub history: History,
+    pub log: Log,
+    pub engine: Options,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Database {
+    /// Connected to and loaded on startup.
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct History {
+    /// Relative paths are relative to the working directory, `~/` to the
+    /// home directory.
+    pub path: String,
+    /// Number of lines kept.
+    pub size: usize,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Log {
+    /// An `EnvFilter` directive like `info` or `warn,expert=debug`.
+    pub level: String,
+    pub format: LogFormat,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Compact,
+    Full,
+    Pretty,
+    Json,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            lang: None,
+            color: None,
+            prompt: ">> ".to_string(),
+            database: Database::default(),
+            history: History::default(),
+            log: Log::default(),
+            engine: Options::default(),
+        }
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History {
+            path: "history.txt".to_string(),
+            size: 100,
+        }
+    }
+}
+
+impl Default for Log {
+    fn default() -> Self {
+        Log {
+            level: "info".to_string(),
+            format: LogFormat::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Reads `path`, or the file at [`default_path`] when there is one.
+    pub fn load(path: Option<&Path>) -> Result<Config> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match default_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Config::default()),
+            },
+        };
+        let source = std::fs::read_to_string(&path)
+            .with_context(|| t!("config-read-error", path = path.display()))?;
+        toml::from_str(&source).with_context(|| t!("config-parse-error", path = path.display()))
+    }
+
+    pub fn history_path(&self) -> PathBuf {
+        match (self.history.path.strip_prefix("~/"), std::env::var_os("HOME")) {
+            (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+            _ => PathBuf::from(&self.history.path),
+        }
+    }
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(dir.join("expert").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expert::Strategy;
+
+    #[test]
+    fn test_config() {
+        let config: Config = toml::from_str(
+            "prompt = \"? \"\n[history]\nsize = 5\n[engine]\nmax_cycles = 3\nstrategy = \"first\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.prompt, "? ");
+        assert_eq!((config.history.path.as_str(), config.history.size), ("history.txt", 5));
+        assert_eq!(config.engine.max_cycles, Some(3));
+        assert_eq!(config.engine.strategy, Strategy::First);
+        assert_eq!(config.log.format, LogFormat::Compact);
+        assert!(toml::from_str::<Config>("promt = \">\"").is_err());
+    }
+}
@@ -0,0 +1,229 @@
+//! Whether the conclusions of a rule base depend on the order of its rules.
+//!
+//! Facts are only ever added, so the order can only matter through negation:
+//! a rule testing `!f` fires or not depending on whether the rule deriving
+//! `f` came before it. [`check`] runs every input under every given order
+//! and reports the inputs whose conclusions differ.
+
+use crate::rule::{Condition, Facts, Options, Rule};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// The conclusions reached under one order of the rules.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Outcome {
+    /// Rule indices in the order they were applied.
+    pub order: Vec<usize>,
+    /// Derived facts, sorted.
+    pub derived: Vec<String>,
+}
+
+/// An input whose conclusions depend on the rule order.
+#[derive(Debug, Clone, Serialize)]
+pub struct Divergence {
+    pub input: Vec<String>,
+    /// One outcome for each distinct set of conclusions.
+    pub outcomes: Vec<Outcome>,
+    /// `(a, b)` where rule `a` tests the negation of a fact rule `b`
+    /// derives, and the first two outcomes ran them in opposite orders.
+    pub pairs: Vec<(usize, usize)>,
+}
+
+/// Runs each input under each order with `options`, inputs that differ are
+/// returned in the order they were given, so with inputs sorted by size the
+/// first one is a minimal counterexample.
+pub fn check(
+    rules: &[Rule],
+    inputs: &[Vec<String>],
+    orders: &[Vec<usize>],
+    options: &Options,
+) -> Vec<Divergence> {
+    let ordered = orders
+        .iter()
+        .map(|order| order.iter().map(|&idx| rules[idx].clone()).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let mut divergences = vec![];
+    for input in inputs {
+        let mut outcomes: Vec<Outcome> = vec![];
+        for (order, rules) in orders.iter().zip(&ordered) {
+            let mut facts = Facts::from(input.clone());
+            facts.deduce_with(rules, options);
+            let mut derived = facts.derived().to_vec();
+            derived.sort();
+            if !outcomes.iter().any(|outcome| outcome.derived == derived) {
+                outcomes.push(Outcome {
+                    order: order.clone(),
+                    derived,
+                });
+            }
+        }
+        if outcomes.len() > 1 {
+            let pairs = responsible_pairs(rules, &outcomes[0], &outcomes[1]);
+            divergences.push(Divergence {
+                input: input.clone(),
+                outcomes,
+                pairs,
+            });
+        }
+    }
+    divergences
+}
+
+fn responsible_pairs(rules: &[Rule], first: &Outcome, second: &Outcome) -> Vec<(usize, usize)> {
+    let position = |order: &[usize], idx: usize| order.iter().position(|&i| i == idx);
+    let derived = first.derived.iter().chain(&second.derived).collect::<BTreeSet<_>>();
+    let mut pairs = vec![];
+    for (a, rule) in rules.iter().enumerate() {
+        for fact in negated_facts(rule.condition()) {
+            if !derived.contains(&fact) {
+                continue;
+            }
+            for (b, other) in rules.iter().enumerate() {
+                if a == b || !other.output().contains(&fact) {
+                    continue;
+                }
+                let before = |order: &[usize]| position(order, a) < position(order, b);
+                if before(&first.order) != before(&second.order) && !pairs.contains(&(a, b)) {
+                    pairs.push((a, b));
+                }
+            }
+        }
+    }
+    pairs
+}
+
+/// Facts that appear under an odd number of negations.
+pub fn negated_facts(condition: &Condition) -> Vec<String> {
+    fn walk(condition: &Condition, negated: bool, facts: &mut Vec<String>) {
+        match condition {
+            Condition::Fact(fact) => {
+                if negated && !facts.contains(fact) {
+                    facts.push(fact.clone());
+                }
+            }
+            Condition::And(lhs, rhs) | Condition::Or(lhs, rhs) => {
+                walk(lhs, negated, facts);
+                walk(rhs, negated, facts);
+            }
+            Condition::Not(inner) => walk(inner, !negated, facts),
+        }
+    }
+    let mut facts = vec![];
+    walk(condition, false, &mut facts);
+    facts
+}
+
+/// Facts tested by some condition that no rule derives, the ones an input
+/// is made of.
+pub fn input_facts(rules: &[Rule]) -> Vec<String> {
+    fn walk(condition: &Condition, facts: &mut BTreeSet<String>) {
+        match condition {
+            Condition::Fact(fact) => {
+                facts.insert(fact.clone());
+            }
+            Condition::And(lhs, rhs) | Condition::Or(lhs, rhs) => {
+                walk(lhs, facts);
+                walk(rhs, facts);
+            }
+            Condition::Not(inner) => walk(inner, facts),
+        }
+    }
+    let mut facts = BTreeSet::new();
+    for rule in rules {
+        walk(rule.condition(), &mut facts);
+    }
+    facts
+        .into_iter()
+        .filter(|fact| !rules.iter().any(|rule| rule.output().contains(fact)))
+        .collect()
+}
+
+/// Subsets of `facts` from the smallest up, stopping before `limit`.
+pub fn subsets(facts: &[String], limit: usize) -> Vec<Vec<String>> {
+    let mut subsets = vec![vec![]];
+    let mut last = vec![(vec![], 0)];
+    while !last.is_empty() && subsets.len() < limit {
+        let mut next = vec![];
+        for (subset, from) in &last {
+            for (idx, fact) in facts.iter().enumerate().skip(*from) {
+                let mut subset: Vec<String> = subset.clone();
+                subset.push(fact.clone());
+                next.push((subset, idx + 1));
+            }
+        }
+        for (subset, _) in &next {
+            if subsets.len() >= limit {
+                break;
+            }
+            subsets.push(subset.clone());
+        }
+        last = next;
+    }
+    subsets
+}
+
+/// Every order of `n` rules, starting with the given one.
+pub fn all_orders(n: usize) -> Vec<Vec<usize>> {
+    fn permute(order: &mut Vec<usize>, k: usize, orders: &mut Vec<Vec<usize>>) {
+        if k == order.len() {
+            orders.push(order.clone());
+            return;
+        }
+        for i in k..order.len() {
+            order.swap(k, i);
+            permute(order, k + 1, orders);
+            order.swap(k, i);
+        }
+    }
+    let mut orders = vec![];
+    permute(&mut (0..n).collect(), 0, &mut orders);
+    orders
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let rules = vec![
+            Rule::new("!b", "c").unwrap(),
+            Rule::new("a", "b").unwrap(),
+            Rule::new("a & c", "d").unwrap(),
+        ];
+        assert_eq!(input_facts(&rules), ["a"]);
+        assert_eq!(all_orders(3).len(), 6);
+        assert_eq!(all_orders(3)[0], [0, 1, 2]);
+
+        let inputs = subsets(&input_facts(&rules), 100);
+        assert_eq!(inputs, [vec![], vec!["a".to_string()]]);
+        let divergences = check(&rules, &inputs, &all_orders(3), &Options::default());
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].input, ["a"]);
+        assert_eq!(divergences[0].outcomes[0].derived, ["b", "c", "d"]);
+        assert_eq!(divergences[0].outcomes[1].derived, ["b"]);
+        assert_eq!(divergences[0].pairs, [(0, 1)]);
+
+        let rules = vec![Rule::new("a", "b").unwrap(), Rule::new("b", "c").unwrap()];
+        let inputs = [vec!["a".to_string()]];
+        assert!(check(&rules, &inputs, &all_orders(2), &Options::default()).is_empty());
+
+        // In a single cycle the chain only gets to `c` in rule order
+        let options = Options {
+            max_cycles: Some(1),
+            ..Options::default()
+        };
+        let divergences = check(&rules, &inputs, &all_orders(2), &options);
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].outcomes[0].derived, ["b", "c"]);
+        assert_eq!(divergences[0].outcomes[1].derived, ["b"]);
+    }
+
+    #[test]
+    fn test_subsets() {
+        let facts = ["a", "b", "c"].map(String::from);
+        let subsets = subsets(&facts, 5);
+        assert_eq!(subsets.len(), 5);
+        assert_eq!(subsets[4], ["a", "b"]);
+    }
+}
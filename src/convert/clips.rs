@@ -0,0 +1,345 @@
+//! A subset of CLIPS `defrule`: patterns made of a single symbol such as
+//! `(mammal)`, combined with `and`, `or` and `not`, and `assert` actions of
+//! the same kind of facts.
+
+use crate::convert::{negation_issues, rule_name, Conversion};
+use crate::rule::{is_fact_name, Condition, Rule};
+use crate::error::{Error, ImportError, LineError, Result};
+
+/// Symbols that CLIPS reads as conditional elements rather than facts.
+const RESERVED: [&str; 7] = ["and", "or", "not", "test", "exists", "forall", "logical"];
+
+pub fn export(rules: &[Rule]) -> Conversion<String> {
+    let mut issues = negation_issues(rules);
+    let mut out = String::new();
+    for (idx, rule) in rules.iter().enumerate() {
+        let name = rule_name(rule, idx);
+        let mut facts = vec![];
+        crate::convert::condition_facts(&rule.condition, &mut facts);
+        facts.extend(rule.output.iter().cloned());
+        for fact in facts.iter().filter(|fact| RESERVED.contains(&fact.as_str())) {
+            issues.push(format!(
+                "{}: fact {} is a CLIPS keyword and will be read as a conditional element",
+                name, fact
+            ));
+        }
+
+        out.push_str(&format!("(defrule {}", name));
+        if let Some(description) = &rule.description {
+            out.push_str(&format!(" \"{}\"", description.replace('"', "\\\"")));
+        }
+        out.push('\n');
+        for pattern in patterns(&rule.condition) {
+            out.push_str(&format!("   {}\n", pattern));
+        }
+        let asserts = rule
+            .output
+            .iter()
+            .map(|fact| format!("({})", fact))
+            .collect::<Vec<_>>()
+            .join(" ");
+        out.push_str(&format!("   =>\n   (assert {}))\n\n", asserts));
+    }
+    Conversion { value: out, issues }
+}
+
+/// A top level conjunction is written as separate patterns.
+fn patterns(condition: &Condition) -> Vec<String> {
+    match condition {
+        Condition::And(lhs, rhs) => {
+            let mut patterns = patterns(lhs);
+            patterns.extend(self::patterns(rhs));
+            patterns
+        }
+        _ => vec![element(condition)],
+    }
+}
+
+fn element(condition: &Condition) -> String {
+    match condition {
+        Condition::Fact(fact) => format!("({})", fact),
+        Condition::And(..) => format!("(and {})", patterns(condition).join(" ")),
+        Condition::Or(lhs, rhs) => format!("(or {} {})", element(lhs), element(rhs)),
+        Condition::Not(inner) => format!("(not {})", element(inner)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Sexp {
+    Atom(String),
+    Str(String),
+    List(usize, Vec<Sexp>),
+}
+
+impl std::fmt::Display for Sexp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Sexp::Atom(atom) => write!(f, "{}", atom),
+            Sexp::Str(s) => write!(f, "{:?}", s),
+            Sexp::List(_, items) => {
+                let items = items.iter().map(|item| item.to_string()).collect::<Vec<_>>();
+                write!(f, "({})", items.join(" "))
+            }
+        }
+    }
+}
+
+pub fn import(source: &str) -> Result<Conversion<Vec<Rule>>> {
+    let mut rules = vec![];
+    let mut issues = vec![];
+    let sexps = read(source).map_err(|e| Error::Import(ImportError(vec![e])))?;
+    for sexp in sexps {
+        let Sexp::List(line, items) = &sexp else {
+            issues.push(format!("stray {} outside of a construct, skipped", sexp));
+            continue;
+        };
+        match items.first() {
+            Some(Sexp::Atom(head)) if head == "defrule" => {
+                match defrule(&items[1..], &mut issues) {
+                    Ok(Some(rule)) => rules.push(rule),
+                    Ok(None) => {}
+                    Err(e) => issues.push(format!("line {}: {}, rule skipped", line, e)),
+                }
+            }
+            Some(Sexp::Atom(head)) => {
+                issues.push(format!("line {}: {} is not supported, skipped", line, head))
+            }
+            _ => issues.push(format!("line {}: expected a construct, skipped", line)),
+        }
+    }
+    Ok(Conversion {
+        value: rules,
+        issues,
+    })
+}
+
+fn defrule(items: &[Sexp], issues: &mut Vec<String>) -> Result<Option<Rule>, String> {
+    let Some(Sexp::Atom(name)) = items.first() else {
+        return Err("defrule without a name".to_string());
+    };
+    let mut rest = &items[1..];
+    let mut description = None;
+    if let Some(Sexp::Str(comment)) = rest.first() {
+        description = Some(comment.clone());
+        rest = &rest[1..];
+    }
+    let Some(arrow) = rest.iter().position(|item| *item == Sexp::Atom("=>".into())) else {
+        return Err(format!("{} has no =>", name));
+    };
+    let (lhs, rhs) = (&rest[..arrow], &rest[arrow + 1..]);
+
+    let mut condition: Option<Condition> = None;
+    for item in lhs {
+        if let Sexp::List(line, items) = item {
+            if items.first() == Some(&Sexp::Atom("declare".into())) {
+                issues.push(format!(
+                    "line {}: {}: declare {} ignored, rules fire in list order",
+                    line,
+                    name,
+                    items[1..].iter().map(|i| i.to_string()).collect::<String>()
+                ));
+                continue;
+            }
+        }
+        let element = element_from(item)?;
+        condition = Some(match condition {
+            Some(condition) => condition.and(element),
+            None => element,
+        });
+    }
+    let Some(condition) = condition else {
+        return Err(format!("{} has no patterns", name));
+    };
+
+    let mut output = vec![];
+    for action in rhs {
+        match action {
+            Sexp::List(_, items) if items.first() == Some(&Sexp::Atom("assert".into())) => {
+                for fact in &items[1..] {
+                    output.push(single_fact(fact)?);
+                }
+            }
+            Sexp::List(line, _) => {
+                issues.push(format!("line {}: {}: action {} dropped", line, name, action))
+            }
+            _ => issues.push(format!("{}: action {} dropped", name, action)),
+        }
+    }
+    if output.is_empty() {
+        issues.push(format!("{}: asserts nothing, skipped", name));
+        return Ok(None);
+    }
+
+    Ok(Some(Rule {
+        name: Some(name.clone()).filter(|name| is_fact_name(name)),
+        description,
+        ..Rule::plain(condition, output)
+    }))
+}
+
+fn element_from(sexp: &Sexp) -> Result<Condition, String> {
+    let Sexp::List(_, items) = sexp else {
+        return Err(format!("{} is not a pattern", sexp));
+    };
+    match items.as_slice() {
+        [Sexp::Atom(op), inner] if op == "not" => Ok(element_from(inner)?.not()),
+        [Sexp::Atom(op), first, rest @ ..] if op == "and" || op == "or" => {
+            let mut condition = element_from(first)?;
+            for item in rest {
+                let rhs = element_from(item)?;
+                condition = if op == "and" {
+                    condition.and(rhs)
+                } else {
+                    condition.or(rhs)
+                };
+            }
+            Ok(condition)
+        }
+        [Sexp::Atom(op), ..] if RESERVED.contains(&op.as_str()) => {
+            Err(format!("{} conditional elements are not supported", op))
+        }
+        _ => Ok(Condition::Fact(single_fact(sexp)?)),
+    }
+}
+
+fn single_fact(sexp: &Sexp) -> Result<String, String> {
+    match sexp {
+        Sexp::List(_, items) => match items.as_slice() {
+            [Sexp::Atom(fact)] if fact.starts_with('?') || fact.starts_with('$') => {
+                Err(format!("variable {} is not supported", fact))
+            }
+            [Sexp::Atom(fact)] if is_fact_name(fact) => Ok(fact.clone()),
+            [Sexp::Atom(fact)] => Err(format!("symbol {} is not a valid fact name", fact)),
+            _ => Err(format!("pattern {} has fields, only single symbol facts are supported", sexp)),
+        },
+        Sexp::Atom(atom) if atom.starts_with('?') => Err(format!("variable {} is not supported", atom)),
+        _ => Err(format!("{} is not a pattern", sexp)),
+    }
+}
+
+fn read(source: &str) -> Result<Vec<Sexp>, LineError> {
+    let mut chars = source.chars().peekable();
+    let mut line = 1;
+    let mut stack: Vec<(usize, Vec<Sexp>)> = vec![(0, vec![])];
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            ';' => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+            }
+            '(' => stack.push((line, vec![])),
+            ')' => {
+                let Some((start, items)) = stack.pop().filter(|_| !stack.is_empty()) else {
+                    return Err(syntax_error(line, "unbalanced ')'"));
+                };
+                stack
+                    .last_mut()
+                    .expect("the top level is never popped")
+                    .1
+                    .push(Sexp::List(start, items));
+            }
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => s.extend(chars.next()),
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            s.push(c)
+                        }
+                        None => return Err(syntax_error(line, "unterminated string")),
+                    }
+                }
+                stack.last_mut().unwrap().1.push(Sexp::Str(s));
+            }
+            c => {
+                let mut atom = c.to_string();
+                while let Some(c) =
+                    chars.next_if(|c| !c.is_whitespace() && !"()\";".contains(*c))
+                {
+                    atom.push(c);
+                }
+                stack.last_mut().unwrap().1.push(Sexp::Atom(atom));
+            }
+        }
+    }
+    if stack.len() != 1 {
+        let line = stack.last().map(|(l, _)| *l).unwrap_or(line);
+        return Err(syntax_error(line, "unbalanced '('"));
+    }
+    Ok(stack.pop().unwrap().1)
+}
+
+fn syntax_error(line: usize, message: &str) -> LineError {
+    LineError {
+        line,
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import() {
+        let source = r#"
+; animals
+(deftemplate animal (slot name))
+(defrule tiger "Striped big cats"
+   (declare (salience 10))
+   (mammal) (carnivore)
+   (or (stripes) (not (spots)))
+   =>
+   (printout t "tiger" crlf)
+   (assert (tiger) (big_cat)))
+(defrule colour
+   (color ?c)
+   =>
+   (assert (coloured)))
+"#;
+        let conversion = import(source).unwrap();
+        assert_eq!(conversion.value.len(), 1);
+        let tiger = &conversion.value[0];
+        assert_eq!(tiger.name.as_deref(), Some("tiger"));
+        assert_eq!(tiger.description.as_deref(), Some("Striped big cats"));
+        assert_eq!(
+            tiger.condition.to_string(),
+            "((mammal & carnivore) & (stripes | !spots))"
+        );
+        assert_eq!(tiger.output, ["tiger", "big_cat"]);
+
+        let issues = conversion.issues.join("\n");
+        assert!(issues.contains("deftemplate is not supported"), "{}", issues);
+        assert!(issues.contains("salience"), "{}", issues);
+        assert!(issues.contains("action (printout"), "{}", issues);
+        assert!(issues.contains("has fields"), "{}", issues);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let rules = vec![
+            Rule::new("mammal & (stripes | !spots) & carnivore", "tiger,big_cat").unwrap(),
+            Rule::new("!(a & b)", "c").unwrap(),
+        ];
+        let exported = export(&rules);
+        assert!(exported.issues.is_empty());
+        let imported = import(&exported.value).unwrap();
+        assert!(imported.issues.is_empty());
+        for (rule, imported) in rules.iter().zip(&imported.value) {
+            assert_eq!(rule.condition, imported.condition);
+            assert_eq!(rule.output, imported.output);
+        }
+    }
+
+    #[test]
+    fn test_export_reports_keywords() {
+        let rules = vec![Rule::new("test", "ok").unwrap()];
+        let exported = export(&rules);
+        assert_eq!(exported.issues.len(), 1);
+    }
+}
@@ -0,0 +1,70 @@
+//! Drools DRL. Facts are instances of a declared `Fact` type carrying their
+//! name, negations are pushed down to single patterns.
+
+use crate::convert::{negation_issues, negation_normal_form, rule_name, Conversion};
+use crate::rule::{Condition, Rule};
+
+pub fn export(rules: &[Rule]) -> Conversion<String> {
+    let issues = negation_issues(rules);
+    let mut out = String::from("package expert;\n\ndeclare Fact\n    name : String\nend\n");
+    for (idx, rule) in rules.iter().enumerate() {
+        out.push('\n');
+        if let Some(description) = &rule.description {
+            out.push_str(&format!("// {}\n", description.replace('\n', " ")));
+        }
+        out.push_str(&format!("rule \"{}\"\nwhen\n", rule_name(rule, idx)));
+        for pattern in patterns(&negation_normal_form(&rule.condition)) {
+            out.push_str(&format!("    {}\n", pattern));
+        }
+        out.push_str("then\n");
+        for fact in &rule.output {
+            out.push_str(&format!("    insert(new Fact({}));\n", string(fact)));
+        }
+        out.push_str("end\n");
+    }
+    Conversion { value: out, issues }
+}
+
+fn patterns(condition: &Condition) -> Vec<String> {
+    match condition {
+        Condition::And(lhs, rhs) => {
+            let mut patterns = patterns(lhs);
+            patterns.extend(self::patterns(rhs));
+            patterns
+        }
+        _ => vec![element(condition)],
+    }
+}
+
+fn element(condition: &Condition) -> String {
+    match condition {
+        Condition::Fact(fact) => format!("Fact(name == {})", string(fact)),
+        Condition::Not(inner) => format!("not {}", element(inner)),
+        Condition::And(lhs, rhs) => format!("({} and {})", element(lhs), element(rhs)),
+        Condition::Or(lhs, rhs) => format!("({} or {})", element(lhs), element(rhs)),
+    }
+}
+
+fn string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export() {
+        let rule = Rule::new("mammal & !(stripes & spots)", "cat").unwrap();
+        let exported = export(&[rule]);
+        assert!(exported.value.ends_with(
+            "rule \"rule_0\"\n\
+             when\n    \
+             Fact(name == \"mammal\")\n    \
+             (not Fact(name == \"stripes\") or not Fact(name == \"spots\"))\n\
+             then\n    \
+             insert(new Fact(\"cat\"));\n\
+             end\n"
+        ));
+    }
+}
@@ -0,0 +1,136 @@
+//! JSON Logic. Conditions use `and`, `or`, `!` and `var`, evaluated against
+//! data where every known fact is `true`. A file holds a list of
+//! `{"name", "description", "condition", "output"}` objects.
+
+use crate::convert::Conversion;
+use crate::rule::{is_fact_name, Condition, Rule};
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Serialize, Deserialize)]
+struct LogicRule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    condition: Value,
+    output: Vec<String>,
+}
+
+pub fn export(rules: &[Rule]) -> Result<Conversion<String>> {
+    let rules = rules
+        .iter()
+        .map(|rule| LogicRule {
+            name: rule.name.clone(),
+            description: rule.description.clone(),
+            condition: logic(&rule.condition),
+            output: rule.output.clone(),
+        })
+        .collect::<Vec<_>>();
+    Ok(Conversion {
+        value: serde_json::to_string_pretty(&rules)? + "\n",
+        issues: vec![],
+    })
+}
+
+fn logic(condition: &Condition) -> Value {
+    match condition {
+        Condition::Fact(fact) => json!({ "var": fact }),
+        Condition::And(lhs, rhs) => json!({ "and": [logic(lhs), logic(rhs)] }),
+        Condition::Or(lhs, rhs) => json!({ "or": [logic(lhs), logic(rhs)] }),
+        Condition::Not(inner) => json!({ "!": [logic(inner)] }),
+    }
+}
+
+pub fn import(source: &str) -> Result<Conversion<Vec<Rule>>> {
+    let records: Vec<LogicRule> = serde_json::from_str(source)?;
+    let mut rules = vec![];
+    let mut issues = vec![];
+    for (idx, record) in records.into_iter().enumerate() {
+        let name = record.name.clone().unwrap_or_else(|| format!("rule_{}", idx));
+        let condition = match from_logic(&record.condition) {
+            Ok(condition) => condition,
+            Err(e) => {
+                issues.push(format!("{}: {}, rule skipped", name, e));
+                continue;
+            }
+        };
+        if let Some(fact) = record.output.iter().find(|fact| !is_fact_name(fact)) {
+            issues.push(format!("{}: invalid output fact {:?}, rule skipped", name, fact));
+            continue;
+        }
+        if record.output.is_empty() {
+            issues.push(format!("{}: no output, rule skipped", name));
+            continue;
+        }
+        rules.push(Rule {
+            name: record.name.filter(|name| is_fact_name(name)),
+            description: record.description,
+            ..Rule::plain(condition, record.output)
+        });
+    }
+    Ok(Conversion {
+        value: rules,
+        issues,
+    })
+}
+
+fn from_logic(value: &Value) -> Result<Condition, String> {
+    let Value::Object(map) = value else {
+        return Err(format!("{} is not an operation", value));
+    };
+    let [(op, args)] = map.iter().collect::<Vec<_>>()[..] else {
+        return Err(format!("{} should have exactly one operator", value));
+    };
+    let args = match args {
+        Value::Array(args) => args.clone(),
+        arg => vec![arg.clone()],
+    };
+    match (op.as_str(), args.as_slice()) {
+        ("var", [Value::String(fact)]) if is_fact_name(fact) => Ok(Condition::Fact(fact.clone())),
+        ("var", _) => Err(format!("var {} is not a plain fact name", value)),
+        ("!", [inner]) => Ok(from_logic(inner)?.not()),
+        ("!!", [inner]) => from_logic(inner),
+        ("and" | "or", [first, rest @ ..]) => {
+            let mut condition = from_logic(first)?;
+            for arg in rest {
+                let rhs = from_logic(arg)?;
+                condition = if op == "and" {
+                    condition.and(rhs)
+                } else {
+                    condition.or(rhs)
+                };
+            }
+            Ok(condition)
+        }
+        _ => Err(format!("operator {:?} is not supported", op)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let rules = vec![Rule::new("a & !(b | c)", "d").unwrap()];
+        let exported = export(&rules).unwrap();
+        assert!(exported.value.contains(r#""!": ["#));
+        let imported = import(&exported.value).unwrap();
+        assert!(imported.issues.is_empty());
+        assert_eq!(imported.value[0].condition, rules[0].condition);
+    }
+
+    #[test]
+    fn test_unsupported_operators() {
+        let source = r#"[
+            {"condition": {"and": [{"var": "a"}, {"var": "b"}, {"var": "c"}]}, "output": ["d"]},
+            {"name": "cmp", "condition": {">": [{"var": "age"}, 18]}, "output": ["adult"]}
+        ]"#;
+        let imported = import(source).unwrap();
+        assert_eq!(imported.value.len(), 1);
+        assert_eq!(imported.value[0].condition.to_string(), "((a & b) & c)");
+        assert_eq!(imported.issues, ["cmp: operator \">\" is not supported, rule skipped"]);
+    }
+}
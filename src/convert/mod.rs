@@ -0,0 +1,167 @@
+//! Converters between rules and other rule languages. Whatever can't be
+//! carried over faithfully ends up in [`Conversion::issues`] so it can be
+//! shown to the user instead of disappearing.
+
+use crate::rule::{Condition, Rule};
+use crate::error::{Error, Result};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+pub mod clips;
+pub mod drools;
+pub mod jsonlogic;
+pub mod prolog;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dialect {
+    Clips,
+    Prolog,
+    Drools,
+    JsonLogic,
+}
+
+impl Dialect {
+    /// `.clp`, `.pl`, `.drl` and `.jsonlogic` files, other extensions are
+    /// left to [`crate::exchange`].
+    pub fn from_path(path: &Path) -> Option<Dialect> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        Some(match ext.as_str() {
+            "clp" => Dialect::Clips,
+            "pl" => Dialect::Prolog,
+            "drl" => Dialect::Drools,
+            "jsonlogic" => Dialect::JsonLogic,
+            _ => return None,
+        })
+    }
+}
+
+/// The result of a conversion along with everything that was lost on the way.
+#[derive(Debug)]
+pub struct Conversion<T> {
+    pub value: T,
+    pub issues: Vec<String>,
+}
+
+pub fn export(dialect: Dialect, rules: &[Rule]) -> Result<Conversion<String>> {
+    Ok(match dialect {
+        Dialect::Clips => clips::export(rules),
+        Dialect::Prolog => prolog::export(rules),
+        Dialect::Drools => drools::export(rules),
+        Dialect::JsonLogic => jsonlogic::export(rules)?,
+    })
+}
+
+pub fn import(dialect: Dialect, source: &str) -> Result<Conversion<Vec<Rule>>> {
+    match dialect {
+        Dialect::Clips => clips::import(source),
+        Dialect::JsonLogic => jsonlogic::import(source),
+        Dialect::Prolog => Err(Error::Unsupported(
+            "importing Prolog is not supported, only exporting".to_string(),
+        )),
+        Dialect::Drools => Err(Error::Unsupported(
+            "importing Drools is not supported, only exporting".to_string(),
+        )),
+    }
+}
+
+/// How a rule is called in the exported file, its name or its position.
+pub(crate) fn rule_name(rule: &Rule, idx: usize) -> String {
+    rule.name.clone().unwrap_or_else(|| format!("rule_{}", idx))
+}
+
+/// Every fact a condition mentions, in order of appearance.
+pub(crate) fn condition_facts(condition: &Condition, facts: &mut Vec<String>) {
+    match condition {
+        Condition::Fact(fact) => {
+            if !facts.contains(fact) {
+                facts.push(fact.clone());
+            }
+        }
+        Condition::And(lhs, rhs) | Condition::Or(lhs, rhs) => {
+            condition_facts(lhs, facts);
+            condition_facts(rhs, facts);
+        }
+        Condition::Not(inner) => condition_facts(inner, facts),
+    }
+}
+
+fn negated_facts(condition: &Condition, negated: bool, facts: &mut BTreeSet<String>) {
+    match condition {
+        Condition::Fact(fact) => {
+            if negated {
+                facts.insert(fact.clone());
+            }
+        }
+        Condition::And(lhs, rhs) | Condition::Or(lhs, rhs) => {
+            negated_facts(lhs, negated, facts);
+            negated_facts(rhs, negated, facts);
+        }
+        Condition::Not(inner) => negated_facts(inner, !negated, facts),
+    }
+}
+
+/// Forward chaining here fires rules in list order, so negating a fact that
+/// another rule derives depends on that order. Target languages resolve this
+/// differently and may reach other conclusions.
+pub(crate) fn negation_issues(rules: &[Rule]) -> Vec<String> {
+    let derived = rules
+        .iter()
+        .flat_map(|rule| rule.output.iter().cloned())
+        .collect::<BTreeSet<_>>();
+    let mut issues = vec![];
+    for (idx, rule) in rules.iter().enumerate() {
+        let mut negated = BTreeSet::new();
+        negated_facts(&rule.condition, false, &mut negated);
+        for fact in negated.intersection(&derived) {
+            issues.push(format!(
+                "{}: negates derived fact {}, conclusions may depend on rule order",
+                rule_name(rule, idx),
+                fact
+            ));
+        }
+    }
+    issues
+}
+
+/// Pushes negations down to the facts, as not every target can negate a
+/// whole group.
+pub(crate) fn negation_normal_form(condition: &Condition) -> Condition {
+    match condition {
+        Condition::Fact(_) => condition.clone(),
+        Condition::And(lhs, rhs) => negation_normal_form(lhs).and(negation_normal_form(rhs)),
+        Condition::Or(lhs, rhs) => negation_normal_form(lhs).or(negation_normal_form(rhs)),
+        Condition::Not(inner) => match inner.as_ref() {
+            Condition::Fact(_) => condition.clone(),
+            Condition::Not(inner) => negation_normal_form(inner),
+            Condition::And(lhs, rhs) => negation_normal_form(&lhs.as_ref().clone().not())
+                .or(negation_normal_form(&rhs.as_ref().clone().not())),
+            Condition::Or(lhs, rhs) => negation_normal_form(&lhs.as_ref().clone().not())
+                .and(negation_normal_form(&rhs.as_ref().clone().not())),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negation_normal_form() {
+        let condition = "!(a & !(b | c))".parse::<Condition>().unwrap();
+        assert_eq!(
+            negation_normal_form(&condition).to_string(),
+            "(!a | (b | c))"
+        );
+    }
+
+    #[test]
+    fn test_negation_issues() {
+        let rules = vec![
+            Rule::new("a", "b").unwrap(),
+            Rule::new("!b & !c", "d").unwrap(),
+        ];
+        let issues = negation_issues(&rules);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].starts_with("rule_1: negates derived fact b"));
+    }
+}
@@ -0,0 +1,81 @@
+//! Prolog clauses, one per output fact. Facts become atoms and negation is
+//! written as negation as failure.
+
+use crate::convert::{condition_facts, negation_issues, rule_name, Conversion};
+use crate::rule::{Condition, Rule};
+
+pub fn export(rules: &[Rule]) -> Conversion<String> {
+    let issues = negation_issues(rules);
+    let mut facts = vec![];
+    for rule in rules {
+        condition_facts(&rule.condition, &mut facts);
+        for fact in &rule.output {
+            if !facts.contains(fact) {
+                facts.push(fact.clone());
+            }
+        }
+    }
+
+    let mut out = String::new();
+    if !facts.is_empty() {
+        let declarations = facts
+            .iter()
+            .map(|fact| format!("{}/0", atom(fact)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        out.push_str(&format!(":- dynamic {}.\n\n", declarations));
+    }
+    for (idx, rule) in rules.iter().enumerate() {
+        out.push_str(&format!("% {}", rule_name(rule, idx)));
+        if let Some(description) = &rule.description {
+            out.push_str(&format!(": {}", description.replace('\n', " ")));
+        }
+        out.push('\n');
+        let body = goal(&rule.condition);
+        for fact in &rule.output {
+            out.push_str(&format!("{} :- {}.\n", atom(fact), body));
+        }
+        out.push('\n');
+    }
+    Conversion { value: out, issues }
+}
+
+fn goal(condition: &Condition) -> String {
+    match condition {
+        Condition::Fact(fact) => atom(fact),
+        Condition::And(lhs, rhs) => format!("({}, {})", goal(lhs), goal(rhs)),
+        Condition::Or(lhs, rhs) => format!("({} ; {})", goal(lhs), goal(rhs)),
+        Condition::Not(inner) => format!("\\+ {}", goal(inner)),
+    }
+}
+
+/// Facts that don't start with a lowercase letter or aren't plain ASCII
+/// have to be quoted.
+fn atom(fact: &str) -> String {
+    let plain = fact.starts_with(|c: char| c.is_ascii_lowercase())
+        && fact.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if plain {
+        fact.to_string()
+    } else {
+        format!("'{}'", fact.replace('\'', "\\'"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export() {
+        let mut rule = Rule::new("mammal & (Stripes | !spots)", "tiger,big_cat").unwrap();
+        rule.name = Some("tiger_rule".to_string());
+        let exported = export(&[rule]);
+        assert_eq!(
+            exported.value,
+            ":- dynamic mammal/0, 'Stripes'/0, spots/0, tiger/0, big_cat/0.\n\n\
+             % tiger_rule\n\
+             tiger :- (mammal, ('Stripes' ; \\+ spots)).\n\
+             big_cat :- (mammal, ('Stripes' ; \\+ spots)).\n\n"
+        );
+    }
+}
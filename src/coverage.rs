@@ -0,0 +1,213 @@
+//! How much of a rule base deduction exercised: how often each rule matched
+//! and fired, and which outcomes every subexpression of its condition took.
+//!
+//! Recording is switched on with [`Facts::record_coverage`]. Subexpressions
+//! are listed in pre-order, so the whole condition comes first, and are
+//! evaluated with the same short-circuiting as [`Condition::matches`], an
+//! operand that was never looked at has neither outcome.
+//!
+//! [`Facts::record_coverage`]: crate::rule::Facts::record_coverage
+
+use crate::rule::{Condition, Rule};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Coverage {
+    rules: Vec<RuleCoverage>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RuleCoverage {
+    /// The rule as text, coverage of an edited rule starts over.
+    pub rule: String,
+    pub evaluated: usize,
+    pub matched: usize,
+    /// Times the rule added at least one fact.
+    pub fired: usize,
+    pub branches: Vec<Branch>,
+}
+
+/// The outcomes of one subexpression.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Branch {
+    pub condition: String,
+    pub true_count: usize,
+    pub false_count: usize,
+}
+
+impl Branch {
+    /// Number of outcomes seen, out of two.
+    pub fn covered(&self) -> usize {
+        (self.true_count > 0) as usize + (self.false_count > 0) as usize
+    }
+}
+
+impl RuleCoverage {
+    fn new(rule: &Rule) -> Self {
+        let mut branches = vec![];
+        collect_branches(rule.condition(), &mut branches);
+        RuleCoverage {
+            rule: rule.to_string(),
+            branches,
+            ..RuleCoverage::default()
+        }
+    }
+
+    /// Outcomes seen over all subexpressions and the number possible.
+    pub fn branch_coverage(&self) -> (usize, usize) {
+        let covered = self.branches.iter().map(Branch::covered).sum();
+        (covered, self.branches.len() * 2)
+    }
+
+    fn add(&mut self, other: &RuleCoverage) {
+        self.evaluated += other.evaluated;
+        self.matched += other.matched;
+        self.fired += other.fired;
+        for (branch, other) in self.branches.iter_mut().zip(&other.branches) {
+            branch.true_count += other.true_count;
+            branch.false_count += other.false_count;
+        }
+    }
+}
+
+impl Coverage {
+    /// The coverage of `rule` at `idx`, unless the rule recorded there was
+    /// a different one.
+    pub fn get(&self, idx: usize, rule: &Rule) -> Option<&RuleCoverage> {
+        self.rules
+            .get(idx)
+            .filter(|coverage| coverage.rule == rule.to_string())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.iter().all(|rule| rule.evaluated == 0)
+    }
+
+    /// Adds the counts of another run, rules that changed in between are
+    /// replaced rather than added up.
+    pub fn merge(&mut self, other: &Coverage) {
+        for (idx, coverage) in other.rules.iter().enumerate() {
+            if coverage.evaluated == 0 {
+                continue;
+            }
+            if idx >= self.rules.len() {
+                self.rules.resize_with(idx + 1, RuleCoverage::default);
+            }
+            let entry = &mut self.rules[idx];
+            if entry.rule == coverage.rule {
+                entry.add(coverage);
+            } else {
+                *entry = coverage.clone();
+            }
+        }
+    }
+
+    /// Evaluates the condition of `rule` while counting the outcomes.
+    pub(crate) fn evaluate(&mut self, idx: usize, rule: &Rule, facts: &[String]) -> bool {
+        if idx >= self.rules.len() {
+            self.rules.resize_with(idx + 1, RuleCoverage::default);
+        }
+        let entry = &mut self.rules[idx];
+        if entry.branches.is_empty() {
+            *entry = RuleCoverage::new(rule);
+        }
+        entry.evaluated += 1;
+        let matched = evaluate(rule.condition(), facts, &mut entry.branches, 0);
+        if matched {
+            entry.matched += 1;
+        }
+        matched
+    }
+
+    pub(crate) fn fired(&mut self, idx: usize) {
+        self.rules[idx].fired += 1;
+    }
+}
+
+fn collect_branches(condition: &Condition, branches: &mut Vec<Branch>) {
+    branches.push(Branch {
+        condition: condition.to_string(),
+        ..Branch::default()
+    });
+    match condition {
+        Condition::Fact(_) => {}
+        Condition::And(lhs, rhs) | Condition::Or(lhs, rhs) => {
+            collect_branches(lhs, branches);
+            collect_branches(rhs, branches);
+        }
+        Condition::Not(inner) => collect_branches(inner, branches),
+    }
+}
+
+fn size(condition: &Condition) -> usize {
+    match condition {
+        Condition::Fact(_) => 1,
+        Condition::And(lhs, rhs) | Condition::Or(lhs, rhs) => 1 + size(lhs) + size(rhs),
+        Condition::Not(inner) => 1 + size(inner),
+    }
+}
+
+/// `at` is the pre-order index of `condition` in `branches`.
+fn evaluate(condition: &Condition, facts: &[String], branches: &mut [Branch], at: usize) -> bool {
+    let result = match condition {
+        Condition::Fact(fact) => facts.contains(fact),
+        Condition::And(lhs, rhs) => {
+            evaluate(lhs, facts, branches, at + 1)
+                && evaluate(rhs, facts, branches, at + 1 + size(lhs))
+        }
+        Condition::Or(lhs, rhs) => {
+            evaluate(lhs, facts, branches, at + 1)
+                || evaluate(rhs, facts, branches, at + 1 + size(lhs))
+        }
+        Condition::Not(inner) => !evaluate(inner, facts, branches, at + 1),
+    };
+    if result {
+        branches[at].true_count += 1;
+    } else {
+        branches[at].false_count += 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rule::{Facts, Rule};
+
+    #[test]
+    fn test_coverage() {
+        let rules = vec![
+            Rule::new("fur", "mammal").unwrap(),
+            Rule::new("feathers & !fur", "bird").unwrap(),
+        ];
+        let mut facts = Facts::new(&["fur"]);
+        facts.record_coverage();
+        facts.deduce(&rules);
+        let mut coverage = facts.coverage().unwrap().clone();
+
+        let mammal = coverage.get(0, &rules[0]).unwrap();
+        assert_eq!((mammal.evaluated, mammal.matched, mammal.fired), (2, 2, 1));
+        assert_eq!(mammal.branch_coverage(), (1, 2));
+        let bird = coverage.get(1, &rules[1]).unwrap();
+        assert_eq!(bird.fired, 0);
+        let outcomes = bird
+            .branches
+            .iter()
+            .map(|branch| (branch.condition.as_str(), branch.true_count, branch.false_count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outcomes,
+            [("(feathers & !fur)", 0, 2), ("feathers", 0, 2), ("!fur", 0, 0), ("fur", 0, 0)]
+        );
+
+        let mut facts = Facts::new(&["feathers"]);
+        facts.record_coverage();
+        facts.deduce(&rules);
+        coverage.merge(facts.coverage().unwrap());
+        let bird = coverage.get(1, &rules[1]).unwrap();
+        assert_eq!(bird.fired, 1);
+        assert_eq!(bird.branch_coverage(), (6, 8));
+
+        let edited = Rule::new("feathers", "bird").unwrap();
+        assert!(coverage.get(1, &edited).is_none());
+    }
+}
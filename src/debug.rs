@@ -0,0 +1,324 @@
+//! Deduction one rule at a time, for stepping through it with breakpoints.
+//!
+//! A [`Debugger`] applies rules in the same order as [`Facts::deduce_with`]
+//! with the same options and ends up with the same facts, it just stops
+//! wherever it is asked to.
+
+use crate::rule::{Condition, Facts, Firing, Options, Rule, Strategy};
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase", tag = "kind", content = "on")]
+pub enum Breakpoint {
+    /// Stop before the rule at this index fires.
+    Rule(usize),
+    /// Stop after a rule derived this fact.
+    Fact(String),
+}
+
+/// What happened while running.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "event")]
+pub enum Event {
+    Fired(Firing),
+    /// The rule was tried and added nothing.
+    Skipped { rule: usize },
+    CycleEnd { cycle: usize, changed: bool },
+}
+
+/// Why running stopped.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "reason", content = "on")]
+pub enum Stop {
+    Step,
+    CycleEnd,
+    Breakpoint(Breakpoint),
+    /// A cycle added nothing, deduction is complete.
+    Finished,
+}
+
+#[derive(Debug, Clone)]
+pub struct Debugger {
+    facts: Facts,
+    options: Options,
+    /// The rule to try next in the current cycle.
+    next: usize,
+    in_cycle: bool,
+    changed: bool,
+    finished: bool,
+    /// A rule breakpoint that was just reported, so resuming doesn't stop
+    /// on it again.
+    paused_at: Option<usize>,
+}
+
+impl Debugger {
+    pub fn new(input: Vec<String>, options: &Options) -> Self {
+        Debugger {
+            facts: Facts::from(input),
+            options: *options,
+            next: 0,
+            in_cycle: false,
+            changed: false,
+            finished: false,
+            paused_at: None,
+        }
+    }
+
+    pub fn facts(&self) -> &Facts {
+        &self.facts
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Rules left in the current cycle, or the next one, that would add a
+    /// fact if they were tried now.
+    pub fn pending(&self, rules: &[Rule]) -> Vec<usize> {
+        let from = if self.in_cycle { self.next } else { 0 };
+        if self.finished || self.at_max_cycles() {
+            return vec![];
+        }
+        let pending = (from..rules.len()).filter(|&idx| {
+            let rule = &rules[idx];
+            rule.is_enabled()
+                && rule.condition().matches(self.facts.all())
+                && rule.output().iter().any(|fact| !self.facts.contains(fact))
+        });
+        match self.options.strategy {
+            Strategy::All => pending.collect(),
+            _ if self.in_cycle => pending.take(1).collect(),
+            strategy => self.facts.choose(rules, strategy).into_iter().collect(),
+        }
+    }
+
+    /// The cycle limit is reached and no further cycle starts.
+    fn at_max_cycles(&self) -> bool {
+        !self.in_cycle && self.options.max_cycles.is_some_and(|max| self.facts.cycle() >= max)
+    }
+
+    /// Tries the next rule, or ends the cycle when none is left.
+    pub fn step(&mut self, rules: &[Rule]) -> (Vec<Event>, Stop) {
+        let mut events = vec![];
+        let stop = match self.advance(rules, &mut events) {
+            Some(Stop::CycleEnd) | None => Stop::Step,
+            Some(stop) => stop,
+        };
+        (events, stop)
+    }
+
+    /// Runs to the end of the current cycle or the first breakpoint.
+    pub fn next_cycle(&mut self, rules: &[Rule], breakpoints: &[Breakpoint]) -> (Vec<Event>, Stop) {
+        self.run(rules, breakpoints, true)
+    }
+
+    /// Runs until deduction is complete or a breakpoint is hit.
+    pub fn resume(&mut self, rules: &[Rule], breakpoints: &[Breakpoint]) -> (Vec<Event>, Stop) {
+        self.run(rules, breakpoints, false)
+    }
+
+    fn run(&mut self, rules: &[Rule], breakpoints: &[Breakpoint], cycle: bool) -> (Vec<Event>, Stop) {
+        let mut events = vec![];
+        loop {
+            if let Some(idx) = self.rule_breakpoint(rules, breakpoints) {
+                self.paused_at = Some(idx);
+                return (events, Stop::Breakpoint(Breakpoint::Rule(idx)));
+            }
+            let stop = self.advance(rules, &mut events);
+            if let Some(Event::Fired(firing)) = events.last() {
+                let hit = breakpoints.iter().find(|breakpoint| {
+                    matches!(breakpoint, Breakpoint::Fact(fact) if firing.added.contains(fact))
+                });
+                if let Some(breakpoint) = hit {
+                    return (events, Stop::Breakpoint(breakpoint.clone()));
+                }
+            }
+            match stop {
+                Some(Stop::CycleEnd) if !cycle => {}
+                Some(stop) => return (events, stop),
+                None => {}
+            }
+        }
+    }
+
+    /// The next rule when it has a breakpoint and is about to fire.
+    fn rule_breakpoint(&self, rules: &[Rule], breakpoints: &[Breakpoint]) -> Option<usize> {
+        let pending = *self.pending(rules).first()?;
+        // A strategy picking one rule goes straight to it
+        let idx = match (self.in_cycle, self.options.strategy) {
+            (true, _) => self.next,
+            (false, Strategy::All) => 0,
+            (false, _) => pending,
+        };
+        (idx == pending
+            && self.paused_at != Some(idx)
+            && breakpoints.contains(&Breakpoint::Rule(idx)))
+        .then_some(idx)
+    }
+
+    /// Tries one rule, returns how the cycle ended when it did.
+    fn advance(&mut self, rules: &[Rule], events: &mut Vec<Event>) -> Option<Stop> {
+        if self.at_max_cycles() {
+            self.finished = true;
+        }
+        if self.finished {
+            return Some(Stop::Finished);
+        }
+        if !self.in_cycle {
+            self.facts.start_cycle();
+            self.in_cycle = true;
+            self.changed = false;
+            self.next = match self.options.strategy {
+                Strategy::All => 0,
+                strategy => self.facts.choose(rules, strategy).unwrap_or(rules.len()),
+            };
+        }
+        self.paused_at = None;
+        if let Some(rule) = rules.get(self.next) {
+            match self.facts.fire(self.next, rule) {
+                Some(firing) => {
+                    events.push(Event::Fired(firing.clone()));
+                    self.changed = true;
+                }
+                None => events.push(Event::Skipped { rule: self.next }),
+            }
+            self.next = match self.options.strategy {
+                Strategy::All => self.next + 1,
+                // The one rule of the cycle has been tried
+                _ => rules.len(),
+            };
+            return None;
+        }
+        self.in_cycle = false;
+        events.push(Event::CycleEnd {
+            cycle: self.facts.cycle(),
+            changed: self.changed,
+        });
+        if self.changed {
+            Some(Stop::CycleEnd)
+        } else {
+            self.finished = true;
+            Some(Stop::Finished)
+        }
+    }
+}
+
+/// Why `condition` holds, or doesn't, given `facts`: the known facts and
+/// the missing facts that decide it. Only the first deciding operand of an
+/// `&` or `|` is looked at, like evaluation does.
+pub fn reasons(condition: &Condition, facts: &[String]) -> (Vec<String>, Vec<String>) {
+    fn walk(condition: &Condition, facts: &[String], known: &mut Vec<String>, missing: &mut Vec<String>) {
+        match condition {
+            Condition::Fact(fact) if facts.contains(fact) => known.push(fact.clone()),
+            Condition::Fact(fact) => missing.push(fact.clone()),
+            Condition::And(lhs, rhs) | Condition::Or(lhs, rhs) => {
+                let value = condition.matches(facts);
+                let short = matches!(condition, Condition::And(..)) != value;
+                if short && lhs.matches(facts) == value {
+                    walk(lhs, facts, known, missing);
+                } else if short {
+                    walk(rhs, facts, known, missing);
+                } else {
+                    walk(lhs, facts, known, missing);
+                    walk(rhs, facts, known, missing);
+                }
+            }
+            Condition::Not(inner) => walk(inner, facts, known, missing),
+        }
+    }
+    let mut known = vec![];
+    let mut missing = vec![];
+    walk(condition, facts, &mut known, &mut missing);
+    (known, missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> Vec<Rule> {
+        vec![
+            Rule::new("fur", "mammal").unwrap(),
+            Rule::new("mammal & stripes", "tiger").unwrap(),
+            Rule::new("tiger", "big_cat").unwrap(),
+        ]
+    }
+
+    #[test]
+    fn test_debugger() {
+        let rules = rules();
+        let input = vec!["fur".to_string(), "stripes".to_string()];
+        let mut debugger = Debugger::new(input.clone(), &Options::default());
+        assert_eq!(debugger.pending(&rules), [0]);
+
+        let (events, stop) = debugger.step(&rules);
+        assert_eq!(stop, Stop::Step);
+        assert!(matches!(&events[..], [Event::Fired(firing)] if firing.rule == 0));
+        assert_eq!(debugger.pending(&rules), [1]);
+
+        let breakpoints = [Breakpoint::Rule(2)];
+        let (_, stop) = debugger.resume(&rules, &breakpoints);
+        assert_eq!(stop, Stop::Breakpoint(Breakpoint::Rule(2)));
+        assert!(!debugger.facts().contains("big_cat"));
+        let (events, stop) = debugger.next_cycle(&rules, &breakpoints);
+        assert_eq!(stop, Stop::CycleEnd);
+        assert_eq!(events.len(), 2);
+        let (_, stop) = debugger.resume(&rules, &breakpoints);
+        assert_eq!(stop, Stop::Finished);
+        assert!(debugger.is_finished());
+
+        let mut facts = Facts::from(input);
+        facts.deduce(&rules);
+        assert_eq!(debugger.facts().all(), facts.all());
+        assert_eq!(debugger.facts().fired(), facts.fired());
+
+        let mut debugger = Debugger::new(vec!["fur".to_string(), "stripes".to_string()], &Options::default());
+        let (_, stop) = debugger.resume(&rules, &[Breakpoint::Fact("tiger".to_string())]);
+        assert_eq!(stop, Stop::Breakpoint(Breakpoint::Fact("tiger".to_string())));
+        assert_eq!(debugger.pending(&rules), [2]);
+    }
+
+    #[test]
+    fn test_debugger_options() {
+        let input = vec!["fur".to_string(), "stripes".to_string()];
+        // Backwards, every rule of the chain needs a cycle of its own
+        let backwards = rules().into_iter().rev().collect::<Vec<_>>();
+        let options = Options {
+            max_cycles: Some(1),
+            ..Options::default()
+        };
+        let mut debugger = Debugger::new(input.clone(), &options);
+        let (_, stop) = debugger.resume(&backwards, &[]);
+        assert_eq!(stop, Stop::Finished);
+        assert_eq!(debugger.facts().derived(), ["mammal"]);
+        assert!(debugger.pending(&backwards).is_empty());
+
+        let rules = rules();
+
+        // The most specific rule fires alone in each cycle
+        let options = Options {
+            strategy: Strategy::Specificity,
+            ..Options::default()
+        };
+        let mut facts = Facts::from(input.clone());
+        facts.deduce_with(&rules, &options);
+        let mut debugger = Debugger::new(input, &options);
+        assert_eq!(debugger.pending(&rules), [0]);
+        let (events, stop) = debugger.next_cycle(&rules, &[Breakpoint::Rule(1)]);
+        assert_eq!((events.len(), stop), (2, Stop::CycleEnd));
+        let (_, stop) = debugger.resume(&rules, &[Breakpoint::Rule(1)]);
+        assert_eq!(stop, Stop::Breakpoint(Breakpoint::Rule(1)));
+        let (_, stop) = debugger.resume(&rules, &[Breakpoint::Rule(1)]);
+        assert_eq!(stop, Stop::Finished);
+        assert_eq!(debugger.facts().fired(), facts.fired());
+    }
+
+    #[test]
+    fn test_reasons() {
+        let facts = ["a".to_string(), "c".to_string()];
+        let condition = "(a | b) & !d".parse::<Condition>().unwrap();
+        assert_eq!(reasons(&condition, &facts), (vec!["a".to_string()], vec!["d".to_string()]));
+        let condition = "b & c".parse::<Condition>().unwrap();
+        assert_eq!(reasons(&condition, &facts), (vec![], vec!["b".to_string()]));
+    }
+}
@@ -0,0 +1,611 @@
+//! The `.rules` text format, meant to be written by hand:
+//!
+//! ```text
+//! # Comments run to the end of the line
+//! include "mammals.rules"
+//!
+//! label carnivore: "它是食肉动物"
+//! label !feathers: "它没有羽毛"
+//!
+//! rule tiger_rule "Striped big cats" [cats]: IF mammal & carnivore
+//!                                               & stripes
+//!                                            THEN tiger, striped_cat
+//! rule: IF feathers THEN bird
+//!
+//! @author "fox"
+//! @explain "Only mammals have fur"
+//! @template "因为{condition}，所以{output}"
+//! @disabled
+//! rule: IF fur THEN mammal
+//!
+//! test striped_tiger: GIVEN mammal, carnivore, stripes EXPECT tiger NOT bird
+//! ```
+//!
+//! The name, the quoted description and the tags in brackets are optional,
+//! the condition may span several lines and uses the same syntax as
+//! `rule add`. Lines starting with `@` before a rule set its author,
+//! explanation, sentence template, `created`/`updated` timestamps or
+//! disable it. A label gives the words explanations use for a fact or, with
+//! `!`, for its absence, see [`crate::explain`]. Included paths
+//! are relative to the including file. A test names the facts it starts
+//! from, the facts that must be derived after `EXPECT` and those that must
+//! not after `NOT`, either of the two may be left out.
+
+use crate::error::{Error, ImportError, LineError, Result};
+use crate::explain::Labels;
+use crate::rule::{is_fact_name, Condition, Rule};
+use crate::testing::TestCase;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The rules, tests and fact labels of a file and everything it includes.
+#[derive(Debug, Default)]
+pub struct RuleFile {
+    pub rules: Vec<Rule>,
+    pub tests: Vec<TestCase>,
+    pub labels: Labels,
+}
+
+/// Reads a rule file along with everything it includes.
+pub fn load_file(path: &Path) -> Result<RuleFile> {
+    let mut file = RuleFile::default();
+    load_into(path, &mut file, &mut HashSet::new(), &mut vec![])?;
+    Ok(file)
+}
+
+/// Files already loaded are skipped when included again, only an include
+/// cycle is an error.
+fn load_into(
+    path: &Path,
+    file: &mut RuleFile,
+    seen: &mut HashSet<PathBuf>,
+    stack: &mut Vec<PathBuf>,
+) -> Result<()> {
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| Error::from(e).in_file(path))?;
+    if stack.contains(&canonical) {
+        return Err(Error::InvalidRule("file includes itself".to_string()).in_file(path));
+    }
+    if !seen.insert(canonical.clone()) {
+        return Ok(());
+    }
+    let source = fs::read_to_string(path).map_err(|e| Error::from(e).in_file(path))?;
+    let items = parse(&source).map_err(|e| Error::from(e).in_file(path))?;
+    let dir = canonical.parent().unwrap_or(Path::new(".")).to_path_buf();
+    stack.push(canonical);
+    for item in items {
+        match item {
+            Item::Include(include) => load_into(&dir.join(include), file, seen, stack)?,
+            Item::Rule(rule) => file.rules.push(rule),
+            Item::Test(test) => file.tests.push(test),
+            Item::Label(fact, label) => {
+                file.labels.insert(fact, label);
+            }
+        }
+    }
+    stack.pop();
+    Ok(())
+}
+
+/// Writes rules, tests and labels so that [`load_file`] reads them back
+/// unchanged.
+pub fn save_file(path: &Path, rules: &[Rule], tests: &[TestCase], labels: &Labels) -> Result<()> {
+    fs::write(path, write(rules, tests, labels)).map_err(|e| Error::from(e).in_file(path))?;
+    Ok(())
+}
+
+pub fn write(rules: &[Rule], tests: &[TestCase], labels: &Labels) -> String {
+    let mut out = String::new();
+    for (fact, label) in labels {
+        out.push_str(&format!("label {}: {}\n", fact, quote(label)));
+    }
+    if !labels.is_empty() && !rules.is_empty() {
+        out.push('\n');
+    }
+    for rule in rules {
+        let annotations = [
+            ("author", &rule.author),
+            ("explain", &rule.explanation),
+            ("template", &rule.template),
+            ("created", &rule.created),
+            ("updated", &rule.updated),
+        ];
+        for (key, value) in annotations {
+            if let Some(value) = value {
+                out.push_str(&format!("@{} {}\n", key, quote(value)));
+            }
+        }
+        if !rule.enabled {
+            out.push_str("@disabled\n");
+        }
+        out.push_str("rule");
+        if let Some(name) = &rule.name {
+            out.push(' ');
+            out.push_str(name);
+        }
+        if let Some(description) = &rule.description {
+            out.push_str(&format!(" {}", quote(description)));
+        }
+        if !rule.tags.is_empty() {
+            out.push_str(&format!(" [{}]", rule.tags.join(", ")));
+        }
+        out.push_str(&format!(
+            ": IF {} THEN {}\n",
+            rule.condition,
+            rule.output.join(", ")
+        ));
+    }
+    if !rules.is_empty() && !tests.is_empty() {
+        out.push('\n');
+    }
+    for test in tests {
+        out.push_str(&format!("test {}: GIVEN {}", test.name, test.given.join(", ")));
+        if !test.expect.is_empty() {
+            out.push_str(&format!(" EXPECT {}", test.expect.join(", ")));
+        }
+        if !test.expect_not.is_empty() {
+            out.push_str(&format!(" NOT {}", test.expect_not.join(", ")));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[derive(Debug)]
+pub enum Item {
+    Include(String),
+    Rule(Rule),
+    Test(TestCase),
+    /// A fact, `!` first for its absence, and its label.
+    Label(String, String),
+}
+
+/// Parses one file without following includes. All errors are collected,
+/// each with the line it was found on.
+pub fn parse(source: &str) -> Result<Vec<Item>, ImportError> {
+    let source = strip_comments(source);
+    let mut parser = Parser {
+        chars: source.chars().collect(),
+        pos: 0,
+        line: 1,
+    };
+    let mut items = vec![];
+    let mut errors = vec![];
+    loop {
+        parser.skip_whitespace();
+        if parser.at_end() {
+            break;
+        }
+        let line = parser.line;
+        match parser.item() {
+            Ok(item) => items.push(item),
+            Err(message) => {
+                errors.push(LineError { line, message });
+                parser.recover();
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(items)
+    } else {
+        Err(ImportError(errors))
+    }
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Blanks out everything from a `#` outside of a string to the end of its line.
+fn strip_comments(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    for line in source.split('\n') {
+        let mut in_string = false;
+        let mut escaped = false;
+        for c in line.chars() {
+            match c {
+                '#' if !in_string => break,
+                '"' if !escaped => in_string = !in_string,
+                _ => {}
+            }
+            escaped = c == '\\' && !escaped;
+            out.push(c);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn at_end(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.bump();
+        }
+    }
+
+    fn word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(c) = self.peek().filter(|c| c.is_alphanumeric() || *c == '_') {
+            word.push(c);
+            self.bump();
+        }
+        word
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.bump() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!("expected '{}', found '{}'", expected, c)),
+            None => Err(format!("expected '{}', found end of file", expected)),
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<(), String> {
+        self.skip_whitespace();
+        let word = self.word();
+        if !word.eq_ignore_ascii_case(keyword) {
+            return Err(format!("expected {}, found {:?}", keyword, word));
+        }
+        Ok(())
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.bump() {
+                    Some(c) => s.push(c),
+                    None => return Err("unterminated string".to_string()),
+                },
+                Some('\n') | None => return Err("unterminated string".to_string()),
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    fn item(&mut self) -> Result<Item, String> {
+        if self.peek() == Some('@') {
+            return self.annotated_rule().map(Item::Rule);
+        }
+        match self.word().as_str() {
+            "include" => Ok(Item::Include(self.string()?)),
+            "rule" => Ok(Item::Rule(self.rule()?)),
+            "test" => Ok(Item::Test(self.test()?)),
+            "label" => self.label(),
+            "" => Err(format!("unexpected '{}'", self.peek().unwrap_or(' '))),
+            word => Err(format!("expected rule, test, label or include, found {:?}", word)),
+        }
+    }
+
+    /// `@key "value"` lines, each setting some metadata of the rule after
+    /// them.
+    fn annotated_rule(&mut self) -> Result<Rule, String> {
+        let mut annotations = vec![];
+        while self.peek() == Some('@') {
+            self.bump();
+            let key = self.word();
+            self.skip_inline_whitespace();
+            let value = match key.as_str() {
+                "disabled" => String::new(),
+                "author" | "explain" | "template" | "created" | "updated" => self.string()?,
+                _ => return Err(format!("unknown annotation @{}", key)),
+            };
+            annotations.push((key, value));
+            self.skip_whitespace();
+        }
+        if self.word() != "rule" {
+            return Err("expected a rule after annotations".to_string());
+        }
+        let mut rule = self.rule()?;
+        for (key, value) in annotations {
+            match key.as_str() {
+                "disabled" => rule.enabled = false,
+                "author" => rule.author = Some(value),
+                "explain" => rule.explanation = Some(value),
+                "template" => rule.template = Some(value),
+                "created" => rule.created = Some(value),
+                _ => rule.updated = Some(value),
+            }
+        }
+        Ok(rule)
+    }
+
+    fn rule(&mut self) -> Result<Rule, String> {
+        self.skip_whitespace();
+        let name = Some(self.word()).filter(|name| !name.is_empty());
+        self.skip_whitespace();
+        let description = match self.peek() {
+            Some('"') => Some(self.string()?),
+            _ => None,
+        };
+        self.skip_whitespace();
+        let mut tags = vec![];
+        if self.peek() == Some('[') {
+            self.bump();
+            loop {
+                self.skip_whitespace();
+                let tag = self.word();
+                if !is_fact_name(&tag) {
+                    return Err("expected a tag".to_string());
+                }
+                tags.push(tag);
+                self.skip_whitespace();
+                match self.bump() {
+                    Some(',') => {}
+                    Some(']') => break,
+                    _ => return Err("expected ',' or ']' after a tag".to_string()),
+                }
+            }
+        }
+        self.expect(':')?;
+        self.keyword("IF")?;
+
+        let condition = self.condition()?;
+        let condition = condition
+            .parse::<Condition>()
+            .map_err(|e| format!("invalid condition {:?}: {}", condition.trim(), e))?;
+
+        let mut output = vec![];
+        loop {
+            self.skip_whitespace();
+            let fact = self.word();
+            if !is_fact_name(&fact) {
+                return Err("expected an output fact after THEN".to_string());
+            }
+            output.push(fact);
+            self.skip_inline_whitespace();
+            if self.peek() != Some(',') {
+                break;
+            }
+            self.bump();
+        }
+        self.skip_inline_whitespace();
+        if !matches!(self.peek(), Some('\n') | None) {
+            return Err(format!("unexpected '{}' after outputs", self.peek().unwrap_or(' ')));
+        }
+
+        Ok(Rule {
+            name,
+            description,
+            tags,
+            ..Rule::plain(condition, output)
+        })
+    }
+
+    fn label(&mut self) -> Result<Item, String> {
+        self.skip_whitespace();
+        let negated = self.peek() == Some('!');
+        if negated {
+            self.bump();
+        }
+        let fact = self.word();
+        if !is_fact_name(&fact) {
+            return Err("expected a fact to label".to_string());
+        }
+        self.expect(':')?;
+        let label = self.string()?;
+        let fact = if negated { format!("!{}", fact) } else { fact };
+        Ok(Item::Label(fact, label))
+    }
+
+    fn test(&mut self) -> Result<TestCase, String> {
+        self.skip_whitespace();
+        let name = self.word();
+        if name.is_empty() {
+            return Err("expected a test name".to_string());
+        }
+        self.expect(':')?;
+        self.keyword("GIVEN")?;
+        let given = self.facts()?;
+        let mut test = TestCase {
+            name,
+            given,
+            expect: vec![],
+            expect_not: vec![],
+        };
+        loop {
+            self.skip_inline_whitespace();
+            if matches!(self.peek(), Some('\n') | None) {
+                break;
+            }
+            let keyword = self.word();
+            if keyword.eq_ignore_ascii_case("EXPECT") && test.expect.is_empty() {
+                test.expect = self.facts()?;
+            } else if keyword.eq_ignore_ascii_case("NOT") && test.expect_not.is_empty() {
+                test.expect_not = self.facts()?;
+            } else {
+                return Err(format!("expected EXPECT or NOT, found {:?}", keyword));
+            }
+        }
+        if test.expect.is_empty() && test.expect_not.is_empty() {
+            return Err("a test needs EXPECT or NOT".to_string());
+        }
+        Ok(test)
+    }
+
+    /// A comma separated list of facts on the current line.
+    fn facts(&mut self) -> Result<Vec<String>, String> {
+        let mut facts = vec![];
+        loop {
+            self.skip_inline_whitespace();
+            let fact = self.word();
+            if !is_fact_name(&fact) {
+                return Err(format!("expected a fact, found {:?}", fact));
+            }
+            facts.push(fact);
+            self.skip_inline_whitespace();
+            if self.peek() != Some(',') {
+                return Ok(facts);
+            }
+            self.bump();
+        }
+    }
+
+    /// Everything up to the `THEN` keyword, which is consumed.
+    fn condition(&mut self) -> Result<String, String> {
+        let mut condition = String::new();
+        loop {
+            match self.peek() {
+                None => return Err("expected THEN, found end of file".to_string()),
+                Some(c) if c.is_alphanumeric() || c == '_' => {
+                    let word = self.word();
+                    if word.eq_ignore_ascii_case("THEN") {
+                        return Ok(condition);
+                    }
+                    condition.push_str(&word);
+                }
+                Some(c) => {
+                    condition.push(c);
+                    self.bump();
+                }
+            }
+        }
+    }
+
+    fn skip_inline_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace() && c != '\n') {
+            self.bump();
+        }
+    }
+
+    /// Skips to the next line starting with a keyword after an error.
+    fn recover(&mut self) {
+        loop {
+            while !matches!(self.bump(), Some('\n') | None) {}
+            let start = self.pos;
+            let line = self.line;
+            self.skip_inline_whitespace();
+            let annotation = self.peek() == Some('@');
+            let word = self.word();
+            self.pos = start;
+            self.line = line;
+            if self.at_end() || annotation || matches!(word.as_str(), "rule" | "test" | "label" | "include") {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rules() {
+        let source = r#"
+# Animals
+include "birds.rules"
+
+rule tiger_rule "Striped \"big\" cat" [cats, big]: IF mammal & carnivore  # the cat part
+    & stripes
+    THEN tiger, striped_cat
+rule: if !feathers then no_bird
+"#;
+        let items = parse(source).unwrap();
+        assert!(matches!(&items[0], Item::Include(path) if path == "birds.rules"));
+        let Item::Rule(tiger) = &items[1] else {
+            panic!("expected a rule");
+        };
+        assert_eq!(tiger.name.as_deref(), Some("tiger_rule"));
+        assert_eq!(tiger.description.as_deref(), Some("Striped \"big\" cat"));
+        assert_eq!(tiger.tags, ["cats", "big"]);
+        assert_eq!(tiger.condition.to_string(), "((mammal & carnivore) & stripes)");
+        assert_eq!(tiger.output, ["tiger", "striped_cat"]);
+        let Item::Rule(bird) = &items[2] else {
+            panic!("expected a rule");
+        };
+        assert_eq!(bird.name, None);
+        assert_eq!(bird.condition, Condition::fact("feathers").not());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let source = "bogus\nrule a: IF x & THEN y\nrule b: IF x THEN y\nrule c IF x THEN y\n";
+        let errors = parse(source).unwrap_err().0;
+        assert_eq!(
+            errors.iter().map(|e| e.line).collect::<Vec<_>>(),
+            [1, 2, 4]
+        );
+
+        // The label after a broken rule still parses on its own
+        let source = "rule a: IF x & THEN y\nlabel x: \"有 x\"\nlabel y \"有 y\"\n";
+        let errors = parse(source).unwrap_err().0;
+        assert_eq!(errors.iter().map(|e| e.line).collect::<Vec<_>>(), [1, 3]);
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        let source = "label !a: \"没有 a\"\nlabel b: \"是 b\"\n\n\
+                      rule tiger \"Big \\\"cat\\\"\": IF (mammal & stripes) THEN tiger, cat\n\
+                      @author \"fox\"\n@explain \"Not a\"\n@template \"因为{condition}\"\n@disabled\n\
+                      rule: IF !a THEN b\n";
+        let mut rules = vec![];
+        let mut labels = Labels::new();
+        for item in parse(source).unwrap() {
+            match item {
+                Item::Rule(rule) => rules.push(rule),
+                Item::Label(fact, label) => {
+                    labels.insert(fact, label);
+                }
+                _ => panic!("expected a rule or a label"),
+            }
+        }
+        assert_eq!(write(&rules, &[], &labels), source);
+        assert_eq!(labels["!a"], "没有 a");
+        assert_eq!(rules[1].explanation(), Some("Not a"));
+        assert_eq!(rules[1].template(), Some("因为{condition}"));
+        assert!(!rules[1].is_enabled());
+    }
+
+    #[test]
+    fn test_parse_tests() {
+        let source = "rule: IF a THEN b\n\ntest ab: given a expect b, c not d\ntest bad: GIVEN a\n";
+        let errors = parse(source).unwrap_err().0;
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 4);
+
+        let source = "rule: IF a THEN b\n\ntest ab: GIVEN a, x EXPECT b, c NOT d\n";
+        let items = parse(source).unwrap();
+        let Item::Test(test) = &items[1] else {
+            panic!("expected a test");
+        };
+        assert_eq!(test.given, ["a", "x"]);
+        assert_eq!(test.expect, ["b", "c"]);
+        assert_eq!(test.expect_not, ["d"]);
+        let Item::Rule(rule) = &items[0] else {
+            panic!("expected a rule");
+        };
+        assert_eq!(
+            write(std::slice::from_ref(rule), std::slice::from_ref(test), &Labels::new()),
+            source
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! Line editing in the interactive mode: completion of commands, facts and
+//! paths, highlighting of conditions and hints from history.
+
+use colored::Colorize;
+use expert::graph::Graph;
+use expert::rule::Condition;
+use expert::Rule;
+use rustyline::completion::{Completer, FilenameCompleter, Pair};
+use rustyline::highlight::{CmdKind, Highlighter};
+use rustyline::hint::{Hinter, HistoryHinter};
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+use std::borrow::Cow;
+
+const COMMANDS: &[&str] = &[
+    "help", "quit", "rule", "test", "load", "save", "scenario", "runs", "db", "kb", "set", "batch",
+    "coverage", "debug", "step", "next-cycle", "continue", "break", "watch", "proof", "label", "tui",
+    "undo", "redo", "history",
+];
+
+/// Marks a hint as a parse error rather than a line from history.
+const ERROR_HINT: &str = "  ← ";
+
+fn subcommands(command: &str) -> &'static [&'static str] {
+    match command {
+        "help" => COMMANDS,
+        "rule" => &[
+            "list", "find", "add", "remove", "edit", "set-condition", "set-output", "rename", "set", "enable", "disable",
+            "shuffle", "confluence", "graph", "export", "import",
+        ],
+        "db" => &["connect", "close", "status", "load", "sync", "reset", "add", "remove", "history", "find"],
+        "kb" => &["list", "create", "use", "copy", "delete", "test"],
+        "scenario" => &["save", "load", "list", "run"],
+        "runs" => &["list", "show"],
+        "set" => &["format", "lang", "max-cycles", "strategy"],
+        "coverage" => &["reset"],
+        "debug" => &["stop"],
+        "break" => &["rule", "fact", "clear"],
+        "watch" => &["clear"],
+        "label" => &["list", "remove"],
+        _ => &[],
+    }
+}
+
+/// What the word being typed is, given the words before it.
+enum Arg {
+    Words(&'static [&'static str]),
+    Facts,
+    Path,
+    None,
+}
+
+fn arg(before: &[&str]) -> Arg {
+    match before {
+        [] => Arg::Words(COMMANDS),
+        ["kb", "test"] => Arg::Words(&["run", "add", "list", "remove"]),
+        ["set", "format"] => Arg::Words(&["text", "json"]),
+        ["set", "lang"] => Arg::Words(&["zh-CN", "en-US"]),
+        ["set", "max-cycles"] => Arg::Words(&["none"]),
+        ["set", "strategy"] => Arg::Words(&["all", "first", "specificity"]),
+        ["test" | "debug" | "watch", ..]
+        | ["proof"]
+        | ["break", "fact"]
+        | ["label", "remove"]
+        | ["rule" | "db", "add", ..]
+        | ["rule", "edit" | "set-condition" | "set-output", _, ..]
+        | ["rule" | "db", "find", .., "--fact" | "--output"]
+        | ["scenario", "save", _, ..]
+        | ["kb", "test", "add", _, ..] => Arg::Facts,
+        ["db", "connect"]
+        | ["load" | "save" | "batch"]
+        | ["batch", _, "--output"]
+        | ["rule", "export" | "import" | "graph", ..]
+        | ["proof", _, ..] => Arg::Path,
+        [command] => Arg::Words(subcommands(command)),
+        _ => Arg::None,
+    }
+}
+
+/// The index of the word holding a condition, if the command takes one.
+fn condition_arg(words: &[&str]) -> Option<usize> {
+    match words {
+        ["rule" | "db", "add", _, ..] => Some(2),
+        ["rule", "edit" | "set-condition", _, _, ..] => Some(3),
+        _ => None,
+    }
+}
+
+pub struct ReplHelper {
+    /// Every fact of the current rule base, sorted.
+    facts: Vec<String>,
+    files: FilenameCompleter,
+    history: HistoryHinter,
+}
+
+impl ReplHelper {
+    pub fn new() -> Self {
+        ReplHelper {
+            facts: vec![],
+            files: FilenameCompleter::new(),
+            history: HistoryHinter::new(),
+        }
+    }
+
+    /// Takes the facts to complete from `rules`.
+    pub fn set_rules(&mut self, rules: &[Rule]) {
+        self.facts = Graph::build(rules).facts;
+    }
+
+    fn candidates(&self, line: &str, pos: usize) -> (usize, Vec<Pair>) {
+        let words = line[..pos].split(' ').collect::<Vec<_>>();
+        let (word, before) = words.split_last().expect("split yields a word");
+        let start = pos - word.len();
+        let pairs = |start, prefix: &str, words: &mut dyn Iterator<Item = &str>| {
+            let pairs = words
+                .filter(|candidate| candidate.starts_with(prefix))
+                .map(|candidate| Pair {
+                    display: candidate.to_string(),
+                    replacement: candidate.to_string(),
+                })
+                .collect();
+            (start, pairs)
+        };
+        match arg(before) {
+            Arg::Words(words) => pairs(start, word, &mut words.iter().copied()),
+            Arg::Facts => {
+                let from = word.rfind(['&', '|', '!', '(', ')', ',']).map_or(0, |idx| idx + 1);
+                let facts = &mut self.facts.iter().map(String::as_str);
+                pairs(start + from, &word[from..], facts)
+            }
+            Arg::Path => self.files.complete_path(line, pos).unwrap_or((start, vec![])),
+            Arg::None => (start, vec![]),
+        }
+    }
+
+    /// The condition being typed and where it starts, if the line has one.
+    fn condition<'l>(&self, line: &'l str) -> Option<(usize, &'l str)> {
+        let words = line.split(' ').collect::<Vec<_>>();
+        let idx = condition_arg(&words)?;
+        let start = words[..idx].iter().map(|word| word.len() + 1).sum();
+        Some((start, words[idx]))
+    }
+}
+
+/// Operators and parentheses in color, everything from a parse error on in
+/// red.
+fn highlight_condition(condition: &str) -> String {
+    let error = match condition.parse::<Condition>() {
+        Ok(_) => None,
+        Err(e) => Some(e.position()),
+    };
+    let mut out = String::new();
+    for (idx, c) in condition.chars().enumerate() {
+        let s = c.to_string();
+        let colored = match c {
+            _ if error.is_some_and(|position| idx >= position) => s.red().underline(),
+            '&' | '|' => s.cyan(),
+            '!' => s.magenta(),
+            '(' | ')' => s.blue(),
+            _ => s.normal(),
+        };
+        out.push_str(&colored.to_string());
+    }
+    out
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        Ok(self.candidates(line, pos))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+
+    fn hint(&self, line: &str, pos: usize, ctx: &Context<'_>) -> Option<String> {
+        if let Some((_, condition)) = self.condition(line).filter(|(_, condition)| !condition.is_empty()) {
+            if let Err(e) = condition.parse::<Condition>() {
+                return (pos == line.len()).then(|| format!("{}{}", ERROR_HINT, e));
+            }
+        }
+        self.history.hint(line, pos, ctx)
+    }
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        match self.condition(line) {
+            Some((start, condition)) => {
+                let end = start + condition.len();
+                let highlighted = highlight_condition(condition);
+                Cow::Owned(format!("{}{}{}", &line[..start], highlighted, &line[end..]))
+            }
+            None => Cow::Borrowed(line),
+        }
+    }
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        if hint.starts_with(ERROR_HINT) {
+            Cow::Owned(hint.red().to_string())
+        } else {
+            Cow::Owned(hint.dimmed().to_string())
+        }
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize, _kind: CmdKind) -> bool {
+        true
+    }
+}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replacements(helper: &ReplHelper, line: &str) -> (usize, Vec<String>) {
+        let (start, pairs) = helper.candidates(line, line.len());
+        (start, pairs.into_iter().map(|pair| pair.replacement).collect())
+    }
+
+    #[test]
+    fn test_complete() {
+        let mut helper = ReplHelper::new();
+        helper.set_rules(&[Rule::new("fur & !feathers", "mammal").unwrap()]);
+        assert_eq!(replacements(&helper, "ru"), (0, vec!["rule".to_string(), "runs".to_string()]));
+        assert_eq!(replacements(&helper, "db con"), (3, vec!["connect".to_string()]));
+        assert_eq!(replacements(&helper, "test fur m"), (9, vec!["mammal".to_string()]));
+        assert_eq!(replacements(&helper, "rule add fur&!fe"), (14, vec!["feathers".to_string()]));
+        assert_eq!(replacements(&helper, "rule remove f"), (12, vec![]));
+        assert_eq!(helper.condition("rule add a&(b mammal"), Some((9, "a&(b")));
+    }
+}
@@ -0,0 +1,180 @@
+//! Errors returned by the library. The enums are `#[non_exhaustive]` so new
+//! cases can be added without a breaking release, and errors of the
+//! serialization and database crates are boxed so their versions don't leak
+//! into the public API.
+
+use std::fmt;
+use std::path::PathBuf;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A condition that doesn't parse.
+    Parse(ParseError),
+    /// A rule that is well formed text but not a valid rule, such as one
+    /// without outputs.
+    InvalidRule(String),
+    /// Rules read from a file, each problem with its line.
+    Import(ImportError),
+    /// Something a converter can't express in the target language.
+    Unsupported(String),
+    /// A knowledge base, rule or run that doesn't exist.
+    NotFound(String),
+    /// A knowledge base that already exists.
+    AlreadyExists(String),
+    /// A store that can't be opened or holds malformed data.
+    Store(String),
+    /// An error that happened while working on `path`.
+    File { path: PathBuf, source: Box<Error> },
+    Io(std::io::Error),
+    /// An error of an underlying serialization or database crate.
+    Backend(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl Error {
+    pub(crate) fn in_file(self, path: impl Into<PathBuf>) -> Error {
+        Error::File {
+            path: path.into(),
+            source: Box::new(self),
+        }
+    }
+
+    #[cfg(any(feature = "formats", feature = "store"))]
+    pub(crate) fn backend(e: impl std::error::Error + Send + Sync + 'static) -> Error {
+        Error::Backend(Box::new(e))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse(e) => write!(f, "{}", e),
+            Error::InvalidRule(message)
+            | Error::Unsupported(message)
+            | Error::Store(message) => write!(f, "{}", message),
+            Error::Import(e) => write!(f, "{}", e),
+            Error::NotFound(what) => write!(f, "{} does not exist", what),
+            Error::AlreadyExists(what) => write!(f, "{} already exists", what),
+            Error::File { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Backend(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Parse(e) => Some(e),
+            Error::Import(e) => Some(e),
+            Error::File { source, .. } => Some(source.as_ref()),
+            Error::Io(e) => Some(e),
+            Error::Backend(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+impl From<ImportError> for Error {
+    fn from(e: ImportError) -> Self {
+        Error::Import(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+#[cfg(any(feature = "formats", feature = "store"))]
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::backend(e)
+    }
+}
+
+#[cfg(any(feature = "formats", feature = "store"))]
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Error::backend(e)
+    }
+}
+
+#[cfg(any(feature = "formats", feature = "store"))]
+impl From<toml::ser::Error> for Error {
+    fn from(e: toml::ser::Error) -> Self {
+        Error::backend(e)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<sqlx::Error> for Error {
+    fn from(e: sqlx::Error) -> Self {
+        Error::backend(e)
+    }
+}
+
+/// A condition that doesn't parse, with the character position it failed at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    message: String,
+    position: usize,
+}
+
+impl ParseError {
+    pub(crate) fn new(message: impl Into<String>, position: usize) -> Self {
+        ParseError {
+            message: message.into(),
+            position,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Position in characters from the start of the condition.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A problem with one rule of an imported file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// Every problem found while importing, nothing is imported when there is one.
+#[derive(Debug)]
+pub struct ImportError(pub Vec<LineError>);
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} invalid rules", self.0.len())?;
+        for error in &self.0 {
+            write!(f, "\n  line {}: {}", error.line, error.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ImportError {}
@@ -0,0 +1,368 @@
+//! Rule files in common data formats, chosen by extension.
+
+use crate::error::{Error, Result};
+use crate::rule::Rule;
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// File formats rule bases can be exported to and imported from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Json,
+    JsonLines,
+    Yaml,
+    Csv,
+    Toml,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Result<Format> {
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase());
+        Ok(match ext.as_deref() {
+            Some("json") => Format::Json,
+            Some("jsonl") => Format::JsonLines,
+            Some("yaml" | "yml") => Format::Yaml,
+            Some("csv") => Format::Csv,
+            Some("toml") => Format::Toml,
+            _ => {
+                return Err(Error::Unsupported(format!(
+                    "unknown rule file format {}, use .json, .jsonl, .yaml, .csv or .toml",
+                    path.display()
+                )))
+            }
+        })
+    }
+}
+
+pub use crate::error::{ImportError, LineError};
+
+#[derive(Serialize, Deserialize)]
+struct TomlFile<T> {
+    rules: Vec<T>,
+}
+
+/// CSV has no nesting, so the outputs are joined with commas and the tree
+/// form of the condition is left out. Files without an `enabled` column
+/// hold enabled rules.
+#[derive(Serialize, Deserialize)]
+struct CsvRecord {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    tags: Option<String>,
+    condition: String,
+    output: String,
+    #[serde(default)]
+    explanation: Option<String>,
+    #[serde(default)]
+    template: Option<String>,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    created: Option<String>,
+    #[serde(default)]
+    updated: Option<String>,
+    #[serde(default)]
+    enabled: Option<bool>,
+}
+
+pub fn export_rules(path: &Path, rules: &[Rule]) -> Result<()> {
+    let content = write_rules(Format::from_path(path)?, rules)?;
+    fs::write(path, content).map_err(|e| Error::from(e).in_file(path))?;
+    Ok(())
+}
+
+pub fn import_rules(path: &Path) -> Result<Vec<Rule>> {
+    let format = Format::from_path(path)?;
+    let content = fs::read_to_string(path).map_err(|e| Error::from(e).in_file(path))?;
+    read_rules(format, &content).map_err(|e| Error::from(e).in_file(path))
+}
+
+pub fn write_rules(format: Format, rules: &[Rule]) -> Result<String> {
+    Ok(match format {
+        Format::Json => serde_json::to_string_pretty(rules)? + "\n",
+        Format::JsonLines => {
+            let mut out = String::new();
+            for rule in rules {
+                out.push_str(&serde_json::to_string(rule)?);
+                out.push('\n');
+            }
+            out
+        }
+        // serde_yaml can't write nested enums, the condition tree goes
+        // through a JSON value and comes out as plain nested maps.
+        Format::Yaml => {
+            serde_yaml::to_string(&serde_json::to_value(rules)?).map_err(Error::backend)?
+        }
+        Format::Toml => toml::to_string_pretty(&TomlFile {
+            rules: rules.to_vec(),
+        })?,
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(vec![]);
+            for rule in rules {
+                writer
+                    .serialize(CsvRecord {
+                        name: rule.name.clone(),
+                        description: rule.description.clone(),
+                        tags: Some(rule.tags.join(",")).filter(|tags| !tags.is_empty()),
+                        condition: rule.condition.to_string(),
+                        output: rule.output.join(","),
+                        explanation: rule.explanation.clone(),
+                        template: rule.template.clone(),
+                        author: rule.author.clone(),
+                        created: rule.created.clone(),
+                        updated: rule.updated.clone(),
+                        enabled: Some(rule.enabled),
+                    })
+                    .map_err(Error::backend)?;
+            }
+            let bytes = writer.into_inner().map_err(|e| Error::backend(e.into_error()))?;
+            String::from_utf8(bytes).map_err(Error::backend)?
+        }
+    })
+}
+
+/// Parses and validates every rule of `content`, reporting problems by line.
+pub fn read_rules(format: Format, content: &str) -> Result<Vec<Rule>, ImportError> {
+    let records = match format {
+        Format::Json => json_records(content),
+        Format::JsonLines => jsonl_records(content),
+        Format::Yaml => yaml_records(content),
+        Format::Toml => toml_records(content),
+        Format::Csv => csv_records(content),
+    }?;
+
+    let mut rules = vec![];
+    let mut errors = vec![];
+    for (line, record) in records {
+        match record {
+            Ok(rule) => rules.push(rule),
+            Err(e) => errors.push(LineError {
+                line,
+                message: e.to_string(),
+            }),
+        }
+    }
+    if errors.is_empty() {
+        Ok(rules)
+    } else {
+        Err(ImportError(errors))
+    }
+}
+
+type Records = Vec<(usize, Result<Rule>)>;
+
+fn syntax_error(line: usize, e: impl fmt::Display) -> ImportError {
+    ImportError(vec![LineError {
+        line,
+        message: e.to_string(),
+    }])
+}
+
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+fn json_records(content: &str) -> Result<Records, ImportError> {
+    let raw: Vec<&RawValue> =
+        serde_json::from_str(content).map_err(|e| syntax_error(e.line(), e))?;
+    Ok(raw
+        .into_iter()
+        .map(|value| {
+            let offset = value.get().as_ptr() as usize - content.as_ptr() as usize;
+            let rule = serde_json::from_str::<Rule>(value.get()).map_err(Error::from);
+            (line_of(content, offset), rule)
+        })
+        .collect())
+}
+
+fn jsonl_records(content: &str) -> Result<Records, ImportError> {
+    Ok(content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            let rule = serde_json::from_str::<Rule>(line).map_err(Error::from);
+            (idx + 1, rule)
+        })
+        .collect())
+}
+
+fn yaml_records(content: &str) -> Result<Records, ImportError> {
+    let values: Vec<serde_yaml::Value> = serde_yaml::from_str(content).map_err(|e| {
+        let line = e.location().map(|location| location.line()).unwrap_or(0);
+        syntax_error(line, e)
+    })?;
+    // serde_yaml keeps no positions, top level sequence items start with a
+    // dash in the first column.
+    let starts = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| *line == "-" || line.starts_with("- "))
+        .map(|(idx, _)| idx + 1)
+        .collect::<Vec<_>>();
+    Ok(values
+        .into_iter()
+        .enumerate()
+        .map(|(idx, value)| {
+            let rule = serde_json::to_value(value)
+                .and_then(serde_json::from_value::<Rule>)
+                .map_err(Error::from);
+            (starts.get(idx).copied().unwrap_or(0), rule)
+        })
+        .collect())
+}
+
+fn toml_records(content: &str) -> Result<Records, ImportError> {
+    let file: TomlFile<toml::Spanned<toml::Value>> = toml::from_str(content).map_err(|e| {
+        let line = e.span().map(|span| line_of(content, span.start)).unwrap_or(0);
+        syntax_error(line, e.message())
+    })?;
+    Ok(file
+        .rules
+        .into_iter()
+        .map(|value| {
+            let line = line_of(content, value.span().start);
+            let rule = value.into_inner().try_into::<Rule>().map_err(Error::from);
+            (line, rule)
+        })
+        .collect())
+}
+
+fn csv_records(content: &str) -> Result<Records, ImportError> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| syntax_error(1, e))?
+        .clone();
+    Ok(reader
+        .records()
+        .map(|result| match result {
+            Ok(record) => {
+                let line = record.position().map(|p| p.line() as usize).unwrap_or(0);
+                let rule = record
+                    .deserialize::<CsvRecord>(Some(&headers))
+                    .map_err(Error::backend)
+                    .and_then(|record| {
+                        let mut builder = Rule::new(&record.condition, &record.output)?.to_builder();
+                        for tag in record.tags.iter().flat_map(|tags| tags.split(',')).filter(|tag| !tag.trim().is_empty()) {
+                            builder = builder.tag(tag.trim());
+                        }
+                        let mut rule = builder.build()?;
+                        rule.name = record.name.filter(|name| !name.is_empty());
+                        rule.description = record.description.filter(|d| !d.is_empty());
+                        rule.explanation = record.explanation.filter(|e| !e.is_empty());
+                        rule.template = record.template.filter(|t| !t.is_empty());
+                        rule.author = record.author.filter(|author| !author.is_empty());
+                        rule.created = record.created.filter(|created| !created.is_empty());
+                        rule.updated = record.updated.filter(|updated| !updated.is_empty());
+                        rule.enabled = record.enabled.unwrap_or(true);
+                        Ok(rule)
+                    });
+                (line, rule)
+            }
+            Err(e) => {
+                let line = e.position().map(|p| p.line() as usize).unwrap_or(0);
+                (line, Err(Error::backend(e)))
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> Vec<Rule> {
+        vec![
+            Rule::new("fur & !feathers", "mammal").unwrap(),
+            Rule::new("mammal & eats_meat", "carnivore,predator").unwrap(),
+        ]
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for format in [
+            Format::Json,
+            Format::JsonLines,
+            Format::Yaml,
+            Format::Csv,
+            Format::Toml,
+        ] {
+            let content = write_rules(format, &rules()).unwrap();
+            let parsed = read_rules(format, &content).unwrap();
+            assert_eq!(parsed.len(), 2, "{:?}", format);
+            assert_eq!(parsed[1].condition, rules()[1].condition, "{:?}", format);
+            assert_eq!(parsed[1].output, rules()[1].output, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_csv_metadata() {
+        let rule = Rule::new("fur & !feathers", "mammal")
+            .unwrap()
+            .to_builder()
+            .name("mammal")
+            .description("has fur")
+            .explanation("only mammals have fur")
+            .template("{condition}, so {output}")
+            .author("ann")
+            .tag("zoo")
+            .created("2024-01-01T00:00:00Z")
+            .updated("2024-02-01T00:00:00Z")
+            .enabled(false)
+            .build()
+            .unwrap();
+        let content = write_rules(Format::Csv, std::slice::from_ref(&rule)).unwrap();
+        let parsed = &read_rules(Format::Csv, &content).unwrap()[0];
+        assert_eq!(parsed.name(), Some("mammal"));
+        assert_eq!(parsed.description(), Some("has fur"));
+        assert_eq!(parsed.explanation(), rule.explanation());
+        assert_eq!(parsed.template(), rule.template());
+        assert_eq!(parsed.author(), Some("ann"));
+        assert_eq!(parsed.tags, rule.tags);
+        assert_eq!(parsed.created(), rule.created());
+        assert_eq!(parsed.updated(), rule.updated());
+        assert!(!parsed.is_enabled());
+
+        // Files from before the metadata columns hold enabled rules
+        let parsed = read_rules(Format::Csv, "name,condition,output\nm,fur,mammal\n").unwrap();
+        assert!(parsed[0].is_enabled());
+        assert_eq!((parsed[0].author(), parsed[0].created()), (None, None));
+    }
+
+    #[test]
+    fn test_errors_by_line() {
+        let json = r#"[
+  {"condition": "a", "output": ["b"]},
+  {"condition": "a &", "output": ["b"]},
+  {"condition": "c", "output": []}
+]"#;
+        let errors = read_rules(Format::Json, json).unwrap_err().0;
+        assert_eq!(
+            errors.iter().map(|e| e.line).collect::<Vec<_>>(),
+            [3, 4]
+        );
+
+        let csv = "condition,output\na,b\n(a,b\nc,\"d,e\"\n";
+        let errors = read_rules(Format::Csv, csv).unwrap_err().0;
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 3);
+
+        let yaml = "- condition: a\n  output: [b]\n- condition: '!'\n  output: [c]\n";
+        let errors = read_rules(Format::Yaml, yaml).unwrap_err().0;
+        assert_eq!(errors[0].line, 3);
+
+        let toml = "[[rules]]\ncondition = \"a\"\noutput = [\"b\"]\n\n[[rules]]\ncondition = \"a b\"\noutput = [\"c\"]\n";
+        let errors = read_rules(Format::Toml, toml).unwrap_err().0;
+        assert_eq!(errors[0].line, 5);
+    }
+}
@@ -0,0 +1,152 @@
+//! Explanations in the words of the knowledge base: facts can carry a
+//! label, "它是哺乳动物" for `mammal`, and rules a sentence template, so a
+//! firing reads "因为它是哺乳动物并且吃肉，所以它是食肉动物" rather than
+//! `(mammal & eats_meat)`.
+//!
+//! A label for `!fact` is used for the negated fact. Conditions without any
+//! labelled fact are written as their [`Condition`] `Display` output.
+
+use crate::rule::{Condition, Rule};
+use std::collections::BTreeMap;
+
+/// The display label of each fact that has one.
+pub type Labels = BTreeMap<String, String>;
+
+/// The words joining labelled facts in a condition.
+#[derive(Debug, Clone)]
+pub struct Connectives {
+    pub and: String,
+    pub or: String,
+    pub not: String,
+}
+
+impl Default for Connectives {
+    fn default() -> Self {
+        Connectives {
+            and: " and ".to_string(),
+            or: " or ".to_string(),
+            not: "not ".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Explainer<'a> {
+    labels: &'a Labels,
+    words: Connectives,
+}
+
+impl<'a> Explainer<'a> {
+    pub fn new(labels: &'a Labels, words: Connectives) -> Self {
+        Explainer { labels, words }
+    }
+
+    /// The label of `fact`, or the fact itself.
+    pub fn fact<'f>(&'f self, fact: &'f str) -> &'f str {
+        self.labels.get(fact).map_or(fact, String::as_str)
+    }
+
+    /// The facts by their labels, joined like an `&` of them.
+    pub fn facts(&self, facts: &[String]) -> String {
+        if !facts.iter().any(|fact| self.labels.contains_key(fact)) {
+            return facts.join(", ");
+        }
+        let labels = facts.iter().map(|fact| self.fact(fact)).collect::<Vec<_>>();
+        labels.join(&self.words.and)
+    }
+
+    pub fn condition(&self, condition: &Condition) -> String {
+        if !self.labelled(condition) {
+            return condition.to_string();
+        }
+        self.words(condition, None)
+    }
+
+    fn labelled(&self, condition: &Condition) -> bool {
+        match condition {
+            Condition::Fact(fact) => self.labels.contains_key(fact),
+            Condition::Not(inner) => match inner.as_ref() {
+                Condition::Fact(fact) => self.labels.contains_key(&format!("!{}", fact)) || self.labelled(inner),
+                _ => self.labelled(inner),
+            },
+            Condition::And(lhs, rhs) | Condition::Or(lhs, rhs) => self.labelled(lhs) || self.labelled(rhs),
+        }
+    }
+
+    /// Chains of the same operator are written without parentheses.
+    fn words(&self, condition: &Condition, parent: Option<bool>) -> String {
+        let (is_and, lhs, rhs) = match condition {
+            Condition::Fact(fact) => return self.fact(fact).to_string(),
+            Condition::Not(inner) => {
+                if let Condition::Fact(fact) = inner.as_ref() {
+                    if let Some(label) = self.labels.get(&format!("!{}", fact)) {
+                        return label.clone();
+                    }
+                }
+                return format!("{}{}", self.words.not, self.words(inner, Some(false)));
+            }
+            Condition::And(lhs, rhs) => (true, lhs, rhs),
+            Condition::Or(lhs, rhs) => (false, lhs, rhs),
+        };
+        let joint = if is_and { &self.words.and } else { &self.words.or };
+        let text = format!(
+            "{}{}{}",
+            self.words(lhs, Some(is_and)),
+            joint,
+            self.words(rhs, Some(is_and))
+        );
+        match parent {
+            Some(parent) if parent != is_and => format!("({})", text),
+            _ => text,
+        }
+    }
+
+    /// The sentence of `rule` adding `outputs`, from its template. `None`
+    /// when the rule has no template.
+    pub fn sentence(&self, rule: &Rule, outputs: &[String]) -> Option<String> {
+        let template = rule.template()?;
+        Some(
+            template
+                .replace("{condition}", &self.condition(rule.condition()))
+                .replace("{output}", &self.facts(outputs)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explainer() {
+        let labels = Labels::from([
+            ("mammal".to_string(), "它是哺乳动物".to_string()),
+            ("eats_meat".to_string(), "吃肉".to_string()),
+            ("carnivore".to_string(), "它是食肉动物".to_string()),
+            ("!feathers".to_string(), "没有羽毛".to_string()),
+        ]);
+        let words = Connectives {
+            and: "并且".to_string(),
+            or: "或者".to_string(),
+            not: "不是".to_string(),
+        };
+        let explainer = Explainer::new(&labels, words);
+        let rule = Rule::new("mammal & eats_meat", "carnivore")
+            .unwrap()
+            .to_builder()
+            .template("因为{condition}，所以{output}")
+            .build()
+            .unwrap();
+        assert_eq!(
+            explainer.sentence(&rule, rule.output()).unwrap(),
+            "因为它是哺乳动物并且吃肉，所以它是食肉动物"
+        );
+
+        let condition = "(mammal | fur) & !feathers & !eats_meat".parse().unwrap();
+        assert_eq!(explainer.condition(&condition), "(它是哺乳动物或者fur)并且没有羽毛并且不是吃肉");
+        // Nothing labelled, nothing changes
+        let condition = "fur & !milk".parse::<Condition>().unwrap();
+        assert_eq!(explainer.condition(&condition), condition.to_string());
+        assert!(explainer.sentence(&Rule::new("fur", "milk").unwrap(), &[]).is_none());
+    }
+}
@@ -0,0 +1,211 @@
+//! The dependency graph of a rule base: facts point to the rules testing
+//! them and rules point to the facts they derive. Facts tested under a
+//! negation get a negated edge.
+
+use crate::rule::{Condition, Rule};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase", tag = "kind", content = "id")]
+pub enum Node {
+    Fact(String),
+    Rule(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Edge {
+    pub from: Node,
+    pub to: Node,
+    pub negated: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Graph {
+    pub facts: Vec<String>,
+    /// Indices into the rules the graph was built from.
+    pub rules: Vec<usize>,
+    pub edges: Vec<Edge>,
+}
+
+impl Graph {
+    pub fn build(rules: &[Rule]) -> Graph {
+        let mut facts = BTreeSet::new();
+        let mut edges = vec![];
+        for (idx, rule) in rules.iter().enumerate() {
+            let mut tested = vec![];
+            condition_facts(rule.condition(), false, &mut tested);
+            for (fact, negated) in tested {
+                facts.insert(fact.clone());
+                edges.push(Edge {
+                    from: Node::Fact(fact),
+                    to: Node::Rule(idx),
+                    negated,
+                });
+            }
+            for fact in rule.output() {
+                facts.insert(fact.clone());
+                edges.push(Edge {
+                    from: Node::Rule(idx),
+                    to: Node::Fact(fact.clone()),
+                    negated: false,
+                });
+            }
+        }
+        Graph {
+            facts: facts.into_iter().collect(),
+            rules: (0..rules.len()).collect(),
+            edges,
+        }
+    }
+
+    /// The part of the graph `goal` can be derived from: the rules deriving
+    /// it, the facts they test, and so on backwards.
+    pub fn reaching(&self, goal: &str) -> Graph {
+        let mut keep = BTreeSet::from([Node::Fact(goal.to_string())]);
+        let mut queue = vec![Node::Fact(goal.to_string())];
+        while let Some(node) = queue.pop() {
+            for edge in self.edges.iter().filter(|edge| edge.to == node) {
+                if keep.insert(edge.from.clone()) {
+                    queue.push(edge.from.clone());
+                }
+            }
+        }
+        Graph {
+            facts: self
+                .facts
+                .iter()
+                .filter(|fact| keep.contains(&Node::Fact(fact.to_string())))
+                .cloned()
+                .collect(),
+            rules: self
+                .rules
+                .iter()
+                .filter(|&&idx| keep.contains(&Node::Rule(idx)))
+                .copied()
+                .collect(),
+            edges: self
+                .edges
+                .iter()
+                .filter(|edge| keep.contains(&edge.from) && keep.contains(&edge.to))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Graphviz source, `rules` must be the ones the graph was built from.
+    pub fn to_dot(&self, rules: &[Rule]) -> String {
+        let mut out = String::from("digraph rules {\n    rankdir=LR;\n");
+        for fact in &self.facts {
+            out.push_str(&format!("    \"f_{}\" [label=\"{}\", shape=ellipse];\n", fact, fact));
+        }
+        for &idx in &self.rules {
+            out.push_str(&format!(
+                "    \"r_{}\" [label=\"{}\", shape=box];\n",
+                idx,
+                dot_escape(&rule_label(idx, &rules[idx]))
+            ));
+        }
+        for edge in &self.edges {
+            out.push_str(&format!("    \"{}\" -> \"{}\"", node_id(&edge.from), node_id(&edge.to)));
+            if edge.negated {
+                out.push_str(" [style=dashed, arrowhead=odot, label=\"not\"]");
+            }
+            out.push_str(";\n");
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// A Mermaid flowchart, `rules` must be the ones the graph was built from.
+    pub fn to_mermaid(&self, rules: &[Rule]) -> String {
+        let mut out = String::from("flowchart LR\n");
+        for fact in &self.facts {
+            out.push_str(&format!("    f_{}([{}])\n", fact, fact));
+        }
+        for &idx in &self.rules {
+            out.push_str(&format!(
+                "    r_{}[\"{}\"]\n",
+                idx,
+                mermaid_escape(&rule_label(idx, &rules[idx]))
+            ));
+        }
+        for edge in &self.edges {
+            let arrow = if edge.negated { "-. not .->" } else { "-->" };
+            out.push_str(&format!("    {} {} {}\n", node_id(&edge.from), arrow, node_id(&edge.to)));
+        }
+        out
+    }
+}
+
+fn condition_facts(condition: &Condition, negated: bool, facts: &mut Vec<(String, bool)>) {
+    match condition {
+        Condition::Fact(fact) => {
+            let entry = (fact.clone(), negated);
+            if !facts.contains(&entry) {
+                facts.push(entry);
+            }
+        }
+        Condition::And(lhs, rhs) | Condition::Or(lhs, rhs) => {
+            condition_facts(lhs, negated, facts);
+            condition_facts(rhs, negated, facts);
+        }
+        Condition::Not(inner) => condition_facts(inner, !negated, facts),
+    }
+}
+
+fn node_id(node: &Node) -> String {
+    match node {
+        Node::Fact(fact) => format!("f_{}", fact),
+        Node::Rule(idx) => format!("r_{}", idx),
+    }
+}
+
+fn rule_label(idx: usize, rule: &Rule) -> String {
+    match rule.name() {
+        Some(name) => format!("#{} {}\nIF {}", idx, name, rule.condition()),
+        None => format!("#{}\nIF {}", idx, rule.condition()),
+    }
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn mermaid_escape(s: &str) -> String {
+    s.replace('#', "#35;")
+        .replace('"', "#quot;")
+        .replace('\n', "<br/>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph() {
+        let rules = vec![
+            Rule::new("fur", "mammal").unwrap(),
+            Rule::new("mammal & !feathers", "tiger").unwrap(),
+            Rule::new("feathers", "bird").unwrap(),
+        ];
+        let graph = Graph::build(&rules);
+        assert_eq!(graph.facts, ["bird", "feathers", "fur", "mammal", "tiger"]);
+        assert!(graph.edges.contains(&Edge {
+            from: Node::Fact("feathers".to_string()),
+            to: Node::Rule(1),
+            negated: true,
+        }));
+
+        let tiger = graph.reaching("tiger");
+        assert_eq!(tiger.rules, [0, 1]);
+        assert_eq!(tiger.facts, ["feathers", "fur", "mammal", "tiger"]);
+
+        let dot = tiger.to_dot(&rules);
+        assert!(dot.contains("\"f_feathers\" -> \"r_1\" [style=dashed, arrowhead=odot, label=\"not\"];"));
+        assert!(!dot.contains("bird"));
+        let mermaid = tiger.to_mermaid(&rules);
+        assert!(mermaid.contains("    f_feathers -. not .-> r_1\n"));
+        assert!(mermaid.contains("    r_0[\"#35;0<br/>IF fur\"]\n"));
+    }
+}
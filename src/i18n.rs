@@ -0,0 +1,163 @@
+//! The message catalog: every message the command line shows, as Fluent
+//! messages in `locales/<lang>/expert.ftl`.
+//!
+//! Messages are looked up with [`t!`], in the language picked by `set lang`
+//! or the environment, falling back to Chinese and then to the message id.
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::LazyLock;
+use unic_langid::LanguageIdentifier;
+
+/// A message from the catalog, `t!(<id>, name = value)` sets the `$name`
+/// variable of the message to `value.to_string()`.
+macro_rules! t {
+    ($id:literal) => {
+        $crate::i18n::message($id, None)
+    };
+    ($id:literal, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value.to_string());)+
+        $crate::i18n::message($id, Some(&args))
+    }};
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lang {
+    ZhCn,
+    EnUs,
+}
+
+impl Lang {
+    pub const ALL: [Lang; 2] = [Lang::ZhCn, Lang::EnUs];
+
+    /// Accepts locale names the way `LANG` spells them, `en_US.UTF-8`, as
+    /// well as `zh-CN` or just `en`.
+    pub fn parse(s: &str) -> Option<Lang> {
+        let name = s.split(['.', '@']).next().unwrap_or_default().replace('_', "-");
+        let id = name.parse::<LanguageIdentifier>().ok()?;
+        match id.language.as_str() {
+            "zh" => Some(Lang::ZhCn),
+            "en" => Some(Lang::EnUs),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::ZhCn => "zh-CN",
+            Lang::EnUs => "en-US",
+        }
+    }
+
+    /// The first of `LC_ALL`, `LC_MESSAGES` and `LANG` that is set, like
+    /// gettext. `C` and `POSIX` name no language.
+    pub fn from_env() -> Option<Lang> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Lang::parse(&value))
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Lang::ZhCn => include_str!("../locales/zh-CN/expert.ftl"),
+            Lang::EnUs => include_str!("../locales/en-US/expert.ftl"),
+        }
+    }
+
+    fn bundle(self) -> FluentBundle<FluentResource> {
+        let resource = FluentResource::try_new(self.source().to_string())
+            .unwrap_or_else(|(resource, _)| resource);
+        let id = self.code().parse::<LanguageIdentifier>().expect("valid language id");
+        let mut bundle = FluentBundle::new_concurrent(vec![id]);
+        // The isolation marks would end up in terminals and log files
+        bundle.set_use_isolating(false);
+        bundle
+            .add_resource(resource)
+            .expect("message ids are unique");
+        bundle
+    }
+}
+
+static BUNDLES: LazyLock<Vec<FluentBundle<FluentResource>>> =
+    LazyLock::new(|| Lang::ALL.iter().map(|lang| lang.bundle()).collect());
+
+/// The index of the current language in [`Lang::ALL`].
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+
+pub fn set_lang(lang: Lang) {
+    let idx = Lang::ALL.iter().position(|l| *l == lang).unwrap_or_default();
+    CURRENT.store(idx, Ordering::Relaxed);
+}
+
+pub fn lang() -> Lang {
+    Lang::ALL[CURRENT.load(Ordering::Relaxed)]
+}
+
+/// Formats message `id`, see [`t!`].
+pub fn message(id: &str, args: Option<&FluentArgs>) -> String {
+    let current = &BUNDLES[CURRENT.load(Ordering::Relaxed)];
+    for bundle in [current, &BUNDLES[0]] {
+        if let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) {
+            let mut errors = vec![];
+            return bundle.format_pattern(pattern, args, &mut errors).into_owned();
+        }
+    }
+    id.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fluent_bundle::FluentResource;
+    use regex::Regex;
+    use std::collections::BTreeSet;
+
+    fn ids(lang: Lang) -> BTreeSet<String> {
+        if let Err((_, errors)) = FluentResource::try_new(lang.source().to_string()) {
+            panic!("{} does not parse: {:?}", lang.code(), errors);
+        }
+        let id = Regex::new(r"(?m)^([a-z][a-z0-9-]*) =").unwrap();
+        id.captures_iter(lang.source()).map(|captures| captures[1].to_string()).collect()
+    }
+
+    #[test]
+    fn test_catalogs() {
+        let zh = ids(Lang::ZhCn);
+        assert_eq!(zh, ids(Lang::EnUs));
+
+        // Every message the code asks for is in the catalogs
+        let used = Regex::new(r#"t!\(\s*"([a-z0-9-]+)""#).unwrap();
+        let mut missing = vec![];
+        let mut files = vec![std::path::PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/src"))];
+        while let Some(path) = files.pop() {
+            if path.is_dir() {
+                files.extend(std::fs::read_dir(&path).unwrap().map(|entry| entry.unwrap().path()));
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                let source = std::fs::read_to_string(&path).unwrap();
+                for captures in used.captures_iter(&source) {
+                    if !zh.contains(&captures[1]) {
+                        missing.push(format!("{}: {}", path.display(), &captures[1]));
+                    }
+                }
+            }
+        }
+        assert!(missing.is_empty(), "missing messages: {:?}", missing);
+    }
+
+    #[test]
+    fn test_lang() {
+        assert_eq!(Lang::parse("en_US.UTF-8"), Some(Lang::EnUs));
+        assert_eq!(Lang::parse("zh-CN"), Some(Lang::ZhCn));
+        assert_eq!(Lang::parse("zh_TW"), Some(Lang::ZhCn));
+        assert_eq!(Lang::parse("C"), None);
+
+        let args = FluentArgs::from_iter([("kb", "animals")]);
+        let en = &BUNDLES[1];
+        let pattern = en.get_message("db-kb-loading").unwrap().value().unwrap();
+        assert_eq!(en.format_pattern(pattern, Some(&args), &mut vec![]), "Loading knowledge base animals");
+    }
+}
@@ -0,0 +1,112 @@
+//! The edits made to the rules, tests and fact labels in this session, for `undo`,
+//! `redo` and `history`.
+
+use expert::explain::Labels;
+use expert::{Rule, TestCase};
+use serde::Serialize;
+
+/// Older entries are dropped beyond this.
+const MAX_ENTRIES: usize = 100;
+
+/// The rules, tests and labels as they were at some point.
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    pub rules: Vec<Rule>,
+    pub tests: Vec<TestCase>,
+    pub labels: Labels,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Entry {
+    pub action: String,
+    /// Undone and not redone yet.
+    pub undone: bool,
+    #[serde(skip)]
+    snapshot: Snapshot,
+}
+
+/// Undone entries stay after the ones that can be undone until a new edit
+/// replaces them. An entry holds the state before its edit while it can be
+/// undone and the state after it once undone.
+#[derive(Debug, Default)]
+pub struct Journal {
+    entries: Vec<Entry>,
+}
+
+impl Journal {
+    /// Records an edit about to be made to the state `before`.
+    pub fn record(&mut self, action: String, before: Snapshot) {
+        self.entries.retain(|entry| !entry.undone);
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.entries.push(Entry {
+            action,
+            undone: false,
+            snapshot: before,
+        });
+    }
+
+    /// Takes back the last edit, returns its action and the state to go
+    /// back to.
+    pub fn undo(&mut self, current: Snapshot) -> Option<(String, Snapshot)> {
+        let entry = self.entries.iter_mut().rev().find(|entry| !entry.undone)?;
+        entry.undone = true;
+        let before = std::mem::replace(&mut entry.snapshot, current);
+        Some((entry.action.clone(), before))
+    }
+
+    /// Makes the last undone edit again.
+    pub fn redo(&mut self, current: Snapshot) -> Option<(String, Snapshot)> {
+        let entry = self.entries.iter_mut().find(|entry| entry.undone)?;
+        entry.undone = false;
+        let after = std::mem::replace(&mut entry.snapshot, current);
+        Some((entry.action.clone(), after))
+    }
+
+    /// Oldest first.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(rules: &[(&str, &str)]) -> Snapshot {
+        Snapshot {
+            rules: rules.iter().map(|(condition, output)| Rule::new(condition, output).unwrap()).collect(),
+            ..Snapshot::default()
+        }
+    }
+
+    fn text(snapshot: &Snapshot) -> Vec<String> {
+        snapshot.rules.iter().map(|rule| rule.to_string()).collect()
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut journal = Journal::default();
+        journal.record("add a".to_string(), snapshot(&[]));
+        journal.record("add b".to_string(), snapshot(&[("a", "x")]));
+        let current = snapshot(&[("a", "x"), ("b", "y")]);
+
+        let (action, state) = journal.undo(current).unwrap();
+        assert_eq!(action, "add b");
+        assert_eq!(text(&state), ["IF a THEN x"]);
+        let (action, state) = journal.undo(state).unwrap();
+        assert_eq!(action, "add a");
+        assert!(state.rules.is_empty());
+        assert!(journal.undo(state.clone()).is_none());
+
+        let (action, state) = journal.redo(state).unwrap();
+        assert_eq!(action, "add a");
+        assert_eq!(text(&state), ["IF a THEN x"]);
+
+        journal.record("add c".to_string(), state);
+        assert_eq!(journal.entries().len(), 2);
+        assert!(journal.entries().iter().all(|entry| !entry.undone));
+        assert!(journal.redo(Snapshot::default()).is_none());
+    }
+}
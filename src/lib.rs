@@ -0,0 +1,50 @@
+//! A small forward chaining expert system.
+//!
+//! Rules are `IF condition THEN facts`, where the condition is a boolean
+//! expression over facts. [`Facts::deduce`] applies rules until nothing new
+//! can be derived:
+//!
+//! ```
+//! use expert::{Facts, Rule};
+//!
+//! let rules = [
+//!     Rule::new("fur | milk", "mammal")?,
+//!     Rule::new("mammal & stripes", "tiger")?,
+//! ];
+//! let mut facts = Facts::new(&["fur", "stripes"]);
+//! facts.deduce(&rules);
+//! assert_eq!(facts.derived(), ["mammal", "tiger"]);
+//! # Ok::<(), expert::Error>(())
+//! ```
+//!
+//! Optional parts are behind features:
+//!
+//! - `formats`: [`exchange`] (JSON, YAML, CSV, TOML), [`convert`] (CLIPS,
+//!   Prolog, Drools, JSON Logic) and [`batch`] evaluation of labelled cases
+//! - `store`: the [`store::RuleStore`] trait with file and memory backends
+//! - `sqlite`: the SQLite backend, implies `store`
+//! - `cli`: everything above plus the `expert` REPL binary, on by default
+
+#[cfg(feature = "formats")]
+pub mod batch;
+pub mod confluence;
+#[cfg(feature = "formats")]
+pub mod convert;
+pub mod coverage;
+pub mod debug;
+pub mod dsl;
+pub mod error;
+pub mod explain;
+pub mod graph;
+#[cfg(feature = "formats")]
+pub mod exchange;
+pub mod proof;
+pub mod rule;
+pub mod search;
+#[cfg(feature = "store")]
+pub mod store;
+pub mod testing;
+
+pub use error::{Error, ImportError, LineError, ParseError, Result};
+pub use rule::{Condition, Facts, Firing, Options, Rule, RuleBuilder, Strategy};
+pub use testing::{TestCase, TestResult};
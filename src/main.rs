@@ -1,24 +1,87 @@
-use crate::command::{handle_command, print_header};
-use crate::db::Database;
-use crate::rule::{Condition, Rule};
+use crate::cli::{Args, Mode};
+use crate::command::{connect_and_load, handle_command, print_header};
+use crate::config::LogFormat;
+use crate::journal::{Journal, Snapshot};
+use crate::output::{render_table, OutputFormat};
 use anyhow::Result;
+use expert::store::{self, RuleStore, DEFAULT_KB};
+use expert::coverage::Coverage;
+use expert::debug::{Breakpoint, Debugger};
+use expert::explain::{Connectives, Explainer, Labels};
+use expert::{Facts, Options, Rule, RuleBuilder, TestCase};
 use colored::Colorize;
 use rustyline::error::ReadlineError;
-use rustyline::DefaultEditor;
-use tabled::settings::object::Rows;
-use tabled::settings::{Alignment, Style};
-use tracing::{error, info};
+use rustyline::history::DefaultHistory;
+use rustyline::{CompletionType, Config, Editor};
+use serde::Serialize;
+use tracing::{error, info, warn};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+use std::process::ExitCode;
 
+#[macro_use]
+mod i18n;
+mod cli;
 mod command;
-mod db;
-mod rule;
+mod config;
+mod editor;
+mod journal;
+mod output;
+#[cfg(feature = "tui")]
+mod tui;
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Context {
-    db: Option<Database>,
+    db: Option<Box<dyn RuleStore>>,
+    kb: String,
     rules: Vec<Rule>,
+    tests: Vec<TestCase>,
+    /// The words explanations use for facts, see [`expert::explain`].
+    labels: Labels,
+    input: Vec<String>,
+    format: OutputFormat,
+    printed: bool,
+    /// Cycle limit and conflict strategy of `test` and `scenario run`.
+    engine: Options,
+    /// Rule coverage of every deduction since the last `coverage reset`.
+    coverage: Coverage,
+    debugger: Option<Debugger>,
+    /// The facts of the last `test` or `scenario run` with the rules it
+    /// used, for `proof`.
+    last_deduction: Option<(Facts, Vec<Rule>)>,
+    breakpoints: Vec<Breakpoint>,
+    watches: Vec<String>,
+    /// Text to start the next REPL line with, set by `rule edit`.
+    prefill: Option<String>,
+    journal: Journal,
+    /// Edits made since the rules were last loaded or synced, `kb use`
+    /// won't drop them without `--force`.
+    unsynced: bool,
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Context {
+            db: None,
+            kb: DEFAULT_KB.to_string(),
+            rules: Vec::new(),
+            tests: Vec::new(),
+            labels: Labels::new(),
+            input: Vec::new(),
+            format: OutputFormat::Text,
+            printed: false,
+            engine: Options::default(),
+            coverage: Coverage::default(),
+            debugger: None,
+            last_deduction: None,
+            breakpoints: Vec::new(),
+            watches: Vec::new(),
+            prefill: None,
+            journal: Journal::default(),
+            unsynced: false,
+        }
+    }
 }
 
 impl Context {
@@ -27,91 +90,283 @@ impl Context {
     }
 
     pub async fn connect(&mut self, url: &str) -> Result<()> {
-        let db = Database::new(url).await?;
+        let db = store::open(url).await?;
         self.db = Some(db);
+        self.kb = DEFAULT_KB.to_string();
         Ok(())
     }
 
     pub fn add_rule(&mut self, condition: &str, output: &str) -> Result<()> {
-        let condition = condition.parse::<Condition>()?;
-        let output = output.split(",").map(|x| x.to_string()).collect::<Vec<_>>();
-        let rule = Rule{condition, output};
+        let now = store::now();
+        let rule = Rule::new(condition, output)?.to_builder().created(&now).updated(now).build()?;
+        self.record(format!("rule add: {}", rule));
         self.rules.push(rule);
         Ok(())
     }
 
-    pub fn list_rules(&self) -> String {
+    /// Explains with the fact labels, in the words of the current language.
+    pub fn explainer(&self) -> Explainer<'_> {
+        let words = Connectives {
+            and: t!("explain-and"),
+            or: t!("explain-or"),
+            not: t!("explain-not"),
+        };
+        Explainer::new(&self.labels, words)
+    }
+
+    /// Prints the result of a command, as `text()` or in JSON mode as `value`.
+    pub fn print(&mut self, value: impl Serialize, text: impl FnOnce() -> String) {
+        self.printed = true;
+        match self.format {
+            OutputFormat::Text => println!("{}", text()),
+            OutputFormat::Json => match serde_json::to_string(&value) {
+                Ok(json) => println!("{}", json),
+                Err(e) => error!("{}", t!("error-json", error = e)),
+            },
+        }
+    }
+
+    /// The rules as a table, `long` adds a column for every piece of
+    /// metadata. Disabled rules are dimmed.
+    pub fn list_rules(&self, long: bool) -> String {
         let mut builder = tabled::builder::Builder::default();
-        builder.push_record(["id", "condition", "output"]);
+        let mut header = vec![t!("col-id"), t!("col-name"), t!("col-condition"), t!("col-output")];
+        if long {
+            header.extend([
+                t!("col-tags"),
+                t!("col-enabled"),
+                t!("col-author"),
+                t!("col-created"),
+                t!("col-updated"),
+                t!("col-description"),
+                t!("col-explanation"),
+                t!("col-template"),
+            ]);
+        }
+        builder.push_record(header);
         for (i, rule) in self.rules.iter().enumerate() {
-            builder.push_record([i.to_string(), rule.condition.to_string(), rule.output.join(",")]);
+            let mut record = vec![
+                i.to_string(),
+                rule.name().unwrap_or_default().to_string(),
+                rule.condition().to_string(),
+                rule.output().join(","),
+            ];
+            if long {
+                record.extend([
+                    rule.tags().join(","),
+                    if rule.is_enabled() { t!("yes") } else { t!("no") },
+                    rule.author().unwrap_or_default().to_string(),
+                    rule.created().unwrap_or_default().to_string(),
+                    rule.updated().unwrap_or_default().to_string(),
+                    rule.description().unwrap_or_default().to_string(),
+                    rule.explanation().unwrap_or_default().to_string(),
+                    rule.template().unwrap_or_default().to_string(),
+                ]);
+            }
+            if !rule.is_enabled() {
+                record = record.into_iter().map(|cell| cell.dimmed().to_string()).collect();
+            }
+            builder.push_record(record);
         }
-        builder
-            .build()
-            .with(Style::rounded())
-            .modify(Rows::new(1..), Alignment::left())
-            .to_string()
+        render_table(builder)
     }
 
     pub fn remove_rule(&mut self, idx: &str) -> Result<()> {
-        let idx = idx.parse::<usize>()?;
+        let idx = self.rule_index(idx)?;
+        self.record(format!("rule remove {}: {}", idx, self.rules[idx]));
         self.rules.remove(idx);
         Ok(())
     }
-}
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    match enable_ansi_support::enable_ansi_support() {
-        Ok(()) => {
-            println!("\x1b[31mHello, world\x1b[0m");
+    /// Replaces a rule in place with `edit` applied to it, nothing changes
+    /// when the edited rule is invalid.
+    pub fn edit_rule(
+        &mut self,
+        idx: &str,
+        edit: impl FnOnce(RuleBuilder) -> Result<RuleBuilder>,
+    ) -> Result<usize> {
+        let idx = self.rule_index(idx)?;
+        let rule = edit(self.rules[idx].to_builder())?.updated(store::now()).build()?;
+        self.record(format!("rule edit {}: {}", idx, rule));
+        self.rules[idx] = rule;
+        Ok(idx)
+    }
+
+    /// Records an edit of the rules or tests about to be made, so it can
+    /// be undone.
+    pub fn record(&mut self, action: impl Into<String>) {
+        let before = self.snapshot();
+        self.journal.record(action.into(), before);
+        self.unsynced = true;
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            rules: self.rules.clone(),
+            tests: self.tests.clone(),
+            labels: self.labels.clone(),
         }
-        Err(e) => {
-            panic!("Could not enable ansi support: {}", e);
+    }
+
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.rules = snapshot.rules;
+        self.tests = snapshot.tests;
+        self.labels = snapshot.labels;
+        self.unsynced = true;
+    }
+
+    fn rule_index(&self, idx: &str) -> Result<usize> {
+        let idx = idx.parse::<usize>()?;
+        if idx >= self.rules.len() {
+            anyhow::bail!(t!("rule-missing", id = idx));
         }
+        Ok(idx)
     }
+}
 
-    if std::env::var("RUST_LOG").is_err() {
-        std::env::set_var("RUST_LOG", "info");
+#[tokio::main]
+async fn main() -> ExitCode {
+    if let Some(lang) = i18n::Lang::from_env() {
+        i18n::set_lang(lang);
+    }
+    let args = Args::parse_localized();
+    let format = args.format;
+    let settings = config::Config::load(args.config.as_deref());
+    let mode = args.mode();
+    let interactive = matches!(mode, Mode::Interactive);
+    let color = settings.as_ref().ok().and_then(|settings| settings.color);
+    if interactive {
+        match enable_ansi_support::enable_ansi_support() {
+            Ok(()) => {
+                println!("\x1b[31mHello, world\x1b[0m");
+            }
+            Err(e) => {
+                panic!("Could not enable ansi support: {}", e);
+            }
+        }
+    }
+    match color {
+        Some(color) => colored::control::set_override(color),
+        None if !interactive => colored::control::set_override(false),
+        None => {}
     }
-    // Start logging to console
+
+    // Start logging to console, RUST_LOG wins over the config file
+    let log = settings.as_ref().map(|settings| &settings.log).ok();
+    let level = log.map_or("info", |log| log.level.as_str());
+    let filter = EnvFilter::try_from_default_env().or_else(|_| EnvFilter::try_new(level));
+    let invalid_level = filter.is_err();
+    let layer = tracing_subscriber::fmt::Layer::default()
+        .with_ansi(color.unwrap_or(interactive))
+        .with_writer(std::io::stderr);
+    let layer = match log.map(|log| log.format).unwrap_or_default() {
+        LogFormat::Compact => layer.compact().boxed(),
+        LogFormat::Full => layer.boxed(),
+        LogFormat::Pretty => layer.pretty().boxed(),
+        LogFormat::Json => layer.json().boxed(),
+    };
     tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::from_default_env())
-        .with(tracing_subscriber::fmt::Layer::default().compact())
+        .with(filter.unwrap_or_else(|_| EnvFilter::new("info")))
+        .with(layer)
         .init();
+    if invalid_level {
+        warn!("{}", t!("config-invalid-log-level", level = level));
+    }
 
-    let mut rl = DefaultEditor::new()?;
-    if rl.load_history("history.txt").is_err() {
-        info!("No previous history.");
+    let settings = match settings {
+        Ok(settings) => settings,
+        Err(e) => {
+            error!("{:#}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Some(lang) = &settings.lang {
+        match i18n::Lang::parse(lang) {
+            Some(lang) => i18n::set_lang(lang),
+            None => warn!("{}", t!("config-unknown-lang", lang = lang)),
+        }
     }
+
     let mut ctx = Context::new();
+    ctx.format = format;
+    ctx.engine = settings.engine;
+    if let Some(url) = &settings.database.url {
+        // The REPL can still connect by hand, a script would run without
+        // the rules it expects
+        if connect_and_load(url, &mut ctx).await.is_err() && !interactive {
+            return ExitCode::FAILURE;
+        }
+    }
+    match mode {
+        Mode::Interactive => match repl(&mut ctx, &settings).await {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                error!("{}", t!("error", error = e));
+                ExitCode::FAILURE
+            }
+        },
+        #[cfg(feature = "tui")]
+        Mode::Tui => match tui::run(&mut ctx) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                error!("{}", t!("error", error = e));
+                ExitCode::FAILURE
+            }
+        },
+        Mode::Script(path) => cli::run_script(&path, &mut ctx).await,
+        Mode::Eval(commands) => cli::run_eval(commands, &mut ctx).await,
+        Mode::Stdin => cli::run_stdin(&mut ctx).await,
+    }
+}
+
+async fn repl(ctx: &mut Context, settings: &config::Config) -> Result<()> {
+    let config = Config::builder()
+        .completion_type(CompletionType::List)
+        .max_history_size(settings.history.size)?
+        .build();
+    let mut rl: Editor<editor::ReplHelper, DefaultHistory> = Editor::with_config(config)?;
+    rl.set_helper(Some(editor::ReplHelper::new()));
+    let history = settings.history_path();
+    if rl.load_history(&history).is_err() {
+        info!("{}", t!("repl-no-history"));
+    }
     print_header();
 
     loop {
-        let readline = rl.readline(&">> ".cyan());
+        if let Some(helper) = rl.helper_mut() {
+            helper.set_rules(&ctx.rules);
+        }
+        let prompt = settings.prompt.cyan().to_string();
+        let readline = match ctx.prefill.take() {
+            Some(line) => rl.readline_with_initial(&prompt, (&line, "")),
+            None => rl.readline(&prompt),
+        };
         match readline {
             Ok(line) => {
                 rl.add_history_entry(line.as_str())?;
-                let quit = handle_command(line, &mut ctx).await?;
-                if quit {
+                // A failed command has logged why, the session goes on
+                if let Ok(true) = handle_command(line, ctx).await {
                     break;
                 }
             }
             Err(ReadlineError::Interrupted) => {
-                info!("Exiting due to CTRL-C");
+                info!("{}", t!("repl-exit-interrupt"));
                 break;
             }
             Err(ReadlineError::Eof) => {
-                info!("Exiting due to CTRL-D");
+                info!("{}", t!("repl-exit-eof"));
                 break;
             }
             Err(err) => {
-                error!("Error: {:?}", err);
+                error!("{}", t!("error", error = format!("{:?}", err)));
                 break;
             }
         }
         println!()
     }
-    rl.save_history("history.txt")?;
+    if let Some(dir) = history.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    rl.save_history(&history)?;
     Ok(())
 }
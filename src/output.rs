@@ -0,0 +1,71 @@
+use crate::command::{Failed, Outcome};
+use clap::ValueEnum;
+use serde::Serialize;
+use tabled::builder::Builder;
+use tabled::settings::object::Rows;
+use tabled::settings::{Alignment, Style};
+
+/// How command results are written to stdout.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// Tables and plain text for people
+    #[default]
+    Text,
+    /// One JSON document per command
+    Json,
+}
+
+impl OutputFormat {
+    pub fn parse(s: &str) -> Option<OutputFormat> {
+        <OutputFormat as ValueEnum>::from_str(s, true).ok()
+    }
+}
+
+/// What a command without a result of its own prints in JSON mode.
+#[derive(Serialize)]
+pub struct Status<'a> {
+    pub command: &'a str,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl<'a> Status<'a> {
+    /// Taken from what the command returned, whether or not its error was
+    /// logged.
+    pub fn new(command: &'a str, outcome: &Outcome) -> Self {
+        Status {
+            command,
+            ok: outcome.is_ok(),
+            error: outcome.as_ref().err().map(|Failed(message)| message.clone()),
+        }
+    }
+}
+
+/// Draws a table the way every command does, rounded with the rows under
+/// the header aligned left.
+pub(crate) fn render_table(builder: Builder) -> String {
+    builder
+        .build()
+        .with(Style::rounded())
+        .modify(Rows::new(1..), Alignment::left())
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status() {
+        assert_eq!(OutputFormat::parse("JSON"), Some(OutputFormat::Json));
+        assert_eq!(OutputFormat::parse("xml"), None);
+        let status = Status::new("rule remove 4", &Err(Failed("rule 4 does not exist".to_string())));
+        assert_eq!(
+            serde_json::to_string(&status).unwrap(),
+            r#"{"command":"rule remove 4","ok":false,"error":"rule 4 does not exist"}"#
+        );
+        let status = Status::new("rule list", &Ok(()));
+        assert_eq!(serde_json::to_string(&status).unwrap(), r#"{"command":"rule list","ok":true}"#);
+    }
+}
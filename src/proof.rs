@@ -0,0 +1,161 @@
+//! How a fact was reached, as a tree of the rules that fired down to the
+//! input facts.
+
+use crate::debug::reasons;
+use crate::rule::{Facts, Rule};
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase", tag = "kind")]
+pub enum Proof {
+    /// An input fact.
+    Given { fact: String },
+    /// A fact whose absence the rule relied on.
+    Absent { fact: String },
+    Derived {
+        fact: String,
+        rule: usize,
+        cycle: usize,
+        condition: String,
+        premises: Vec<Proof>,
+    },
+}
+
+impl Proof {
+    /// The proof of `fact` after deduction, `rules` must be the ones
+    /// deduction ran with. `None` when the fact isn't known.
+    pub fn build(facts: &Facts, rules: &[Rule], fact: &str) -> Option<Proof> {
+        if facts.input().iter().any(|input| input == fact) {
+            return Some(Proof::Given {
+                fact: fact.to_string(),
+            });
+        }
+        let position = facts
+            .fired()
+            .iter()
+            .position(|firing| firing.added.iter().any(|added| added == fact))?;
+        let firing = &facts.fired()[position];
+        let rule = rules.get(firing.rule)?;
+        // The facts known when the rule fired
+        let mut known = facts.input().to_vec();
+        for earlier in &facts.fired()[..position] {
+            known.extend(earlier.added.iter().cloned());
+        }
+        let (present, missing) = reasons(rule.condition(), &known);
+        let mut premises = present
+            .iter()
+            .map(|premise| Proof::build(facts, rules, premise))
+            .collect::<Option<Vec<_>>>()?;
+        premises.extend(missing.into_iter().map(|fact| Proof::Absent { fact }));
+        Some(Proof::Derived {
+            fact: fact.to_string(),
+            rule: firing.rule,
+            cycle: firing.cycle,
+            condition: rule.condition().to_string(),
+            premises,
+        })
+    }
+
+    pub fn fact(&self) -> &str {
+        match self {
+            Proof::Given { fact } | Proof::Absent { fact } | Proof::Derived { fact, .. } => fact,
+        }
+    }
+
+    /// The line for this step in [`Proof::to_tree`] and [`Proof::to_dot`].
+    pub fn label(&self) -> String {
+        match self {
+            Proof::Given { fact } => format!("{} (given)", fact),
+            Proof::Absent { fact } => format!("not {} (unknown when the rule fired)", fact),
+            Proof::Derived {
+                fact,
+                rule,
+                cycle,
+                condition,
+                ..
+            } => format!("{} (rule #{}, cycle {}: IF {})", fact, rule, cycle, condition),
+        }
+    }
+
+    fn premises(&self) -> &[Proof] {
+        match self {
+            Proof::Derived { premises, .. } => premises,
+            _ => &[],
+        }
+    }
+
+    /// The tree drawn with box-drawing characters.
+    pub fn to_tree(&self) -> String {
+        self.to_tree_with(&Proof::label)
+    }
+
+    /// [`Proof::to_tree`] with the lines written by `label`.
+    pub fn to_tree_with(&self, label: &dyn Fn(&Proof) -> String) -> String {
+        fn draw(proof: &Proof, label: &dyn Fn(&Proof) -> String, prefix: &str, out: &mut String) {
+            let premises = proof.premises();
+            for (i, premise) in premises.iter().enumerate() {
+                let last = i + 1 == premises.len();
+                let (branch, indent) = if last { ("└── ", "    ") } else { ("├── ", "│   ") };
+                out.push_str(&format!("{}{}{}\n", prefix, branch, label(premise)));
+                draw(premise, label, &format!("{}{}", prefix, indent), out);
+            }
+        }
+        let mut out = format!("{}\n", label(self));
+        draw(self, label, "", &mut out);
+        out
+    }
+
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with(&Proof::label)
+    }
+
+    /// [`Proof::to_dot`] with the node labels written by `label`.
+    pub fn to_dot_with(&self, label: &dyn Fn(&Proof) -> String) -> String {
+        fn walk(proof: &Proof, label: &dyn Fn(&Proof) -> String, next: &mut usize, out: &mut String) -> usize {
+            let id = *next;
+            *next += 1;
+            let style = match proof {
+                Proof::Given { .. } => ", shape=ellipse",
+                Proof::Absent { .. } => ", shape=ellipse, style=dashed",
+                Proof::Derived { .. } => ", shape=box",
+            };
+            let text = label(proof).replace('\\', "\\\\").replace('"', "\\\"");
+            out.push_str(&format!("    n{} [label=\"{}\"{}];\n", id, text, style));
+            for premise in proof.premises() {
+                let child = walk(premise, label, next, out);
+                out.push_str(&format!("    n{} -> n{};\n", child, id));
+            }
+            id
+        }
+        let mut out = String::from("digraph proof {\n    rankdir=BT;\n");
+        walk(self, label, &mut 0, &mut out);
+        out.push_str("}\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proof() {
+        let rules = vec![
+            Rule::new("mammal & stripes & !feathers", "tiger").unwrap(),
+            Rule::new("fur", "mammal").unwrap(),
+        ];
+        let mut facts = Facts::new(&["fur", "stripes"]);
+        facts.deduce(&rules);
+        let proof = Proof::build(&facts, &rules, "tiger").unwrap();
+        assert_eq!(
+            proof.to_tree(),
+            "tiger (rule #0, cycle 2: IF ((mammal & stripes) & !feathers))\n\
+             ├── mammal (rule #1, cycle 1: IF fur)\n\
+             │   └── fur (given)\n\
+             ├── stripes (given)\n\
+             └── not feathers (unknown when the rule fired)\n"
+        );
+        assert!(proof.to_dot().contains("    n2 -> n1;\n"));
+        assert!(Proof::build(&facts, &rules, "feathers").is_none());
+    }
+}
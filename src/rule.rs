@@ -1,92 +1,613 @@
-use anyhow::anyhow;
+//! The inference engine: conditions, rules and the facts they are applied to.
+
+use crate::coverage::Coverage;
+use crate::error::{Error, ParseError, Result};
+use serde::{Deserialize, Serialize};
 use std::iter::Peekable;
 use std::str::Chars;
 use std::str::FromStr;
 use log::info;
 
-pub struct Facts(Vec<String>);
+/// Known facts, the input they started from and the rules that fired to
+/// derive the rest.
+#[derive(Debug, Clone, Default)]
+pub struct Facts {
+    facts: Vec<String>,
+    initial: usize,
+    cycle: usize,
+    fired: Vec<Firing>,
+    coverage: Option<Coverage>,
+}
+
+/// How deduction runs, see [`Facts::deduce_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// Deduction stops after this many cycles, `None` runs until nothing
+    /// changes.
+    pub max_cycles: Option<usize>,
+    pub strategy: Strategy,
+}
+
+/// Which of the rules whose condition holds fire in a cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strategy {
+    /// All of them, in rule order.
+    #[default]
+    All,
+    /// Only the first one in rule order that adds a fact.
+    First,
+    /// Only the one whose condition names the most facts, the first of
+    /// those on a tie.
+    Specificity,
+}
+
+/// A rule that fired during deduction, along with the facts it added.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Firing {
+    pub cycle: usize,
+    pub rule: usize,
+    pub added: Vec<String>,
+}
 
 impl Facts {
     pub fn new(vec: &[&str]) -> Facts {
-        Facts(vec.iter().map(|s| s.to_string()).collect())
+        Facts::from(vec.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    }
+
+    /// Every known fact, input first, then derived facts in the order they
+    /// were added.
+    pub fn all(&self) -> &[String] {
+        &self.facts
+    }
+
+    pub fn input(&self) -> &[String] {
+        &self.facts[..self.initial]
     }
 
-    fn recall(&self, fact: &str) -> bool {
-        self.0.iter().any(|x| x == fact)
+    pub fn derived(&self) -> &[String] {
+        &self.facts[self.initial..]
+    }
+
+    pub fn fired(&self) -> &[Firing] {
+        &self.fired
+    }
+
+    /// Number of cycles run so far, including the last one that added nothing.
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    /// Starts counting how rules and their subexpressions evaluate from the
+    /// next cycle on.
+    pub fn record_coverage(&mut self) {
+        self.coverage.get_or_insert_with(Coverage::default);
+    }
+
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_ref()
+    }
+
+    pub fn contains(&self, fact: &str) -> bool {
+        self.facts.iter().any(|x| x == fact)
     }
 
     fn test_if(&self, condition: &Condition) -> bool {
-        condition.matches(&self.0)
+        condition.matches(&self.facts)
     }
 
     fn remember(&mut self, fact: &str) -> bool {
-        if self.recall(fact) {
+        if self.contains(fact) {
             return false;
         }
-        self.0.push(fact.to_string());
+        self.facts.push(fact.to_string());
         true
     }
 
+    /// Runs one cycle, firing every enabled rule whose condition holds in
+    /// order. Returns whether any rule added a fact.
     pub fn step_forward(&mut self, rules: &[Rule]) -> bool {
+        self.step_forward_with(rules, Strategy::All)
+    }
+
+    /// Runs one cycle, firing the rules `strategy` picks. With a strategy
+    /// picking one rule, coverage only counts the rule that fires.
+    pub fn step_forward_with(&mut self, rules: &[Rule], strategy: Strategy) -> bool {
+        if strategy != Strategy::All {
+            self.start_cycle();
+            return match self.choose(rules, strategy) {
+                Some(idx) => self.fire(idx, &rules[idx]).is_some(),
+                None => false,
+            };
+        }
         let mut any_rule_matched = false;
+        self.start_cycle();
 
-        for rule in rules {
-            if self.test_if(&rule.condition) {
-                let matched = rule.output.iter().any(|fact| self.remember(fact));
-                any_rule_matched |= matched;
-                if matched {
-                    info!("Because {} is valid, add outputs: {:?}", rule.condition.to_string(), rule.output);
-                }
-                
+        for (idx, rule) in rules.iter().enumerate() {
+            if self.fire(idx, rule).is_some() {
+                any_rule_matched = true;
             }
         }
 
         any_rule_matched
     }
 
+    /// The rule a strategy picking one rule fires next, if any would add
+    /// a fact.
+    pub(crate) fn choose(&self, rules: &[Rule], strategy: Strategy) -> Option<usize> {
+        let mut candidates = rules.iter().enumerate().filter(|(_, rule)| {
+            rule.enabled
+                && self.test_if(&rule.condition)
+                && rule.output.iter().any(|fact| !self.contains(fact))
+        });
+        let chosen = match strategy {
+            Strategy::Specificity => {
+                candidates.min_by_key(|(_, rule)| std::cmp::Reverse(fact_count(&rule.condition)))
+            }
+            _ => candidates.next(),
+        };
+        chosen.map(|(idx, _)| idx)
+    }
+
+    pub(crate) fn start_cycle(&mut self) {
+        self.cycle += 1;
+    }
+
+    /// Applies one rule of the current cycle, returns its firing when it
+    /// added a fact.
+    pub(crate) fn fire(&mut self, idx: usize, rule: &Rule) -> Option<&Firing> {
+        if !rule.enabled {
+            return None;
+        }
+        let matched = match self.coverage.as_mut() {
+            Some(coverage) => coverage.evaluate(idx, rule, &self.facts),
+            None => self.test_if(&rule.condition),
+        };
+        if !matched {
+            return None;
+        }
+        let added = rule
+            .output
+            .iter()
+            .filter(|fact| self.remember(fact))
+            .cloned()
+            .collect::<Vec<_>>();
+        if added.is_empty() {
+            return None;
+        }
+        if let Some(coverage) = self.coverage.as_mut() {
+            coverage.fired(idx);
+        }
+        self.fired.push(Firing {
+            cycle: self.cycle,
+            rule: idx,
+            added,
+        });
+        self.fired.last()
+    }
+
+    /// Runs cycles until nothing changes and returns how many added facts.
     pub fn deduce(&mut self, rules: &[Rule]) -> usize {
+        self.deduce_with(rules, &Options::default())
+    }
+
+    /// [`Facts::deduce`] with the cycle limit and strategy of `options`.
+    pub fn deduce_with(&mut self, rules: &[Rule], options: &Options) -> usize {
         let mut step = 0;
-        info!("Initial facts: {:?}", self.0);
-        while self.step_forward(rules) {
+        info!("Initial facts: {:?}", self.facts);
+        while options.max_cycles.is_none_or(|max| step < max) && self.step_forward_with(rules, options.strategy) {
             step += 1;
-            info!("Cycle {}, facts: {:?}", step, self.0);
+            info!("Cycle {}, facts: {:?}", step, self.facts);
         }
-        
-        info!("Deduction complete, used {} cycle, facts: {:?}", step, self.0);
+
+        info!("Deduction complete, used {} cycle, facts: {:?}", step, self.facts);
         step
     }
 }
 
+/// How many facts a condition names, for [`Strategy::Specificity`].
+fn fact_count(condition: &Condition) -> usize {
+    match condition {
+        Condition::Fact(_) => 1,
+        Condition::And(lhs, rhs) | Condition::Or(lhs, rhs) => fact_count(lhs) + fact_count(rhs),
+        Condition::Not(inner) => fact_count(inner),
+    }
+}
+
 impl From<Vec<&str>> for Facts {
     fn from(vec: Vec<&str>) -> Facts {
-        Facts(vec.iter().map(|s| s.to_string()).collect())
+        Facts::new(&vec)
     }
 }
 
 impl From<Vec<String>> for Facts {
     fn from(vec: Vec<String>) -> Facts {
-        Facts(vec)
+        let mut facts = Facts::default();
+        for fact in &vec {
+            facts.remember(fact);
+        }
+        facts.initial = facts.facts.len();
+        facts
     }
 }
 
-#[derive(Debug, Clone)]
+/// `IF condition THEN output`, with optional metadata. Disabled rules are
+/// kept but never fire.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "RuleRecord", try_from = "RuleRecord")]
 pub struct Rule {
     pub(crate) condition: Condition,
     pub(crate) output: Vec<String>,
+    pub(crate) name: Option<String>,
+    pub(crate) description: Option<String>,
+    pub(crate) tags: Vec<String>,
+    pub(crate) explanation: Option<String>,
+    pub(crate) template: Option<String>,
+    pub(crate) author: Option<String>,
+    pub(crate) created: Option<String>,
+    pub(crate) updated: Option<String>,
+    pub(crate) enabled: bool,
+}
+
+/// The serialized shape of a [`Rule`], the condition is written both as its
+/// canonical text and as a tree. When reading, the text is authoritative and
+/// the tree is optional, but must agree with the text when present.
+#[derive(Serialize, Deserialize)]
+struct RuleRecord {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    explanation: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    updated: Option<String>,
+    #[serde(default = "enabled", skip_serializing_if = "is_enabled")]
+    enabled: bool,
+    condition: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ast: Option<Condition>,
+    output: Vec<String>,
+}
+
+pub(crate) fn enabled() -> bool {
+    true
+}
+
+pub(crate) fn is_enabled(enabled: &bool) -> bool {
+    *enabled
+}
+
+impl From<Rule> for RuleRecord {
+    fn from(rule: Rule) -> Self {
+        RuleRecord {
+            name: rule.name,
+            description: rule.description,
+            tags: rule.tags,
+            explanation: rule.explanation,
+            template: rule.template,
+            author: rule.author,
+            created: rule.created,
+            updated: rule.updated,
+            enabled: rule.enabled,
+            condition: rule.condition.to_string(),
+            ast: Some(rule.condition),
+            output: rule.output,
+        }
+    }
+}
+
+impl TryFrom<RuleRecord> for Rule {
+    type Error = Error;
+
+    fn try_from(record: RuleRecord) -> Result<Self> {
+        let condition = record.condition.parse::<Condition>()?;
+        if let Some(ast) = record.ast {
+            if ast != condition {
+                return Err(Error::InvalidRule(format!(
+                    "ast {} does not match condition {}",
+                    ast, condition
+                )));
+            }
+        }
+        let mut builder = Rule::builder(condition).outputs(record.output);
+        builder.name = record.name;
+        builder.description = record.description;
+        builder.tags = record.tags;
+        builder.explanation = record.explanation;
+        builder.template = record.template;
+        builder.author = record.author;
+        builder.created = record.created;
+        builder.updated = record.updated;
+        builder.enabled = record.enabled;
+        builder.build()
+    }
+}
+
+/// Whether `s` can be written as a fact in a condition.
+pub fn is_fact_name(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+impl Rule {
+    /// Parses a rule from its condition and comma separated outputs.
+    pub fn new(condition: &str, output: &str) -> Result<Rule> {
+        let condition = condition.parse::<Condition>()?;
+        Rule::builder(condition).outputs(output.split(",")).build()
+    }
+
+    /// A rule without metadata, the outputs aren't checked.
+    pub(crate) fn plain(condition: Condition, output: Vec<String>) -> Rule {
+        Rule {
+            condition,
+            output,
+            name: None,
+            description: None,
+            tags: vec![],
+            explanation: None,
+            template: None,
+            author: None,
+            created: None,
+            updated: None,
+            enabled: true,
+        }
+    }
+
+    /// Starts a rule with `condition`, at least one output has to be added
+    /// before it can be built.
+    ///
+    /// ```
+    /// use expert::{Condition, Rule};
+    ///
+    /// let rule = Rule::builder(Condition::fact("fur").and(Condition::fact("milk")))
+    ///     .output("mammal")
+    ///     .name("mammal_rule")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(rule.to_string(), "IF (fur & milk) THEN mammal");
+    /// ```
+    pub fn builder(condition: Condition) -> RuleBuilder {
+        RuleBuilder {
+            condition,
+            output: vec![],
+            name: None,
+            description: None,
+            tags: vec![],
+            explanation: None,
+            template: None,
+            author: None,
+            created: None,
+            updated: None,
+            enabled: true,
+        }
+    }
+
+    /// A builder starting from this rule, for changing part of it.
+    pub fn to_builder(&self) -> RuleBuilder {
+        RuleBuilder {
+            condition: self.condition.clone(),
+            output: self.output.clone(),
+            name: self.name.clone(),
+            description: self.description.clone(),
+            tags: self.tags.clone(),
+            explanation: self.explanation.clone(),
+            template: self.template.clone(),
+            author: self.author.clone(),
+            created: self.created.clone(),
+            updated: self.updated.clone(),
+            enabled: self.enabled,
+        }
+    }
+
+    pub fn condition(&self) -> &Condition {
+        &self.condition
+    }
+
+    pub fn output(&self) -> &[String] {
+        &self.output
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Why the rule holds, for people reading an explanation of a
+    /// conclusion.
+    pub fn explanation(&self) -> Option<&str> {
+        self.explanation.as_deref()
+    }
+
+    /// The sentence explaining a firing of the rule, `{condition}` and
+    /// `{output}` in it are replaced, see [`crate::explain::Explainer`].
+    pub fn template(&self) -> Option<&str> {
+        self.template.as_deref()
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    /// When the rule was first stored, as `YYYY-MM-DD HH:MM:SS` in UTC.
+    pub fn created(&self) -> Option<&str> {
+        self.created.as_deref()
+    }
+
+    pub fn updated(&self) -> Option<&str> {
+        self.updated.as_deref()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "IF {} THEN {}", self.condition, self.output.join(", "))
+    }
+}
+
+/// Builder for [`Rule`], see [`Rule::builder`].
+#[derive(Debug, Clone)]
+pub struct RuleBuilder {
+    condition: Condition,
+    output: Vec<String>,
+    name: Option<String>,
+    description: Option<String>,
+    tags: Vec<String>,
+    explanation: Option<String>,
+    template: Option<String>,
+    author: Option<String>,
+    created: Option<String>,
+    updated: Option<String>,
+    enabled: bool,
+}
+
+impl RuleBuilder {
+    pub fn condition(mut self, condition: Condition) -> Self {
+        self.condition = condition;
+        self
+    }
+
+    /// Removes the outputs added so far.
+    pub fn clear_outputs(mut self) -> Self {
+        self.output.clear();
+        self
+    }
+
+    /// Adds a fact the rule derives.
+    pub fn output(mut self, fact: impl Into<String>) -> Self {
+        self.output.push(fact.into());
+        self
+    }
+
+    pub fn outputs<I>(mut self, facts: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.output.extend(facts.into_iter().map(Into::into));
+        self
+    }
+
+    /// The name has to be a valid fact name so it can be used as an
+    /// identifier by the text formats.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Tags follow the same rules as names.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        let tag = tag.into();
+        if !self.tags.contains(&tag) {
+            self.tags.push(tag);
+        }
+        self
+    }
+
+    /// Removes the tags added so far.
+    pub fn clear_tags(mut self) -> Self {
+        self.tags.clear();
+        self
+    }
+
+    pub fn explanation(mut self, explanation: impl Into<String>) -> Self {
+        self.explanation = Some(explanation.into());
+        self
+    }
+
+    pub fn template(mut self, template: impl Into<String>) -> Self {
+        self.template = Some(template.into());
+        self
+    }
+
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    pub fn created(mut self, timestamp: impl Into<String>) -> Self {
+        self.created = Some(timestamp.into());
+        self
+    }
+
+    pub fn updated(mut self, timestamp: impl Into<String>) -> Self {
+        self.updated = Some(timestamp.into());
+        self
+    }
+
+    /// Rules are enabled unless disabled here.
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    pub fn build(self) -> Result<Rule> {
+        if self.output.is_empty() {
+            return Err(Error::InvalidRule("rule has no output".to_string()));
+        }
+        if let Some(fact) = self.output.iter().find(|fact| !is_fact_name(fact)) {
+            return Err(Error::InvalidRule(format!("invalid output fact {:?}", fact)));
+        }
+        if let Some(name) = self.name.as_deref().filter(|name| !is_fact_name(name)) {
+            return Err(Error::InvalidRule(format!("invalid rule name {:?}", name)));
+        }
+        if let Some(tag) = self.tags.iter().find(|tag| !is_fact_name(tag)) {
+            return Err(Error::InvalidRule(format!("invalid tag {:?}", tag)));
+        }
+        Ok(Rule {
+            condition: self.condition,
+            output: self.output,
+            name: self.name,
+            description: self.description,
+            tags: self.tags,
+            explanation: self.explanation,
+            template: self.template,
+            author: self.author,
+            created: self.created,
+            updated: self.updated,
+            enabled: self.enabled,
+        })
+    }
 }
 
 impl TryFrom<(i64, String, String)> for Rule {
-    type Error = anyhow::Error;
+    type Error = Error;
 
-    fn try_from(value: (i64, String, String)) -> Result<Self, Self::Error> {
+    fn try_from(value: (i64, String, String)) -> Result<Self> {
         let (_, condition, output) = value;
-        let condition = Condition::from_str(&condition).map_err(|s| anyhow!(s))?;
+        let condition = Condition::from_str(&condition)?;
         let output = output.split(",").map(|s| s.to_string()).collect();
-        Ok(Rule { condition, output })
+        Ok(Rule::plain(condition, output))
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+/// A boolean expression over facts, written as `a & (b | !c)`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Condition {
     Fact(String),
     And(Box<Condition>, Box<Condition>),
@@ -107,11 +628,12 @@ impl Condition {
         Condition::Or(Box::new(self), Box::new(rhs))
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn not(self) -> Condition {
         Condition::Not(Box::new(self))
     }
 
-    pub fn matches(&self, facts: &Vec<String>) -> bool {
+    pub fn matches(&self, facts: &[String]) -> bool {
         match self {
             Condition::Fact(obj) => facts.contains(obj),
             Condition::And(lhs, rhs) => lhs.matches(facts) && rhs.matches(facts),
@@ -122,11 +644,21 @@ impl Condition {
 }
 
 impl FromStr for Condition {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut chars = s.chars().peekable();
-        parse_or(&mut chars).map_err(|e| anyhow!(e))
+        let position = |chars: Peekable<Chars>| s.chars().count() - chars.count();
+        let condition = match parse_or(&mut chars) {
+            Ok(condition) => condition,
+            Err(e) => return Err(ParseError::new(e, position(chars))),
+        };
+        skip_whitespace(&mut chars);
+        if let Some(&c) = chars.peek() {
+            let message = format!("Unexpected character '{}'", c);
+            return Err(ParseError::new(message, position(chars)));
+        }
+        Ok(condition)
     }
 }
 
@@ -241,6 +773,10 @@ fn skip_whitespace(chars: &mut Peekable<Chars>) {
 mod tests {
     use super::*;
 
+    fn rule(condition: Condition, output: Vec<String>) -> Rule {
+        Rule::plain(condition, output)
+    }
+
     #[test]
     fn test_parse_fact() {
         let rule = "fact".parse::<Condition>().unwrap();
@@ -293,6 +829,8 @@ mod tests {
         assert!("& fact".parse::<Condition>().is_err());
         assert!("fact1 &".parse::<Condition>().is_err());
         assert!("(fact1 & fact2".parse::<Condition>().is_err());
+        assert!("fact1 fact2".parse::<Condition>().is_err());
+        assert!("fact1)".parse::<Condition>().is_err());
     }
 
     #[test]
@@ -336,25 +874,28 @@ mod tests {
         facts.remember("fact1");
 
         let rules = vec![
-            Rule {
-                condition: Condition::fact("fact1"),
-                output: vec!["fact2".to_string()],
-            },
-            Rule {
-                condition: Condition::fact("fact2"),
-                output: vec!["fact3".to_string()],
-            },
-            Rule {
-                condition: Condition::fact("fact4"),
-                output: vec!["fact5".to_string()],
-            },
+            rule(Condition::fact("fact1"), vec!["fact2".to_string()]),
+            rule(Condition::fact("fact2"), vec!["fact3".to_string()]),
+            rule(Condition::fact("fact4"), vec!["fact5".to_string()]),
         ];
 
         let result = facts.step_forward(&rules);
         assert!(result);
-        assert!(facts.recall("fact2"));
-        assert!(facts.recall("fact3"));
-        assert!(!facts.recall("fact5"));
+        assert!(facts.contains("fact2"));
+        assert!(facts.contains("fact3"));
+        assert!(!facts.contains("fact5"));
+    }
+
+    #[test]
+    fn test_step_forward_skips_disabled() {
+        let mut facts = Facts::new(&[]);
+        facts.remember("fact1");
+
+        let disabled = Rule::builder(Condition::fact("fact1")).output("fact2").enabled(false).build().unwrap();
+        assert!(!facts.step_forward(std::slice::from_ref(&disabled)));
+        assert!(!facts.contains("fact2"));
+        assert!(facts.step_forward(&[disabled.to_builder().enabled(true).build().unwrap()]));
+        assert!(facts.contains("fact2"));
     }
 
     #[test]
@@ -362,14 +903,11 @@ mod tests {
         let mut facts = Facts::new(&[]);
         facts.remember("fact1");
 
-        let rules = vec![Rule {
-            condition: Condition::fact("fact4"),
-            output: vec!["fact5".to_string()],
-        }];
+        let rules = vec![rule(Condition::fact("fact4"), vec!["fact5".to_string()])];
 
         let result = facts.step_forward(&rules);
         assert!(!result);
-        assert!(!facts.recall("fact5"));
+        assert!(!facts.contains("fact5"));
     }
 
     #[test]
@@ -378,41 +916,120 @@ mod tests {
         facts.remember("fact1");
 
         let rules = vec![
-            Rule {
-                condition: Condition::fact("fact2"),
-                output: vec!["fact3".to_string()],
-            },
-            Rule {
-                condition: Condition::fact("fact1"),
-                output: vec!["fact2".to_string()],
-            },
-            Rule {
-                condition: Condition::fact("fact3"),
-                output: vec!["fact4".to_string()],
-            },
+            rule(Condition::fact("fact2"), vec!["fact3".to_string()]),
+            rule(Condition::fact("fact1"), vec!["fact2".to_string()]),
+            rule(Condition::fact("fact3"), vec!["fact4".to_string()]),
         ];
 
         let step = facts.deduce(&rules);
 
-        assert!(facts.recall("fact2"));
-        assert!(facts.recall("fact3"));
-        assert!(facts.recall("fact4"));
+        assert!(facts.contains("fact2"));
+        assert!(facts.contains("fact3"));
+        assert!(facts.contains("fact4"));
         assert_eq!(step, 2)
     }
 
+    #[test]
+    fn test_deduce_with_options() {
+        let rules = vec![
+            Rule::new("b", "e").unwrap(),
+            Rule::new("a", "b").unwrap(),
+            Rule::new("a & c", "d").unwrap(),
+        ];
+        let mut facts = Facts::new(&["a", "c"]);
+        let options = Options {
+            max_cycles: Some(1),
+            strategy: Strategy::All,
+        };
+        assert_eq!(facts.deduce_with(&rules, &options), 1);
+        assert_eq!(facts.derived(), ["b", "d"]);
+
+        let mut facts = Facts::new(&["a", "c"]);
+        let options = Options {
+            max_cycles: None,
+            strategy: Strategy::Specificity,
+        };
+        assert_eq!(facts.deduce_with(&rules, &options), 3);
+        assert_eq!(facts.derived(), ["d", "b", "e"]);
+
+        let mut facts = Facts::new(&["a", "c"]);
+        facts.deduce_with(&rules, &Options { strategy: Strategy::First, ..options });
+        assert_eq!(facts.derived(), ["b", "e", "d"]);
+    }
+
+    #[test]
+    fn test_step_forward_records_firings() {
+        let mut facts = Facts::new(&["fact1"]);
+
+        let rules = vec![
+            rule(Condition::fact("fact4"), vec!["fact5".to_string()]),
+            rule(Condition::fact("fact1"), vec!["fact2".to_string(), "fact3".to_string()]),
+        ];
+
+        facts.deduce(&rules);
+
+        assert_eq!(facts.input(), ["fact1"]);
+        assert_eq!(facts.derived(), ["fact2", "fact3"]);
+        assert_eq!(
+            facts.fired(),
+            [Firing {
+                cycle: 1,
+                rule: 1,
+                added: vec!["fact2".to_string(), "fact3".to_string()],
+            }]
+        );
+    }
+
     #[test]
     fn test_deduce_no_changes() {
         let mut facts = Facts::new(&[]);
         facts.remember("fact1");
 
-        let rules = vec![Rule {
-            condition: Condition::fact("fact5"),
-            output: vec!["fact6".to_string()],
-        }];
+        let rules = vec![rule(Condition::fact("fact5"), vec!["fact6".to_string()])];
 
         facts.deduce(&rules);
 
-        assert!(!facts.recall("fact6"));
+        assert!(!facts.contains("fact6"));
+    }
+
+    #[test]
+    fn test_rule_serde() {
+        let rule = Rule::new("a & !b", "c,d").unwrap();
+        let json = serde_json::to_string(&rule).unwrap();
+        assert_eq!(
+            json,
+            r#"{"condition":"(a & !b)","ast":{"and":[{"fact":"a"},{"not":{"fact":"b"}}]},"output":["c","d"]}"#
+        );
+        let parsed: Rule = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.condition, rule.condition);
+
+        let without_ast: Rule = serde_json::from_str(r#"{"condition":"a","output":["b"]}"#).unwrap();
+        assert_eq!(without_ast.condition, Condition::fact("a"));
+        assert!(serde_json::from_str::<Rule>(r#"{"condition":"a","ast":{"fact":"b"},"output":["b"]}"#).is_err());
+        assert!(serde_json::from_str::<Rule>(r#"{"condition":"a","output":["not a fact"]}"#).is_err());
+    }
+
+    #[test]
+    fn test_rule_builder() {
+        let rule = Rule::builder(Condition::fact("a"))
+            .outputs(["b", "c"])
+            .description("Both")
+            .tag("pairs")
+            .build()
+            .unwrap();
+        assert_eq!(rule.output(), ["b", "c"]);
+        assert_eq!(rule.description(), Some("Both"));
+        assert_eq!(rule.tags(), ["pairs"]);
+        assert!(Rule::builder(Condition::fact("a")).output("b").tag("no spaces").build().is_err());
+        assert!(Rule::builder(Condition::fact("a")).build().is_err());
+        assert!(Rule::builder(Condition::fact("a")).output("b").name("no spaces").build().is_err());
+
+        let edited = rule.to_builder().condition(Condition::fact("x")).clear_outputs().output("y").build().unwrap();
+        assert_eq!(edited.to_string(), "IF x THEN y");
+        assert_eq!(edited.description(), Some("Both"));
+
+        let e = "a & (b | )".parse::<Condition>().unwrap_err();
+        assert_eq!(e.position(), 9);
     }
 
     #[test]
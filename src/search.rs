@@ -0,0 +1,157 @@
+//! Finding rules by the facts they use, their outputs, tags or the text of
+//! their condition.
+
+use crate::rule::{Condition, Rule};
+use regex::Regex;
+use std::ops::Range;
+
+/// Every filter that is set has to match.
+///
+/// ```
+/// use expert::search::Query;
+/// use expert::Rule;
+///
+/// let rules = [Rule::new("fur & !feathers", "mammal")?, Rule::new("feathers", "bird")?];
+/// let query = Query {
+///     facts: vec!["feathers".to_string()],
+///     negation: Some(true),
+///     ..Default::default()
+/// };
+/// assert_eq!(query.find(&rules), [0]);
+/// # Ok::<(), expert::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    /// Facts the condition has to test.
+    pub facts: Vec<String>,
+    /// Facts the rule has to derive.
+    pub outputs: Vec<String>,
+    /// Whether the condition has to use `!`, or must not.
+    pub negation: Option<bool>,
+    pub tags: Vec<String>,
+    /// Searched in the condition as `Display` writes it.
+    pub pattern: Option<Regex>,
+}
+
+impl Query {
+    pub fn is_empty(&self) -> bool {
+        self.facts.is_empty()
+            && self.outputs.is_empty()
+            && self.negation.is_none()
+            && self.tags.is_empty()
+            && self.pattern.is_none()
+    }
+
+    pub fn matches(&self, rule: &Rule) -> bool {
+        let mut tested = vec![];
+        condition_facts(rule.condition(), &mut tested);
+        self.facts.iter().all(|fact| tested.contains(&fact.as_str()))
+            && self.outputs.iter().all(|fact| rule.output().contains(fact))
+            && self
+                .negation
+                .is_none_or(|negation| uses_negation(rule.condition()) == negation)
+            && self.tags.iter().all(|tag| rule.tags().contains(tag))
+            && self
+                .pattern
+                .as_ref()
+                .is_none_or(|pattern| pattern.is_match(&rule.condition().to_string()))
+    }
+
+    /// Indices of the matching rules.
+    pub fn find(&self, rules: &[Rule]) -> Vec<usize> {
+        (0..rules.len()).filter(|&idx| self.matches(&rules[idx])).collect()
+    }
+
+    /// The byte ranges of a rendered condition that the query matched, in
+    /// order and without overlaps, for highlighting.
+    pub fn condition_matches(&self, condition: &str) -> Vec<Range<usize>> {
+        let mut ranges = vec![];
+        for fact in &self.facts {
+            ranges.extend(fact_positions(condition, fact));
+        }
+        if self.negation == Some(true) {
+            ranges.extend(condition.match_indices('!').map(|(start, _)| start..start + 1));
+        }
+        if let Some(pattern) = &self.pattern {
+            ranges.extend(pattern.find_iter(condition).map(|m| m.range()).filter(|range| !range.is_empty()));
+        }
+        ranges.sort_by_key(|range| range.start);
+        let mut merged: Vec<Range<usize>> = vec![];
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        merged
+    }
+}
+
+fn condition_facts<'a>(condition: &'a Condition, facts: &mut Vec<&'a str>) {
+    match condition {
+        Condition::Fact(fact) => facts.push(fact),
+        Condition::And(lhs, rhs) | Condition::Or(lhs, rhs) => {
+            condition_facts(lhs, facts);
+            condition_facts(rhs, facts);
+        }
+        Condition::Not(inner) => condition_facts(inner, facts),
+    }
+}
+
+fn uses_negation(condition: &Condition) -> bool {
+    match condition {
+        Condition::Fact(_) => false,
+        Condition::And(lhs, rhs) | Condition::Or(lhs, rhs) => uses_negation(lhs) || uses_negation(rhs),
+        Condition::Not(_) => true,
+    }
+}
+
+/// Where `fact` appears as a whole fact name in `text`.
+fn fact_positions<'a>(text: &'a str, fact: &'a str) -> impl Iterator<Item = Range<usize>> + 'a {
+    let is_name = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(fact).filter_map(move |(start, _)| {
+        let end = start + fact.len();
+        let before = text[..start].chars().next_back().is_some_and(is_name);
+        let after = text[end..].chars().next().is_some_and(is_name);
+        (!before && !after).then_some(start..end)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query() {
+        let rules = [
+            Rule::new("fur & !feathers", "mammal").unwrap(),
+            Rule::builder("feathers".parse().unwrap()).output("bird").tag("birds").build().unwrap(),
+            Rule::new("furry_tail", "squirrel").unwrap(),
+        ];
+        let query = Query {
+            facts: vec!["fur".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(query.find(&rules), [0]);
+        assert_eq!(query.condition_matches("(fur & !feathers) | fur"), [1..4, 20..23]);
+
+        let query = Query {
+            negation: Some(false),
+            tags: vec!["birds".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(query.find(&rules), [1]);
+
+        let query = Query {
+            negation: Some(true),
+            pattern: Some(Regex::new("fea.*s").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(query.find(&rules), [0]);
+        // The `!` and the regex match merge into one range
+        let ranges = query.condition_matches("(fur & !feathers)");
+        assert_eq!((ranges.len(), &ranges[0]), (1, &(7..16)));
+        assert!(Query::default().is_empty());
+        assert_eq!(Query::default().find(&rules).len(), 3);
+    }
+}
@@ -0,0 +1,355 @@
+use crate::explain::Labels;
+use crate::rule::Rule;
+use crate::store::memory::{KbState, State};
+use crate::store::{HistoryEntry, Run, RuleStore};
+use crate::testing::TestCase;
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FileFormat {
+    Toml,
+    Json,
+}
+
+impl FileFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            FileFormat::Toml => "toml",
+            FileFormat::Json => "json",
+        }
+    }
+
+    fn serialize<T: Serialize>(self, value: &T) -> Result<String> {
+        Ok(match self {
+            FileFormat::Toml => toml::to_string_pretty(value)?,
+            FileFormat::Json => serde_json::to_string_pretty(value)?,
+        })
+    }
+
+    fn deserialize<T: DeserializeOwned>(self, s: &str) -> Result<T> {
+        Ok(match self {
+            FileFormat::Toml => toml::from_str(s)?,
+            FileFormat::Json => serde_json::from_str(s)?,
+        })
+    }
+}
+
+/// A directory of plain text files that diffs nicely under version control.
+///
+/// Every knowledge base lives in `kb/<name>.toml` (or `.json`) together with
+/// its tests and scenarios, runs and history are kept in `runs.toml` and
+/// `history.toml` next to it. Files are re-read on every access so edits
+/// made outside the program are picked up, and only the files a change
+/// touches are written back.
+#[derive(Debug)]
+pub struct FileStore {
+    root: PathBuf,
+    format: FileFormat,
+    lock: Mutex<()>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct RunsFile {
+    #[serde(default)]
+    runs: Vec<Run>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct HistoryFile {
+    #[serde(default)]
+    history: Vec<HistoryEntry>,
+}
+
+impl FileStore {
+    pub fn open(root: impl AsRef<Path>, format: FileFormat) -> Result<Self> {
+        let store = FileStore {
+            root: root.as_ref().to_path_buf(),
+            format,
+            lock: Mutex::new(()),
+        };
+        fs::create_dir_all(store.kb_dir())
+            .map_err(|e| Error::from(e).in_file(&store.root))?;
+        let state = store.read()?;
+        if state.kbs.is_empty() {
+            store.write(&state, &State::new())?;
+        }
+        Ok(store)
+    }
+
+    fn kb_dir(&self) -> PathBuf {
+        self.root.join("kb")
+    }
+
+    fn kb_file(&self, kb: &str) -> PathBuf {
+        self.kb_dir().join(format!("{}.{}", kb, self.format.extension()))
+    }
+
+    fn file(&self, name: &str) -> PathBuf {
+        self.root
+            .join(format!("{}.{}", name, self.format.extension()))
+    }
+
+    fn read_file<T: DeserializeOwned + Default>(&self, path: &Path) -> Result<T> {
+        if !path.exists() {
+            return Ok(T::default());
+        }
+        let content = fs::read_to_string(path)?;
+        self.format
+            .deserialize(&content)
+            .map_err(|e| e.in_file(path))
+    }
+
+    /// Writes `value` unless it comes out the same as `before`, so files
+    /// nothing changed keep the formatting they were given by hand.
+    fn write_file<T: Serialize>(&self, path: &Path, before: Option<&T>, value: &T) -> Result<()> {
+        let content = self.format.serialize(value)?;
+        if before.map(|before| self.format.serialize(before)).transpose()?.as_deref() == Some(content.as_str()) {
+            return Ok(());
+        }
+        fs::write(path, content).map_err(|e| Error::from(e).in_file(path))
+    }
+
+    fn read(&self) -> Result<State> {
+        let mut state = State::default();
+        for entry in fs::read_dir(self.kb_dir())? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(self.format.extension()) {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let kb: KbState = self.read_file(&path)?;
+            state.kbs.insert(name.to_string(), kb);
+        }
+        state.runs = self.read_file::<RunsFile>(&self.file("runs"))?.runs;
+        state.history = self.read_file::<HistoryFile>(&self.file("history"))?.history;
+        Ok(state)
+    }
+
+    /// Writes what differs between `before`, as read from the directory,
+    /// and `state`.
+    fn write(&self, before: &State, state: &State) -> Result<()> {
+        for name in before.kbs.keys().filter(|name| !state.kbs.contains_key(*name)) {
+            let path = self.kb_file(name);
+            fs::remove_file(&path).map_err(|e| Error::from(e).in_file(&path))?;
+        }
+        for (name, kb) in &state.kbs {
+            self.write_file(&self.kb_file(name), before.kbs.get(name), kb)?;
+        }
+        let runs = |state: &State| RunsFile {
+            runs: state.runs.clone(),
+        };
+        self.write_file(&self.file("runs"), Some(&runs(before)), &runs(state))?;
+        let history = |state: &State| HistoryFile {
+            history: state.history.clone(),
+        };
+        self.write_file(&self.file("history"), Some(&history(before)), &history(state))
+    }
+
+    /// Reads the directory, applies `f` and writes back what it changed.
+    fn with<T>(&self, f: impl FnOnce(&mut State) -> Result<T>) -> Result<T> {
+        let _guard = self.lock.lock().expect("file store lock poisoned");
+        let before = self.read()?;
+        let mut state = before.clone();
+        let result = f(&mut state)?;
+        self.write(&before, &state)?;
+        Ok(result)
+    }
+
+    fn peek<T>(&self, f: impl FnOnce(&mut State) -> Result<T>) -> Result<T> {
+        let _guard = self.lock.lock().expect("file store lock poisoned");
+        f(&mut self.read()?)
+    }
+}
+
+#[async_trait]
+impl RuleStore for FileStore {
+    async fn list_kbs(&self) -> Result<Vec<(String, i64)>> {
+        self.peek(|state| Ok(state.list_kbs()))
+    }
+
+    async fn kb_exists(&self, kb: &str) -> Result<bool> {
+        self.peek(|state| Ok(state.kbs.contains_key(kb)))
+    }
+
+    async fn create_kb(&self, kb: &str) -> Result<()> {
+        self.with(|state| state.create_kb(kb))
+    }
+
+    async fn copy_kb(&self, from: &str, to: &str) -> Result<()> {
+        self.with(|state| state.copy_kb(from, to))
+    }
+
+    async fn delete_kb(&self, kb: &str) -> Result<()> {
+        self.with(|state| state.delete_kb(kb))
+    }
+
+    async fn load_rules_raw(&self, kb: &str) -> Result<Vec<(i64, String, String)>> {
+        self.peek(|state| state.load_rules_raw(kb))
+    }
+
+    async fn load_rules_with_ids(&self, kb: &str) -> Result<Vec<(i64, Rule)>> {
+        self.peek(|state| state.load_rules_with_ids(kb))
+    }
+
+    async fn save_rules(&self, kb: &str, rules: &[Rule]) -> Result<()> {
+        self.with(|state| state.save_rules(kb, rules))
+    }
+
+    async fn insert_rule(&self, kb: &str, rule: &Rule) -> Result<i64> {
+        self.with(|state| state.insert_rule(kb, rule))
+    }
+
+    async fn delete_rule(&self, kb: &str, id: i64) -> Result<()> {
+        self.with(|state| state.delete_rule(kb, id))
+    }
+
+    async fn history(&self, kb: &str) -> Result<Vec<HistoryEntry>> {
+        self.peek(|state| Ok(state.history(kb)))
+    }
+
+    async fn reset(&self) -> Result<()> {
+        self.with(|state| {
+            *state = State::new();
+            Ok(())
+        })
+    }
+
+    async fn save_tests(&self, kb: &str, tests: &[TestCase]) -> Result<()> {
+        self.with(|state| state.save_tests(kb, tests))
+    }
+
+    async fn load_tests(&self, kb: &str) -> Result<Vec<TestCase>> {
+        self.peek(|state| state.load_tests(kb))
+    }
+
+    async fn save_labels(&self, kb: &str, labels: &Labels) -> Result<()> {
+        self.with(|state| state.save_labels(kb, labels))
+    }
+
+    async fn load_labels(&self, kb: &str) -> Result<Labels> {
+        self.peek(|state| state.load_labels(kb))
+    }
+
+    async fn save_scenario(&self, kb: &str, name: &str, facts: &[String]) -> Result<()> {
+        self.with(|state| state.save_scenario(kb, name, facts))
+    }
+
+    async fn load_scenario(&self, kb: &str, name: &str) -> Result<Option<Vec<String>>> {
+        self.peek(|state| state.load_scenario(kb, name))
+    }
+
+    async fn list_scenarios(&self, kb: &str) -> Result<Vec<(String, Vec<String>)>> {
+        self.peek(|state| state.list_scenarios(kb))
+    }
+
+    async fn insert_run(&self, run: &Run) -> Result<i64> {
+        self.with(|state| Ok(state.insert_run(run)))
+    }
+
+    async fn list_runs(&self, kb: &str) -> Result<Vec<Run>> {
+        self.peek(|state| Ok(state.list_runs(kb)))
+    }
+
+    async fn load_run(&self, id: i64) -> Result<Option<Run>> {
+        self.peek(|state| Ok(state.load_run(id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rule::Facts;
+    use crate::store::tests::conformance;
+    use crate::store::DEFAULT_KB;
+
+    #[tokio::test]
+    async fn test_conformance() {
+        for format in [FileFormat::Toml, FileFormat::Json] {
+            let root = std::env::temp_dir().join(format!(
+                "expert-file-store-conformance-{}-{}",
+                std::process::id(),
+                format.extension()
+            ));
+            let _ = fs::remove_dir_all(&root);
+            conformance(&FileStore::open(&root, format).unwrap()).await;
+            fs::remove_dir_all(&root).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_store_round_trip() {
+        for format in [FileFormat::Toml, FileFormat::Json] {
+            let root = std::env::temp_dir().join(format!(
+                "expert-file-store-{}-{}",
+                std::process::id(),
+                format.extension()
+            ));
+            let _ = fs::remove_dir_all(&root);
+
+            let rules = vec![Rule::new("a & !b", "c,d").unwrap()];
+            let store = FileStore::open(&root, format).unwrap();
+            store.create_kb("animals").await.unwrap();
+            store.save_rules("animals", &rules).await.unwrap();
+            let mut facts = Facts::new(&["a"]);
+            let cycles = facts.deduce(&rules);
+            let run = Run::record("animals", None, &facts, cycles, &rules);
+            store.insert_run(&run).await.unwrap();
+
+            let store = FileStore::open(&root, format).unwrap();
+            assert!(store.kb_exists(DEFAULT_KB).await.unwrap());
+            let loaded = store.load_rules("animals").await.unwrap();
+            assert_eq!(loaded[0].condition, rules[0].condition);
+            assert_eq!(loaded[0].output, rules[0].output);
+            assert_eq!(store.list_runs("animals").await.unwrap()[0].derived, ["c", "d"]);
+
+            store.delete_kb("animals").await.unwrap();
+            assert!(!root.join("kb").join(format!("animals.{}", format.extension())).exists());
+            fs::remove_dir_all(&root).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_store_writes_changes_only() {
+        let root = std::env::temp_dir().join(format!("expert-file-store-changes-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let store = FileStore::open(&root, FileFormat::Toml).unwrap();
+        store.create_kb("animals").await.unwrap();
+        let default = root.join("kb").join("default.toml");
+        let edited = "# Kept by hand\nrules = []\n";
+        fs::write(&default, edited).unwrap();
+
+        let rules = [Rule::new("a", "b").unwrap()];
+        store.save_rules("animals", &rules).await.unwrap();
+        let mut facts = Facts::new(&["a"]);
+        let cycles = facts.deduce(&rules);
+        let history = fs::read_to_string(root.join("history.toml")).unwrap();
+        store.insert_run(&Run::record("animals", None, &facts, cycles, &rules)).await.unwrap();
+
+        assert_eq!(fs::read_to_string(&default).unwrap(), edited);
+        assert_eq!(fs::read_to_string(root.join("history.toml")).unwrap(), history);
+        assert_eq!(store.list_runs("animals").await.unwrap().len(), 1);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_file_store_kb_names() {
+        let root = std::env::temp_dir().join(format!("expert-file-store-names-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let store = FileStore::open(root.join("store"), FileFormat::Toml).unwrap();
+        assert!(store.create_kb("../../escaped").await.is_err());
+        assert!(store.copy_kb(DEFAULT_KB, "../escaped").await.is_err());
+        assert!(!root.join("escaped.toml").exists());
+        assert!(!root.join("store").join("escaped.toml").exists());
+        store.create_kb("my-kb_2").await.unwrap();
+        assert!(store.kb_exists("my-kb_2").await.unwrap());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
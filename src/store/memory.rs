@@ -0,0 +1,403 @@
+use crate::explain::Labels;
+use crate::rule::Rule;
+use crate::store::{check_kb_name, now, HistoryEntry, Run, RuleStore, DEFAULT_KB};
+use crate::testing::TestCase;
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Keeps everything in memory, nothing survives the process. Mostly useful
+/// for tests and for trying things out without touching a file.
+#[derive(Debug)]
+pub struct MemoryStore {
+    state: Mutex<State>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        MemoryStore {
+            state: Mutex::new(State::new()),
+        }
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut State) -> Result<T>) -> Result<T> {
+        let mut state = self.state.lock().expect("memory store lock poisoned");
+        f(&mut state)
+    }
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        MemoryStore::new()
+    }
+}
+
+/// The whole content of a store, shared by the memory and file backends.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct State {
+    pub(crate) kbs: BTreeMap<String, KbState>,
+    pub(crate) runs: Vec<Run>,
+    pub(crate) history: Vec<HistoryEntry>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub(crate) struct KbState {
+    #[serde(default)]
+    pub(crate) rules: Vec<StoredRule>,
+    #[serde(default)]
+    pub(crate) scenarios: BTreeMap<String, Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) tests: Vec<TestCase>,
+    #[serde(default, skip_serializing_if = "Labels::is_empty")]
+    pub(crate) labels: Labels,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct StoredRule {
+    pub(crate) id: i64,
+    pub(crate) condition: String,
+    pub(crate) output: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) explanation: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) template: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) updated: Option<String>,
+    #[serde(default = "crate::rule::enabled", skip_serializing_if = "crate::rule::is_enabled")]
+    pub(crate) enabled: bool,
+}
+
+impl StoredRule {
+    fn new(id: i64, rule: &Rule) -> Self {
+        let created = rule.created.clone().unwrap_or_else(now);
+        StoredRule {
+            id,
+            condition: rule.condition.to_string(),
+            output: rule.output.clone(),
+            name: rule.name.clone(),
+            description: rule.description.clone(),
+            explanation: rule.explanation.clone(),
+            template: rule.template.clone(),
+            tags: rule.tags.clone(),
+            author: rule.author.clone(),
+            updated: Some(rule.updated.clone().unwrap_or_else(|| created.clone())),
+            created: Some(created),
+            enabled: rule.enabled,
+        }
+    }
+
+    fn rule(&self) -> Result<Rule> {
+        Ok(Rule {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            explanation: self.explanation.clone(),
+            template: self.template.clone(),
+            tags: self.tags.clone(),
+            author: self.author.clone(),
+            created: self.created.clone(),
+            updated: self.updated.clone(),
+            enabled: self.enabled,
+            ..Rule::plain(self.condition.parse()?, self.output.clone())
+        })
+    }
+}
+
+impl State {
+    pub(crate) fn new() -> Self {
+        let mut state = State::default();
+        state.kbs.insert(DEFAULT_KB.to_string(), KbState::default());
+        state
+    }
+
+    fn kb(&mut self, kb: &str) -> Result<&mut KbState> {
+        match self.kbs.get_mut(kb) {
+            Some(state) => Ok(state),
+            None => Err(Error::NotFound(format!("knowledge base {}", kb))),
+        }
+    }
+
+    fn next_rule_id(&self) -> i64 {
+        self.kbs
+            .values()
+            .flat_map(|kb| kb.rules.iter().map(|rule| rule.id))
+            .max()
+            .unwrap_or(0)
+            + 1
+    }
+
+    fn log(&mut self, kb: &str, action: &str, detail: &str) {
+        let id = self.history.last().map(|entry| entry.id).unwrap_or(0) + 1;
+        self.history.push(HistoryEntry {
+            id,
+            kb: kb.to_string(),
+            action: action.to_string(),
+            detail: detail.to_string(),
+            created_at: now(),
+        });
+    }
+
+    pub(crate) fn list_kbs(&self) -> Vec<(String, i64)> {
+        self.kbs
+            .iter()
+            .map(|(name, kb)| (name.clone(), kb.rules.len() as i64))
+            .collect()
+    }
+
+    pub(crate) fn create_kb(&mut self, kb: &str) -> Result<()> {
+        check_kb_name(kb)?;
+        if self.kbs.contains_key(kb) {
+            return Err(Error::AlreadyExists(format!("knowledge base {}", kb)));
+        }
+        self.kbs.insert(kb.to_string(), KbState::default());
+        self.log(kb, "create", "");
+        Ok(())
+    }
+
+    pub(crate) fn copy_kb(&mut self, from: &str, to: &str) -> Result<()> {
+        let source = self.kb(from)?.clone();
+        self.create_kb(to)?;
+        let first = self.next_rule_id();
+        let target = self.kb(to)?;
+        for (rule, id) in source.rules.into_iter().zip(first..) {
+            target.rules.push(StoredRule { id, ..rule });
+        }
+        target.scenarios = source.scenarios;
+        target.tests = source.tests;
+        target.labels = source.labels;
+        self.log(to, "copy", &format!("from {}", from));
+        Ok(())
+    }
+
+    pub(crate) fn delete_kb(&mut self, kb: &str) -> Result<()> {
+        if self.kbs.remove(kb).is_none() {
+            return Err(Error::NotFound(format!("knowledge base {}", kb)));
+        }
+        self.runs.retain(|run| run.kb != kb);
+        self.history.retain(|entry| entry.kb != kb);
+        Ok(())
+    }
+
+    pub(crate) fn load_rules_raw(&mut self, kb: &str) -> Result<Vec<(i64, String, String)>> {
+        Ok(self
+            .kb(kb)?
+            .rules
+            .iter()
+            .map(|rule| (rule.id, rule.condition.clone(), rule.output.join(",")))
+            .collect())
+    }
+
+    pub(crate) fn load_rules_with_ids(&mut self, kb: &str) -> Result<Vec<(i64, Rule)>> {
+        self.kb(kb)?
+            .rules
+            .iter()
+            .map(|rule| Ok((rule.id, rule.rule()?)))
+            .collect()
+    }
+
+    pub(crate) fn save_rules(&mut self, kb: &str, rules: &[Rule]) -> Result<()> {
+        let first = self.next_rule_id();
+        self.kb(kb)?.rules = rules
+            .iter()
+            .zip(first..)
+            .map(|(rule, id)| StoredRule::new(id, rule))
+            .collect();
+        self.log(kb, "save", &format!("{} rules", rules.len()));
+        Ok(())
+    }
+
+    pub(crate) fn insert_rule(&mut self, kb: &str, rule: &Rule) -> Result<i64> {
+        let id = self.next_rule_id();
+        self.kb(kb)?.rules.push(StoredRule::new(id, rule));
+        let detail = format!("#{} {} => {}", id, rule.condition, rule.output.join(","));
+        self.log(kb, "insert", &detail);
+        Ok(id)
+    }
+
+    pub(crate) fn delete_rule(&mut self, kb: &str, id: i64) -> Result<()> {
+        let rules = &mut self.kb(kb)?.rules;
+        let Some(idx) = rules.iter().position(|rule| rule.id == id) else {
+            return Err(Error::NotFound(format!("rule #{} in knowledge base {}", id, kb)));
+        };
+        let rule = rules.remove(idx);
+        let detail = format!("#{} {} => {}", id, rule.condition, rule.output.join(","));
+        self.log(kb, "delete", &detail);
+        Ok(())
+    }
+
+    pub(crate) fn history(&self, kb: &str) -> Vec<HistoryEntry> {
+        self.history
+            .iter()
+            .filter(|entry| entry.kb == kb)
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn save_tests(&mut self, kb: &str, tests: &[TestCase]) -> Result<()> {
+        self.kb(kb)?.tests = tests.to_vec();
+        Ok(())
+    }
+
+    pub(crate) fn load_tests(&mut self, kb: &str) -> Result<Vec<TestCase>> {
+        Ok(self.kb(kb)?.tests.clone())
+    }
+
+    pub(crate) fn save_labels(&mut self, kb: &str, labels: &Labels) -> Result<()> {
+        self.kb(kb)?.labels = labels.clone();
+        Ok(())
+    }
+
+    pub(crate) fn load_labels(&mut self, kb: &str) -> Result<Labels> {
+        Ok(self.kb(kb)?.labels.clone())
+    }
+
+    pub(crate) fn save_scenario(&mut self, kb: &str, name: &str, facts: &[String]) -> Result<()> {
+        self.kb(kb)?
+            .scenarios
+            .insert(name.to_string(), facts.to_vec());
+        Ok(())
+    }
+
+    pub(crate) fn load_scenario(&mut self, kb: &str, name: &str) -> Result<Option<Vec<String>>> {
+        Ok(self.kb(kb)?.scenarios.get(name).cloned())
+    }
+
+    pub(crate) fn list_scenarios(&mut self, kb: &str) -> Result<Vec<(String, Vec<String>)>> {
+        Ok(self
+            .kb(kb)?
+            .scenarios
+            .iter()
+            .map(|(name, facts)| (name.clone(), facts.clone()))
+            .collect())
+    }
+
+    pub(crate) fn insert_run(&mut self, run: &Run) -> i64 {
+        let id = self.runs.last().map(|run| run.id).unwrap_or(0) + 1;
+        self.runs.push(Run { id, ..run.clone() });
+        id
+    }
+
+    pub(crate) fn list_runs(&self, kb: &str) -> Vec<Run> {
+        self.runs.iter().filter(|run| run.kb == kb).cloned().collect()
+    }
+
+    pub(crate) fn load_run(&self, id: i64) -> Option<Run> {
+        self.runs.iter().find(|run| run.id == id).cloned()
+    }
+}
+
+#[async_trait]
+impl RuleStore for MemoryStore {
+    async fn list_kbs(&self) -> Result<Vec<(String, i64)>> {
+        self.with(|state| Ok(state.list_kbs()))
+    }
+
+    async fn kb_exists(&self, kb: &str) -> Result<bool> {
+        self.with(|state| Ok(state.kbs.contains_key(kb)))
+    }
+
+    async fn create_kb(&self, kb: &str) -> Result<()> {
+        self.with(|state| state.create_kb(kb))
+    }
+
+    async fn copy_kb(&self, from: &str, to: &str) -> Result<()> {
+        self.with(|state| state.copy_kb(from, to))
+    }
+
+    async fn delete_kb(&self, kb: &str) -> Result<()> {
+        self.with(|state| state.delete_kb(kb))
+    }
+
+    async fn load_rules_raw(&self, kb: &str) -> Result<Vec<(i64, String, String)>> {
+        self.with(|state| state.load_rules_raw(kb))
+    }
+
+    async fn load_rules_with_ids(&self, kb: &str) -> Result<Vec<(i64, Rule)>> {
+        self.with(|state| state.load_rules_with_ids(kb))
+    }
+
+    async fn save_rules(&self, kb: &str, rules: &[Rule]) -> Result<()> {
+        self.with(|state| state.save_rules(kb, rules))
+    }
+
+    async fn insert_rule(&self, kb: &str, rule: &Rule) -> Result<i64> {
+        self.with(|state| state.insert_rule(kb, rule))
+    }
+
+    async fn delete_rule(&self, kb: &str, id: i64) -> Result<()> {
+        self.with(|state| state.delete_rule(kb, id))
+    }
+
+    async fn history(&self, kb: &str) -> Result<Vec<HistoryEntry>> {
+        self.with(|state| Ok(state.history(kb)))
+    }
+
+    async fn reset(&self) -> Result<()> {
+        self.with(|state| {
+            *state = State::new();
+            Ok(())
+        })
+    }
+
+    async fn save_tests(&self, kb: &str, tests: &[TestCase]) -> Result<()> {
+        self.with(|state| state.save_tests(kb, tests))
+    }
+
+    async fn load_tests(&self, kb: &str) -> Result<Vec<TestCase>> {
+        self.with(|state| state.load_tests(kb))
+    }
+
+    async fn save_labels(&self, kb: &str, labels: &Labels) -> Result<()> {
+        self.with(|state| state.save_labels(kb, labels))
+    }
+
+    async fn load_labels(&self, kb: &str) -> Result<Labels> {
+        self.with(|state| state.load_labels(kb))
+    }
+
+    async fn save_scenario(&self, kb: &str, name: &str, facts: &[String]) -> Result<()> {
+        self.with(|state| state.save_scenario(kb, name, facts))
+    }
+
+    async fn load_scenario(&self, kb: &str, name: &str) -> Result<Option<Vec<String>>> {
+        self.with(|state| state.load_scenario(kb, name))
+    }
+
+    async fn list_scenarios(&self, kb: &str) -> Result<Vec<(String, Vec<String>)>> {
+        self.with(|state| state.list_scenarios(kb))
+    }
+
+    async fn insert_run(&self, run: &Run) -> Result<i64> {
+        self.with(|state| Ok(state.insert_run(run)))
+    }
+
+    async fn list_runs(&self, kb: &str) -> Result<Vec<Run>> {
+        self.with(|state| Ok(state.list_runs(kb)))
+    }
+
+    async fn load_run(&self, id: i64) -> Result<Option<Run>> {
+        self.with(|state| Ok(state.load_run(id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::tests::conformance;
+
+    #[tokio::test]
+    async fn test_conformance() {
+        conformance(&MemoryStore::new()).await;
+    }
+}
@@ -0,0 +1,397 @@
+//! Persistence of knowledge bases behind the [`RuleStore`] trait.
+
+use crate::error::{Error, Result};
+use crate::explain::Labels;
+use crate::rule::{Facts, Rule};
+use crate::search::Query;
+use crate::testing::TestCase;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::path::Path;
+
+mod file;
+mod memory;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+pub use file::{FileFormat, FileStore};
+pub use memory::MemoryStore;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+
+pub const DEFAULT_KB: &str = "default";
+
+/// Letters, digits, `_` and `-`, so a knowledge base name is also a safe
+/// file name.
+pub fn is_kb_name(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+pub(crate) fn check_kb_name(kb: &str) -> Result<()> {
+    if !is_kb_name(kb) {
+        return Err(Error::Store(format!(
+            "invalid knowledge base name {:?}, use letters, digits, _ and -",
+            kb
+        )));
+    }
+    Ok(())
+}
+
+/// A recorded inference run.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Run {
+    pub id: i64,
+    pub kb: String,
+    pub scenario: Option<String>,
+    pub input: Vec<String>,
+    pub derived: Vec<String>,
+    pub cycles: i64,
+    pub fired: Vec<FiredRule>,
+    pub created_at: String,
+}
+
+/// A rule firing as stored with a run, the condition is kept as text so the
+/// run still reads correctly after the rule base changes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FiredRule {
+    pub cycle: i64,
+    pub rule: i64,
+    pub condition: String,
+    pub added: Vec<String>,
+}
+
+impl Run {
+    /// Builds a run from finished deduction, the id is assigned by the store.
+    pub fn record(
+        kb: &str,
+        scenario: Option<&str>,
+        facts: &Facts,
+        cycles: usize,
+        rules: &[Rule],
+    ) -> Run {
+        let fired = facts
+            .fired()
+            .iter()
+            .map(|firing| FiredRule {
+                cycle: firing.cycle as i64,
+                rule: firing.rule as i64,
+                condition: rules
+                    .get(firing.rule)
+                    .map(|rule| rule.condition.to_string())
+                    .unwrap_or_default(),
+                added: firing.added.clone(),
+            })
+            .collect();
+        Run {
+            id: 0,
+            kb: kb.to_string(),
+            scenario: scenario.map(|s| s.to_string()),
+            input: facts.input().to_vec(),
+            derived: facts.derived().to_vec(),
+            cycles: cycles as i64,
+            fired,
+            created_at: now(),
+        }
+    }
+}
+
+/// A change made to a knowledge base through a store.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub kb: String,
+    pub action: String,
+    pub detail: String,
+    pub created_at: String,
+}
+
+/// Persistence for knowledge bases, their rules, tests, scenarios and runs.
+///
+/// Rules are addressed by `(id, condition, output)` rows, ids are assigned
+/// by the store and stay stable until the rule is deleted. Stores keep the
+/// metadata of rules and fill in the created and updated timestamps of
+/// rules that have none.
+///
+/// Reading or writing the rules, tests, labels or scenarios of a knowledge
+/// base that doesn't exist fails with [`Error::NotFound`], the history and
+/// runs of one are just empty.
+#[async_trait]
+pub trait RuleStore: Debug + Send + Sync {
+    async fn list_kbs(&self) -> Result<Vec<(String, i64)>>;
+
+    async fn kb_exists(&self, kb: &str) -> Result<bool>;
+
+    async fn create_kb(&self, kb: &str) -> Result<()>;
+
+    /// Copies the rules, tests and scenarios of `from` into the new knowledge
+    /// base `to`.
+    async fn copy_kb(&self, from: &str, to: &str) -> Result<()>;
+
+    async fn delete_kb(&self, kb: &str) -> Result<()>;
+
+    async fn load_rules_raw(&self, kb: &str) -> Result<Vec<(i64, String, String)>>;
+
+    /// The rules of the knowledge base with their metadata and ids.
+    async fn load_rules_with_ids(&self, kb: &str) -> Result<Vec<(i64, Rule)>>;
+
+    async fn load_rules(&self, kb: &str) -> Result<Vec<Rule>> {
+        Ok(self
+            .load_rules_with_ids(kb)
+            .await?
+            .into_iter()
+            .map(|(_, rule)| rule)
+            .collect())
+    }
+
+    /// The rules of the knowledge base matching `query`, with their ids.
+    async fn find_rules(&self, kb: &str, query: &Query) -> Result<Vec<(i64, Rule)>> {
+        let mut rules = self.load_rules_with_ids(kb).await?;
+        rules.retain(|(_, rule)| query.matches(rule));
+        Ok(rules)
+    }
+
+    /// Replaces every rule of the knowledge base with `rules`.
+    async fn save_rules(&self, kb: &str, rules: &[Rule]) -> Result<()>;
+
+    async fn insert_rule(&self, kb: &str, rule: &Rule) -> Result<i64>;
+
+    async fn delete_rule(&self, kb: &str, id: i64) -> Result<()>;
+
+    async fn history(&self, kb: &str) -> Result<Vec<HistoryEntry>>;
+
+    /// Drops everything and starts over with an empty default knowledge base.
+    async fn reset(&self) -> Result<()>;
+
+    /// Replaces every test case of the knowledge base with `tests`.
+    async fn save_tests(&self, kb: &str, tests: &[TestCase]) -> Result<()>;
+
+    async fn load_tests(&self, kb: &str) -> Result<Vec<TestCase>>;
+
+    /// Replaces every fact label of the knowledge base with `labels`.
+    async fn save_labels(&self, kb: &str, labels: &Labels) -> Result<()>;
+
+    async fn load_labels(&self, kb: &str) -> Result<Labels>;
+
+    async fn save_scenario(&self, kb: &str, name: &str, facts: &[String]) -> Result<()>;
+
+    async fn load_scenario(&self, kb: &str, name: &str) -> Result<Option<Vec<String>>>;
+
+    async fn list_scenarios(&self, kb: &str) -> Result<Vec<(String, Vec<String>)>>;
+
+    async fn insert_run(&self, run: &Run) -> Result<i64>;
+
+    async fn list_runs(&self, kb: &str) -> Result<Vec<Run>>;
+
+    async fn load_run(&self, id: i64) -> Result<Option<Run>>;
+
+    /// The most recent run of a scenario, used to compare conclusions after
+    /// the rules were edited.
+    async fn last_scenario_run(&self, kb: &str, scenario: &str) -> Result<Option<Run>> {
+        let runs = self.list_runs(kb).await?;
+        Ok(runs
+            .into_iter()
+            .rev()
+            .find(|run| run.scenario.as_deref() == Some(scenario)))
+    }
+}
+
+/// Opens the store a `db connect` argument points to.
+///
+/// `memory:` gives an in-memory store, `toml:` and `json:` urls or an
+/// existing directory use a directory of plain text files. `sqlite:` urls
+/// and any other path use SQLite, as every path did before there were
+/// other backends.
+pub async fn open(url: &str) -> Result<Box<dyn RuleStore>> {
+    if url == "memory:" || url.starts_with("memory://") {
+        return Ok(Box::new(MemoryStore::new()));
+    }
+    if url.starts_with("sqlite:") {
+        return open_sqlite(url).await;
+    }
+    if let Some(path) = url.strip_prefix("toml://").or(url.strip_prefix("toml:")) {
+        return Ok(Box::new(FileStore::open(path, FileFormat::Toml)?));
+    }
+    if let Some(path) = url.strip_prefix("json://").or(url.strip_prefix("json:")) {
+        return Ok(Box::new(FileStore::open(path, FileFormat::Json)?));
+    }
+
+    if Path::new(url).is_dir() {
+        return Ok(Box::new(FileStore::open(url, FileFormat::Toml)?));
+    }
+    open_sqlite(&format!("sqlite://{}", url)).await
+}
+
+#[cfg(feature = "sqlite")]
+async fn open_sqlite(url: &str) -> Result<Box<dyn RuleStore>> {
+    Ok(Box::new(SqliteStore::new(url).await?))
+}
+
+#[cfg(not(feature = "sqlite"))]
+async fn open_sqlite(url: &str) -> Result<Box<dyn RuleStore>> {
+    Err(Error::Store(format!("{} needs the sqlite feature", url)))
+}
+
+/// Timestamps are stored as UTC text in the same shape SQLite uses.
+pub fn now() -> String {
+    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    fn rule(condition: &str, output: &str) -> Rule {
+        Rule::new(condition, output).unwrap()
+    }
+
+    /// What every backend has to do, each runs it on a new, empty store.
+    pub(crate) async fn conformance(store: &dyn RuleStore) {
+        rules(store).await;
+        knowledge_bases(store).await;
+        runs(store).await;
+    }
+
+    async fn rules(store: &dyn RuleStore) {
+        store
+            .save_rules(DEFAULT_KB, &[rule("a & b", "c"), rule("c", "d")])
+            .await
+            .unwrap();
+        let first = store.load_rules_raw(DEFAULT_KB).await.unwrap()[0].0;
+        let id = store.insert_rule(DEFAULT_KB, &rule("!d", "e")).await.unwrap();
+        store.delete_rule(DEFAULT_KB, first).await.unwrap();
+        assert!(store.delete_rule(DEFAULT_KB, first).await.is_err());
+
+        let rules = store.load_rules_raw(DEFAULT_KB).await.unwrap();
+        assert_eq!(
+            rules.iter().map(|(_, condition, output)| (condition.as_str(), output.as_str())).collect::<Vec<_>>(),
+            [("c", "d"), ("!d", "e")]
+        );
+        assert_eq!(rules[1].0, id);
+        let query = Query {
+            facts: vec!["d".to_string()],
+            ..Default::default()
+        };
+        let found = store.find_rules(DEFAULT_KB, &query).await.unwrap();
+        assert_eq!(found.iter().map(|(id, _)| *id).collect::<Vec<_>>(), [id]);
+
+        let actions = store
+            .history(DEFAULT_KB)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.action)
+            .collect::<Vec<_>>();
+        assert_eq!(actions, ["save", "insert", "delete"]);
+
+        let annotated = rule("a", "b")
+            .to_builder()
+            .description("a gives b")
+            .author("ann")
+            .tag("x")
+            .enabled(false)
+            .build()
+            .unwrap();
+        store.save_rules(DEFAULT_KB, &[annotated]).await.unwrap();
+        let loaded = store.load_rules(DEFAULT_KB).await.unwrap();
+        assert_eq!(loaded[0].description(), Some("a gives b"));
+        assert_eq!((loaded[0].author(), loaded[0].tags()), (Some("ann"), &["x".to_string()][..]));
+        assert!(!loaded[0].is_enabled());
+        assert!(loaded[0].created().is_some());
+        assert_eq!(loaded[0].created(), loaded[0].updated());
+    }
+
+    async fn knowledge_bases(store: &dyn RuleStore) {
+        store.create_kb("animals").await.unwrap();
+        assert!(store.create_kb("animals").await.is_err());
+        for name in ["", "..", "../escaped", "a/b", "a\\b", "a.b"] {
+            assert!(store.create_kb(name).await.is_err(), "{:?}", name);
+        }
+        assert!(store.copy_kb("animals", "../escaped").await.is_err());
+
+        store.save_rules("animals", &[rule("fur", "mammal")]).await.unwrap();
+        store.save_scenario("animals", "cat", &["fur".to_string()]).await.unwrap();
+        let test = TestCase {
+            name: "cat".to_string(),
+            given: vec!["fur".to_string()],
+            expect: vec!["mammal".to_string()],
+            expect_not: vec![],
+        };
+        store.save_tests("animals", std::slice::from_ref(&test)).await.unwrap();
+        let labels = Labels::from([("fur".to_string(), "有毛发".to_string())]);
+        store.save_labels("animals", &labels).await.unwrap();
+        store.copy_kb("animals", "plants").await.unwrap();
+        assert!(store.copy_kb("animals", "plants").await.is_err());
+        assert!(store.copy_kb("missing", "trees").await.is_err());
+        store.delete_kb("animals").await.unwrap();
+        assert!(store.delete_kb("animals").await.is_err());
+
+        assert_eq!(
+            store.list_kbs().await.unwrap(),
+            [(DEFAULT_KB.to_string(), 1), ("plants".to_string(), 1)]
+        );
+        assert!(store.kb_exists("plants").await.unwrap());
+        assert!(!store.kb_exists("animals").await.unwrap());
+        assert_eq!(store.load_rules("plants").await.unwrap()[0].output(), ["mammal"]);
+        assert_eq!(
+            store.list_scenarios("plants").await.unwrap(),
+            [("cat".to_string(), vec!["fur".to_string()])]
+        );
+        assert_eq!(store.load_scenario("plants", "dog").await.unwrap(), None);
+        assert_eq!(store.load_tests("plants").await.unwrap(), [test]);
+        assert_eq!(store.load_labels("plants").await.unwrap(), labels);
+        let history = store.history("plants").await.unwrap();
+        assert_eq!(history.last().map(|entry| entry.action.as_str()), Some("copy"));
+
+        // A knowledge base that doesn't exist has no contents to read or write
+        assert!(store.load_rules_raw("animals").await.is_err());
+        assert!(store.load_rules("animals").await.is_err());
+        assert!(store.find_rules("animals", &Query::default()).await.is_err());
+        assert!(store.save_rules("animals", &[rule("a", "b")]).await.is_err());
+        assert!(store.insert_rule("animals", &rule("a", "b")).await.is_err());
+        assert!(store.delete_rule("animals", 1).await.is_err());
+        assert!(store.save_tests("animals", &[]).await.is_err());
+        assert!(store.load_tests("animals").await.is_err());
+        assert!(store.save_labels("animals", &Labels::new()).await.is_err());
+        assert!(store.load_labels("animals").await.is_err());
+        assert!(store.save_scenario("animals", "cat", &[]).await.is_err());
+        assert!(store.load_scenario("animals", "cat").await.is_err());
+        assert!(store.list_scenarios("animals").await.is_err());
+        assert!(store.history("animals").await.unwrap().is_empty());
+        assert!(store.list_runs("animals").await.unwrap().is_empty());
+        assert!(!store.list_kbs().await.unwrap().iter().any(|(kb, _)| kb == "animals"));
+    }
+
+    #[tokio::test]
+    async fn test_open() {
+        assert!(open("memory:").await.unwrap().kb_exists(DEFAULT_KB).await.unwrap());
+        // A path without a known extension is an SQLite file
+        let path = std::env::temp_dir().join(format!("expert-open-{}", std::process::id()));
+        let store = open(path.to_str().unwrap()).await.unwrap();
+        store.save_rules(DEFAULT_KB, &[rule("a", "b")]).await.unwrap();
+        drop(store);
+        assert!(std::fs::read(&path).unwrap().starts_with(b"SQLite format 3"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    async fn runs(store: &dyn RuleStore) {
+        let rules = [rule("a", "b"), rule("b", "c")];
+        let mut facts = Facts::new(&["a"]);
+        let cycles = facts.deduce(&rules);
+
+        let run = Run::record(DEFAULT_KB, Some("s"), &facts, cycles, &rules);
+        store.insert_run(&run).await.unwrap();
+        let id = store.insert_run(&run).await.unwrap();
+        let run = Run::record(DEFAULT_KB, None, &facts, cycles, &rules);
+        store.insert_run(&run).await.unwrap();
+
+        let last = store.last_scenario_run(DEFAULT_KB, "s").await.unwrap().unwrap();
+        assert_eq!(last.id, id);
+        assert_eq!(last.derived, ["b", "c"]);
+        assert_eq!(last.fired[0].condition, "a");
+        assert_eq!(store.load_run(id).await.unwrap().unwrap().fired.len(), 2);
+        assert_eq!(store.list_runs(DEFAULT_KB).await.unwrap().len(), 3);
+        assert!(store.list_runs("plants").await.unwrap().is_empty());
+        assert!(store.load_run(id + 10).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,858 @@
+use crate::explain::Labels;
+use crate::rule::Rule;
+use crate::search::Query;
+use crate::store::{check_kb_name, now, FiredRule, HistoryEntry, Run, RuleStore, DEFAULT_KB};
+use crate::testing::TestCase;
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use sqlx::migrate::MigrateDatabase;
+use sqlx::{Row, Sqlite, SqliteExecutor, SqlitePool};
+
+#[derive(Clone, Debug)]
+pub struct SqliteStore {
+    conn: SqlitePool,
+}
+
+impl SqliteStore {
+    pub async fn new(db_url: &str) -> Result<Self> {
+        if !Sqlite::database_exists(db_url).await.unwrap_or(false) {
+            Sqlite::create_database(db_url).await?
+        }
+        let conn = SqlitePool::connect(db_url).await?;
+        let db = Self { conn };
+        db.init().await?;
+        db.migrate().await?;
+        Ok(db)
+    }
+
+    async fn init(&self) -> Result<()> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS knowledge_bases (
+                name TEXT PRIMARY KEY
+            )",
+        )
+        .execute(&self.conn)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS rules (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kb TEXT NOT NULL DEFAULT 'default',
+                condition TEXT NOT NULL,
+                output TEXT NOT NULL,
+                name TEXT,
+                description TEXT,
+                explanation TEXT,
+                template TEXT,
+                tags TEXT NOT NULL DEFAULT '',
+                author TEXT,
+                created_at TEXT,
+                updated_at TEXT,
+                enabled INTEGER NOT NULL DEFAULT 1
+            )",
+        )
+        .execute(&self.conn)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS scenarios (
+                kb TEXT NOT NULL,
+                name TEXT NOT NULL,
+                facts TEXT NOT NULL,
+                PRIMARY KEY (kb, name)
+            )",
+        )
+        .execute(&self.conn)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS tests (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kb TEXT NOT NULL,
+                name TEXT NOT NULL,
+                given TEXT NOT NULL,
+                expect TEXT NOT NULL,
+                expect_not TEXT NOT NULL
+            )",
+        )
+        .execute(&self.conn)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS fact_labels (
+                kb TEXT NOT NULL,
+                fact TEXT NOT NULL,
+                label TEXT NOT NULL,
+                PRIMARY KEY (kb, fact)
+            )",
+        )
+        .execute(&self.conn)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS runs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kb TEXT NOT NULL,
+                scenario TEXT,
+                input TEXT NOT NULL,
+                derived TEXT NOT NULL,
+                cycles INTEGER NOT NULL,
+                fired TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )",
+        )
+        .execute(&self.conn)
+        .await?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kb TEXT NOT NULL,
+                action TEXT NOT NULL,
+                detail TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )",
+        )
+        .execute(&self.conn)
+        .await?;
+
+        self.init_search().await?;
+
+        sqlx::query("INSERT OR IGNORE INTO knowledge_bases (name) VALUES (?)")
+            .bind(DEFAULT_KB)
+            .execute(&self.conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// A full text index of the rules kept up to date by triggers, fact
+    /// names are indexed whole. Databases created before it get it filled
+    /// from their rules.
+    async fn init_search(&self) -> Result<()> {
+        let exists = sqlx::query("SELECT 1 FROM sqlite_master WHERE name = 'rules_fts'")
+            .fetch_optional(&self.conn)
+            .await?
+            .is_some();
+        if exists {
+            return Ok(());
+        }
+        sqlx::query(
+            "CREATE VIRTUAL TABLE rules_fts USING fts5(
+                condition, output,
+                content = 'rules', content_rowid = 'id',
+                tokenize = \"unicode61 tokenchars '_'\"
+            )",
+        )
+        .execute(&self.conn)
+        .await?;
+        sqlx::query(
+            "CREATE TRIGGER IF NOT EXISTS rules_fts_insert AFTER INSERT ON rules BEGIN
+                INSERT INTO rules_fts (rowid, condition, output)
+                VALUES (new.id, new.condition, new.output);
+            END",
+        )
+        .execute(&self.conn)
+        .await?;
+        sqlx::query(
+            "CREATE TRIGGER IF NOT EXISTS rules_fts_delete AFTER DELETE ON rules BEGIN
+                INSERT INTO rules_fts (rules_fts, rowid, condition, output)
+                VALUES ('delete', old.id, old.condition, old.output);
+            END",
+        )
+        .execute(&self.conn)
+        .await?;
+        sqlx::query("INSERT INTO rules_fts (rules_fts) VALUES ('rebuild')")
+            .execute(&self.conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Adds the columns older databases lack, rules from before knowledge
+    /// bases end up in the default one.
+    async fn migrate(&self) -> Result<()> {
+        let columns = sqlx::query("SELECT name FROM pragma_table_info('rules')")
+            .fetch_all(&self.conn)
+            .await?;
+        let columns = columns
+            .iter()
+            .map(|row| row.get::<String, _>(0))
+            .collect::<Vec<_>>();
+        for (column, definition) in [
+            ("kb", "TEXT NOT NULL DEFAULT 'default'"),
+            ("name", "TEXT"),
+            ("description", "TEXT"),
+            ("explanation", "TEXT"),
+            ("template", "TEXT"),
+            ("tags", "TEXT NOT NULL DEFAULT ''"),
+            ("author", "TEXT"),
+            ("created_at", "TEXT"),
+            ("updated_at", "TEXT"),
+            ("enabled", "INTEGER NOT NULL DEFAULT 1"),
+        ] {
+            if !columns.iter().any(|name| name == column) {
+                sqlx::query(&format!("ALTER TABLE rules ADD COLUMN {} {}", column, definition))
+                    .execute(&self.conn)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fails with [`Error::NotFound`] unless the knowledge base exists.
+async fn check_kb(conn: impl SqliteExecutor<'_>, kb: &str) -> Result<()> {
+    let exists = sqlx::query("SELECT 1 FROM knowledge_bases WHERE name = ?")
+        .bind(kb)
+        .fetch_optional(conn)
+        .await?
+        .is_some();
+    if !exists {
+        return Err(Error::NotFound(format!("knowledge base {}", kb)));
+    }
+    Ok(())
+}
+
+async fn insert_kb(conn: impl SqliteExecutor<'_>, kb: &str) -> Result<()> {
+    sqlx::query("INSERT INTO knowledge_bases (name) VALUES (?)")
+        .bind(kb)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+async fn log(conn: impl SqliteExecutor<'_>, kb: &str, action: &str, detail: &str) -> Result<()> {
+    sqlx::query("INSERT INTO history (kb, action, detail, created_at) VALUES (?, ?, ?, ?)")
+        .bind(kb)
+        .bind(action)
+        .bind(detail)
+        .bind(now())
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+async fn clear_rules(conn: impl SqliteExecutor<'_>, kb: &str) -> Result<()> {
+    sqlx::query("DELETE FROM rules WHERE kb = ?")
+        .bind(kb)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+async fn insert_rule_row(conn: impl SqliteExecutor<'_>, kb: &str, rule: &Rule) -> Result<i64> {
+    let condition = rule.condition.to_string();
+    let output = rule.output.join(",");
+    let created = rule.created.clone().unwrap_or_else(now);
+    let updated = rule.updated.clone().unwrap_or_else(|| created.clone());
+    let result = sqlx::query(
+        "INSERT INTO rules (kb, condition, output, name, description, explanation,
+             template, tags, author, created_at, updated_at, enabled)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(kb)
+    .bind(condition)
+    .bind(output)
+    .bind(&rule.name)
+    .bind(&rule.description)
+    .bind(&rule.explanation)
+    .bind(&rule.template)
+    .bind(rule.tags.join(" "))
+    .bind(&rule.author)
+    .bind(created)
+    .bind(updated)
+    .bind(rule.enabled)
+    .execute(conn)
+    .await?;
+    Ok(result.last_insert_rowid())
+}
+
+#[async_trait]
+impl RuleStore for SqliteStore {
+    async fn list_kbs(&self) -> Result<Vec<(String, i64)>> {
+        let rows = sqlx::query(
+            "SELECT k.name, COUNT(r.id) FROM knowledge_bases k
+             LEFT JOIN rules r ON r.kb = k.name
+             GROUP BY k.name ORDER BY k.name",
+        )
+        .fetch_all(&self.conn)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.get(0), row.get(1)))
+            .collect())
+    }
+
+    async fn kb_exists(&self, kb: &str) -> Result<bool> {
+        let row = sqlx::query("SELECT COUNT(*) FROM knowledge_bases WHERE name = ?")
+            .bind(kb)
+            .fetch_one(&self.conn)
+            .await?;
+        Ok(row.get::<i64, _>(0) > 0)
+    }
+
+    async fn create_kb(&self, kb: &str) -> Result<()> {
+        check_kb_name(kb)?;
+        if self.kb_exists(kb).await? {
+            return Err(Error::AlreadyExists(format!("knowledge base {}", kb)));
+        }
+        let mut tx = self.conn.begin().await?;
+        insert_kb(&mut *tx, kb).await?;
+        log(&mut *tx, kb, "create", "").await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn copy_kb(&self, from: &str, to: &str) -> Result<()> {
+        if !self.kb_exists(from).await? {
+            return Err(Error::NotFound(format!("knowledge base {}", from)));
+        }
+        check_kb_name(to)?;
+        if self.kb_exists(to).await? {
+            return Err(Error::AlreadyExists(format!("knowledge base {}", to)));
+        }
+        let mut tx = self.conn.begin().await?;
+        insert_kb(&mut *tx, to).await?;
+        log(&mut *tx, to, "create", "").await?;
+        sqlx::query(
+            "INSERT INTO rules (kb, condition, output, name, description, explanation,
+                 template, tags, author, created_at, updated_at, enabled)
+             SELECT ?, condition, output, name, description, explanation,
+                 template, tags, author, created_at, updated_at, enabled
+             FROM rules WHERE kb = ? ORDER BY id",
+        )
+        .bind(to)
+        .bind(from)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "INSERT INTO scenarios (kb, name, facts)
+             SELECT ?, name, facts FROM scenarios WHERE kb = ?",
+        )
+        .bind(to)
+        .bind(from)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "INSERT INTO tests (kb, name, given, expect, expect_not)
+             SELECT ?, name, given, expect, expect_not FROM tests WHERE kb = ? ORDER BY id",
+        )
+        .bind(to)
+        .bind(from)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "INSERT INTO fact_labels (kb, fact, label)
+             SELECT ?, fact, label FROM fact_labels WHERE kb = ?",
+        )
+        .bind(to)
+        .bind(from)
+        .execute(&mut *tx)
+        .await?;
+        log(&mut *tx, to, "copy", &format!("from {}", from)).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete_kb(&self, kb: &str) -> Result<()> {
+        if !self.kb_exists(kb).await? {
+            return Err(Error::NotFound(format!("knowledge base {}", kb)));
+        }
+        let mut tx = self.conn.begin().await?;
+        clear_rules(&mut *tx, kb).await?;
+        for table in ["tests", "fact_labels", "scenarios", "runs", "history"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE kb = ?", table))
+                .bind(kb)
+                .execute(&mut *tx)
+                .await?;
+        }
+        sqlx::query("DELETE FROM knowledge_bases WHERE name = ?")
+            .bind(kb)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn load_rules_raw(&self, kb: &str) -> Result<Vec<(i64, String, String)>> {
+        check_kb(&self.conn, kb).await?;
+        let rows = sqlx::query("SELECT id, condition, output FROM rules WHERE kb = ? ORDER BY id")
+            .bind(kb)
+            .fetch_all(&self.conn)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.get(0), row.get(1), row.get(2)))
+            .collect())
+    }
+
+    async fn load_rules_with_ids(&self, kb: &str) -> Result<Vec<(i64, Rule)>> {
+        check_kb(&self.conn, kb).await?;
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM rules r WHERE r.kb = ? ORDER BY r.id",
+            RULE_COLUMNS
+        ))
+        .bind(kb)
+        .fetch_all(&self.conn)
+        .await?;
+        rows.iter().map(rule_from_row).collect()
+    }
+
+    async fn save_rules(&self, kb: &str, rules: &[Rule]) -> Result<()> {
+        // All of it or nothing, a failed insert leaves the old rules
+        let mut tx = self.conn.begin().await?;
+        check_kb(&mut *tx, kb).await?;
+        clear_rules(&mut *tx, kb).await?;
+        for rule in rules {
+            insert_rule_row(&mut *tx, kb, rule).await?;
+        }
+        log(&mut *tx, kb, "save", &format!("{} rules", rules.len())).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Narrows the rules down with the full text index on the facts and
+    /// outputs asked for, the query decides on what is left.
+    async fn find_rules(&self, kb: &str, query: &Query) -> Result<Vec<(i64, Rule)>> {
+        check_kb(&self.conn, kb).await?;
+        let terms = query
+            .facts
+            .iter()
+            .map(|fact| format!("condition : \"{}\"", fact))
+            .chain(query.outputs.iter().map(|fact| format!("output : \"{}\"", fact)))
+            .collect::<Vec<_>>();
+        let mut rules = if terms.is_empty() {
+            self.load_rules_with_ids(kb).await?
+        } else {
+            sqlx::query(&format!(
+                "SELECT {} FROM rules r
+                 JOIN rules_fts f ON f.rowid = r.id
+                 WHERE r.kb = ? AND rules_fts MATCH ? ORDER BY r.id",
+                RULE_COLUMNS
+            ))
+            .bind(kb)
+            .bind(terms.join(" AND "))
+            .fetch_all(&self.conn)
+            .await?
+            .iter()
+            .map(rule_from_row)
+            .collect::<Result<Vec<_>>>()?
+        };
+        rules.retain(|(_, rule)| query.matches(rule));
+        Ok(rules)
+    }
+
+    async fn insert_rule(&self, kb: &str, rule: &Rule) -> Result<i64> {
+        let mut tx = self.conn.begin().await?;
+        check_kb(&mut *tx, kb).await?;
+        let id = insert_rule_row(&mut *tx, kb, rule).await?;
+        let detail = format!("#{} {} => {}", id, rule.condition, rule.output.join(","));
+        log(&mut *tx, kb, "insert", &detail).await?;
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    async fn delete_rule(&self, kb: &str, id: i64) -> Result<()> {
+        check_kb(&self.conn, kb).await?;
+        let row = sqlx::query("SELECT condition, output FROM rules WHERE kb = ? AND id = ?")
+            .bind(kb)
+            .bind(id)
+            .fetch_optional(&self.conn)
+            .await?;
+        let Some(row) = row else {
+            return Err(Error::NotFound(format!("rule #{} in knowledge base {}", id, kb)));
+        };
+        let mut tx = self.conn.begin().await?;
+        sqlx::query("DELETE FROM rules WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        let detail = format!(
+            "#{} {} => {}",
+            id,
+            row.get::<String, _>(0),
+            row.get::<String, _>(1)
+        );
+        log(&mut *tx, kb, "delete", &detail).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn history(&self, kb: &str) -> Result<Vec<HistoryEntry>> {
+        let rows = sqlx::query(
+            "SELECT id, kb, action, detail, created_at FROM history WHERE kb = ? ORDER BY id",
+        )
+        .bind(kb)
+        .fetch_all(&self.conn)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| HistoryEntry {
+                id: row.get(0),
+                kb: row.get(1),
+                action: row.get(2),
+                detail: row.get(3),
+                created_at: row.get(4),
+            })
+            .collect())
+    }
+
+    async fn reset(&self) -> Result<()> {
+        for table in [
+            "rules_fts",
+            "rules",
+            "knowledge_bases",
+            "tests",
+            "fact_labels",
+            "scenarios",
+            "runs",
+            "history",
+        ] {
+            sqlx::query(&format!("DROP TABLE IF EXISTS {}", table))
+                .execute(&self.conn)
+                .await?;
+        }
+
+        self.init().await
+    }
+
+    async fn save_tests(&self, kb: &str, tests: &[TestCase]) -> Result<()> {
+        let mut tx = self.conn.begin().await?;
+        check_kb(&mut *tx, kb).await?;
+        sqlx::query("DELETE FROM tests WHERE kb = ?")
+            .bind(kb)
+            .execute(&mut *tx)
+            .await?;
+        for test in tests {
+            sqlx::query(
+                "INSERT INTO tests (kb, name, given, expect, expect_not) VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(kb)
+            .bind(&test.name)
+            .bind(test.given.join(" "))
+            .bind(test.expect.join(" "))
+            .bind(test.expect_not.join(" "))
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn load_tests(&self, kb: &str) -> Result<Vec<TestCase>> {
+        check_kb(&self.conn, kb).await?;
+        let rows = sqlx::query(
+            "SELECT name, given, expect, expect_not FROM tests WHERE kb = ? ORDER BY id",
+        )
+        .bind(kb)
+        .fetch_all(&self.conn)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| TestCase {
+                name: row.get(0),
+                given: split_facts(row.get(1)),
+                expect: split_facts(row.get(2)),
+                expect_not: split_facts(row.get(3)),
+            })
+            .collect())
+    }
+
+    async fn save_labels(&self, kb: &str, labels: &Labels) -> Result<()> {
+        let mut tx = self.conn.begin().await?;
+        check_kb(&mut *tx, kb).await?;
+        sqlx::query("DELETE FROM fact_labels WHERE kb = ?")
+            .bind(kb)
+            .execute(&mut *tx)
+            .await?;
+        for (fact, label) in labels {
+            sqlx::query("INSERT INTO fact_labels (kb, fact, label) VALUES (?, ?, ?)")
+                .bind(kb)
+                .bind(fact)
+                .bind(label)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn load_labels(&self, kb: &str) -> Result<Labels> {
+        check_kb(&self.conn, kb).await?;
+        let rows = sqlx::query("SELECT fact, label FROM fact_labels WHERE kb = ?")
+            .bind(kb)
+            .fetch_all(&self.conn)
+            .await?;
+        Ok(rows.into_iter().map(|row| (row.get(0), row.get(1))).collect())
+    }
+
+    async fn save_scenario(&self, kb: &str, name: &str, facts: &[String]) -> Result<()> {
+        check_kb(&self.conn, kb).await?;
+        sqlx::query("INSERT OR REPLACE INTO scenarios (kb, name, facts) VALUES (?, ?, ?)")
+            .bind(kb)
+            .bind(name)
+            .bind(facts.join(" "))
+            .execute(&self.conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn load_scenario(&self, kb: &str, name: &str) -> Result<Option<Vec<String>>> {
+        check_kb(&self.conn, kb).await?;
+        let row = sqlx::query("SELECT facts FROM scenarios WHERE kb = ? AND name = ?")
+            .bind(kb)
+            .bind(name)
+            .fetch_optional(&self.conn)
+            .await?;
+        Ok(row.map(|row| split_facts(row.get(0))))
+    }
+
+    async fn list_scenarios(&self, kb: &str) -> Result<Vec<(String, Vec<String>)>> {
+        check_kb(&self.conn, kb).await?;
+        let rows = sqlx::query("SELECT name, facts FROM scenarios WHERE kb = ? ORDER BY name")
+            .bind(kb)
+            .fetch_all(&self.conn)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.get(0), split_facts(row.get(1))))
+            .collect())
+    }
+
+    async fn insert_run(&self, run: &Run) -> Result<i64> {
+        let fired = run
+            .fired
+            .iter()
+            .map(|fired| {
+                format!(
+                    "{}\t{}\t{}\t{}",
+                    fired.cycle,
+                    fired.rule,
+                    fired.condition,
+                    fired.added.join(",")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let result = sqlx::query(
+            "INSERT INTO runs (kb, scenario, input, derived, cycles, fired, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&run.kb)
+        .bind(&run.scenario)
+        .bind(run.input.join(" "))
+        .bind(run.derived.join(" "))
+        .bind(run.cycles)
+        .bind(fired)
+        .bind(&run.created_at)
+        .execute(&self.conn)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn list_runs(&self, kb: &str) -> Result<Vec<Run>> {
+        let rows = sqlx::query(
+            "SELECT id, kb, scenario, input, derived, cycles, fired, created_at
+             FROM runs WHERE kb = ? ORDER BY id",
+        )
+        .bind(kb)
+        .fetch_all(&self.conn)
+        .await?;
+        rows.iter().map(run_from_row).collect()
+    }
+
+    async fn load_run(&self, id: i64) -> Result<Option<Run>> {
+        let row = sqlx::query(
+            "SELECT id, kb, scenario, input, derived, cycles, fired, created_at
+             FROM runs WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_optional(&self.conn)
+        .await?;
+        row.as_ref().map(run_from_row).transpose()
+    }
+
+    async fn last_scenario_run(&self, kb: &str, scenario: &str) -> Result<Option<Run>> {
+        let row = sqlx::query(
+            "SELECT id, kb, scenario, input, derived, cycles, fired, created_at
+             FROM runs WHERE kb = ? AND scenario = ? ORDER BY id DESC LIMIT 1",
+        )
+        .bind(kb)
+        .bind(scenario)
+        .fetch_optional(&self.conn)
+        .await?;
+        row.as_ref().map(run_from_row).transpose()
+    }
+}
+
+/// The columns [`rule_from_row`] reads, on a table aliased as `r`.
+const RULE_COLUMNS: &str = "r.id, r.condition, r.output, r.name, r.description, r.explanation, \
+    r.template, r.tags, r.author, r.created_at, r.updated_at, r.enabled";
+
+fn rule_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<(i64, Rule)> {
+    let id: i64 = row.get(0);
+    let condition: String = row.get(1);
+    let output: String = row.get(2);
+    let rule = Rule {
+        name: row.get(3),
+        description: row.get(4),
+        explanation: row.get(5),
+        template: row.get(6),
+        tags: split_facts(row.get(7)),
+        author: row.get(8),
+        created: row.get(9),
+        updated: row.get(10),
+        enabled: row.get(11),
+        ..Rule::try_from((id, condition, output))?
+    };
+    Ok((id, rule))
+}
+
+fn split_facts(facts: String) -> Vec<String> {
+    facts.split_whitespace().map(|s| s.to_string()).collect()
+}
+
+fn run_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Run> {
+    let fired: String = row.get(6);
+    let fired = fired
+        .lines()
+        .map(|line| {
+            let parts = line.splitn(4, '\t').collect::<Vec<_>>();
+            let malformed = || Error::Store(format!("malformed fired rule record: {}", line));
+            let [cycle, rule, condition, added] = parts.as_slice() else {
+                return Err(malformed());
+            };
+            Ok(FiredRule {
+                cycle: cycle.parse().map_err(|_| malformed())?,
+                rule: rule.parse().map_err(|_| malformed())?,
+                condition: condition.to_string(),
+                added: added.split(',').map(|s| s.to_string()).collect(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Run {
+        id: row.get(0),
+        kb: row.get(1),
+        scenario: row.get(2),
+        input: split_facts(row.get(3)),
+        derived: split_facts(row.get(4)),
+        cycles: row.get(5),
+        fired,
+        created_at: row.get(7),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::tests::conformance;
+
+    fn rule(condition: &str, output: &str) -> Rule {
+        Rule::new(condition, output).unwrap()
+    }
+
+    async fn store() -> SqliteStore {
+        SqliteStore::new("sqlite::memory:").await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_conformance() {
+        conformance(&store().await).await;
+    }
+
+    #[tokio::test]
+    async fn test_find_rules() {
+        let store = store().await;
+        let ids = |found: Vec<(i64, Rule)>| found.into_iter().map(|(id, _)| id).collect::<Vec<_>>();
+        let facts = |facts: &[&str]| Query {
+            facts: facts.iter().map(|fact| fact.to_string()).collect(),
+            ..Default::default()
+        };
+        store
+            .save_rules(DEFAULT_KB, &[rule("fur & eats_meat", "carnivore"), rule("feathers", "bird")])
+            .await
+            .unwrap();
+        let id = store.insert_rule(DEFAULT_KB, &rule("eats_meat | claws", "hunter")).await.unwrap();
+        store.create_kb("other").await.unwrap();
+        store.insert_rule("other", &rule("eats_meat", "x")).await.unwrap();
+        assert_eq!(ids(store.find_rules(DEFAULT_KB, &facts(&["eats_meat"])).await.unwrap()), [1, id]);
+        // Fact names are indexed whole, `eats` is not `eats_meat`
+        assert!(store.find_rules(DEFAULT_KB, &facts(&["eats"])).await.unwrap().is_empty());
+        let outputs = Query {
+            outputs: vec!["bird".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(ids(store.find_rules(DEFAULT_KB, &outputs).await.unwrap()), [2]);
+
+        store.delete_rule(DEFAULT_KB, 1).await.unwrap();
+        assert_eq!(ids(store.find_rules(DEFAULT_KB, &facts(&["eats_meat"])).await.unwrap()), [id]);
+        assert!(store.find_rules(DEFAULT_KB, &facts(&["fur"])).await.unwrap().is_empty());
+
+        // Saving replaces the rules, the index follows the new conditions
+        store
+            .save_rules(DEFAULT_KB, &[rule("fur & claws", "carnivore"), rule("feathers", "bird")])
+            .await
+            .unwrap();
+        let found = store.find_rules(DEFAULT_KB, &facts(&["fur", "claws"])).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].1.output(), ["carnivore"]);
+        assert!(store.find_rules(DEFAULT_KB, &facts(&["eats_meat"])).await.unwrap().is_empty());
+        assert_eq!(store.find_rules(DEFAULT_KB, &Query::default()).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_save_changes_nothing() {
+        let store = store().await;
+        store.save_rules(DEFAULT_KB, &[rule("a", "b")]).await.unwrap();
+        sqlx::query(
+            "CREATE TRIGGER reject BEFORE INSERT ON rules WHEN new.condition = 'c'
+             BEGIN SELECT RAISE(ABORT, 'rejected'); END",
+        )
+        .execute(&store.conn)
+        .await
+        .unwrap();
+        assert!(store.save_rules(DEFAULT_KB, &[rule("b", "c"), rule("c", "d")]).await.is_err());
+        let rules = store.load_rules_raw(DEFAULT_KB).await.unwrap();
+        assert_eq!(rules, [(1, "a".to_string(), "b".to_string())]);
+        assert!(store.insert_rule(DEFAULT_KB, &rule("c", "d")).await.is_err());
+        // Neither failure is in the history
+        assert_eq!(store.history(DEFAULT_KB).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_baseline() {
+        // The schema of databases from before knowledge bases
+        let conn = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::query(
+            "CREATE TABLE rules (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                condition TEXT NOT NULL,
+                output TEXT NOT NULL
+            )",
+        )
+        .execute(&conn)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO rules (condition, output) VALUES ('fur', 'mammal'), ('mammal & eats_meat', 'carnivore')")
+            .execute(&conn)
+            .await
+            .unwrap();
+
+        let store = SqliteStore { conn };
+        store.init().await.unwrap();
+        store.migrate().await.unwrap();
+        let rules = store.load_rules(DEFAULT_KB).await.unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[1].output(), ["carnivore"]);
+        assert!(rules.iter().all(|rule| rule.is_enabled() && rule.created().is_none()));
+        let query = Query {
+            facts: vec!["mammal".to_string()],
+            ..Default::default()
+        };
+        let found = store.find_rules(DEFAULT_KB, &query).await.unwrap();
+        assert_eq!(found.iter().map(|(id, _)| *id).collect::<Vec<_>>(), [2]);
+        // Migrating again leaves it as it is
+        store.migrate().await.unwrap();
+        assert_eq!(store.load_rules(DEFAULT_KB).await.unwrap().len(), 2);
+    }
+}
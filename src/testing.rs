@@ -0,0 +1,102 @@
+//! Test cases for a rule base: given some facts, these facts must be
+//! derived and those must not.
+
+use crate::coverage::Coverage;
+use crate::rule::{Facts, Firing, Options, Rule};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestCase {
+    pub name: String,
+    pub given: Vec<String>,
+    #[serde(default)]
+    pub expect: Vec<String>,
+    #[serde(default)]
+    pub expect_not: Vec<String>,
+}
+
+/// The outcome of a test case along with the firings that led to it.
+#[derive(Debug, Clone, Serialize)]
+pub struct TestResult {
+    pub name: String,
+    pub derived: Vec<String>,
+    /// Expected facts that weren't derived.
+    pub missing: Vec<String>,
+    /// Facts that were derived although they shouldn't be.
+    pub unwanted: Vec<String>,
+    pub fired: Vec<Firing>,
+    #[serde(skip)]
+    pub coverage: Coverage,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.missing.is_empty() && self.unwanted.is_empty()
+    }
+}
+
+impl TestCase {
+    pub fn run(&self, rules: &[Rule]) -> TestResult {
+        self.run_with(rules, &Options::default())
+    }
+
+    /// [`TestCase::run`] with the cycle limit and strategy of `options`.
+    pub fn run_with(&self, rules: &[Rule], options: &Options) -> TestResult {
+        let mut facts = Facts::from(self.given.clone());
+        facts.record_coverage();
+        facts.deduce_with(rules, options);
+        TestResult {
+            name: self.name.clone(),
+            derived: facts.derived().to_vec(),
+            missing: self
+                .expect
+                .iter()
+                .filter(|fact| !facts.contains(fact))
+                .cloned()
+                .collect(),
+            unwanted: self
+                .expect_not
+                .iter()
+                .filter(|fact| facts.contains(fact))
+                .cloned()
+                .collect(),
+            fired: facts.fired().to_vec(),
+            coverage: facts.coverage().cloned().unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let rules = vec![
+            Rule::new("fur", "mammal").unwrap(),
+            Rule::new("mammal & spots", "cheetah").unwrap(),
+        ];
+        let case = TestCase {
+            name: "tiger".to_string(),
+            given: vec!["fur".to_string(), "spots".to_string()],
+            expect: vec!["mammal".to_string(), "tiger".to_string()],
+            expect_not: vec!["cheetah".to_string()],
+        };
+        let result = case.run(&rules);
+        assert!(!result.passed());
+        assert_eq!(result.missing, ["tiger"]);
+        assert_eq!(result.unwanted, ["cheetah"]);
+        assert_eq!(result.fired.len(), 2);
+
+        // With the cheetah rule first, a single cycle stops before it fires
+        let rules = [rules[1].clone(), rules[0].clone()];
+        let options = Options {
+            max_cycles: Some(1),
+            ..Options::default()
+        };
+        let result = case.run_with(&rules, &options);
+        assert_eq!(result.derived, ["mammal"]);
+        assert_eq!(result.missing, ["tiger"]);
+        assert!(result.unwanted.is_empty());
+    }
+}
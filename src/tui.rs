@@ -0,0 +1,413 @@
+//! Full-screen mode: a searchable rule list, a rule editor that validates
+//! while typing, the input facts and what each cycle derives from them.
+//!
+//! It works on the same [`Context`] as the line REPL, so rules, tests and
+//! the database connection carry over in both directions.
+
+use crate::Context;
+use expert::{Condition, Facts, ParseError, Rule};
+use expert::store;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Focus {
+    Rules,
+    Editor,
+    Facts,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Condition,
+    Output,
+}
+
+struct App {
+    focus: Focus,
+    /// Typing goes to the search box of the rule list.
+    searching: bool,
+    search: String,
+    /// Position in the filtered rule list.
+    selected: usize,
+    /// The rule being edited, `None` for a new one.
+    editing: Option<usize>,
+    field: Field,
+    condition: String,
+    output: String,
+    facts: String,
+    message: String,
+}
+
+impl App {
+    fn new(ctx: &Context) -> Self {
+        App {
+            focus: Focus::Rules,
+            searching: false,
+            search: String::new(),
+            selected: 0,
+            editing: None,
+            field: Field::Condition,
+            condition: String::new(),
+            output: String::new(),
+            facts: ctx.input.join(" "),
+            message: t!("tui-keys"),
+        }
+    }
+
+    /// Indices of the rules matching the search.
+    fn visible(&self, rules: &[Rule]) -> Vec<usize> {
+        let search = self.search.to_lowercase();
+        rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule_text(rule).to_lowercase().contains(&search))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    fn validate(&self) -> Result<Rule, String> {
+        let condition = self
+            .condition
+            .parse::<Condition>()
+            .map_err(|e| parse_error(&e))?;
+        let output = self
+            .output
+            .split(',')
+            .map(str::trim)
+            .filter(|fact| !fact.is_empty());
+        Rule::builder(condition)
+            .outputs(output)
+            .build()
+            .map_err(|e| e.to_string())
+    }
+
+    /// Handles a key, returns whether to leave.
+    fn key(&mut self, key: KeyEvent, ctx: &mut Context) -> bool {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return true;
+        }
+        if key.code == KeyCode::Tab && !self.searching {
+            self.focus = match self.focus {
+                Focus::Rules => Focus::Editor,
+                Focus::Editor => Focus::Facts,
+                Focus::Facts => Focus::Rules,
+            };
+            return false;
+        }
+        match self.focus {
+            Focus::Rules if self.searching => match key.code {
+                KeyCode::Enter | KeyCode::Esc => self.searching = false,
+                KeyCode::Backspace => {
+                    self.search.pop();
+                    self.selected = 0;
+                }
+                KeyCode::Char(c) => {
+                    self.search.push(c);
+                    self.selected = 0;
+                }
+                _ => {}
+            },
+            Focus::Rules => return self.rules_key(key, ctx),
+            Focus::Editor => match key.code {
+                KeyCode::Up | KeyCode::Down => {
+                    self.field = match self.field {
+                        Field::Condition => Field::Output,
+                        Field::Output => Field::Condition,
+                    }
+                }
+                KeyCode::Enter => self.save(ctx),
+                KeyCode::Esc => self.focus = Focus::Rules,
+                KeyCode::Backspace => {
+                    self.field_mut().pop();
+                }
+                KeyCode::Char(c) => self.field_mut().push(c),
+                _ => {}
+            },
+            Focus::Facts => match key.code {
+                KeyCode::Esc => self.focus = Focus::Rules,
+                KeyCode::Backspace => {
+                    self.facts.pop();
+                    self.update_input(ctx);
+                }
+                KeyCode::Char(c) => {
+                    self.facts.push(c);
+                    self.update_input(ctx);
+                }
+                _ => {}
+            },
+        }
+        false
+    }
+
+    fn rules_key(&mut self, key: KeyEvent, ctx: &mut Context) -> bool {
+        let visible = self.visible(&ctx.rules);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Char('/') => self.searching = true,
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if self.selected + 1 < visible.len() => self.selected += 1,
+            KeyCode::Enter => {
+                if let Some(&idx) = visible.get(self.selected) {
+                    let rule = &ctx.rules[idx];
+                    self.editing = Some(idx);
+                    self.condition = rule.condition().to_string();
+                    self.output = rule.output().join(", ");
+                    self.field = Field::Condition;
+                    self.focus = Focus::Editor;
+                }
+            }
+            KeyCode::Char('n') => {
+                self.editing = None;
+                self.condition.clear();
+                self.output.clear();
+                self.field = Field::Condition;
+                self.focus = Focus::Editor;
+            }
+            KeyCode::Char('d') => {
+                if let Some(&idx) = visible.get(self.selected) {
+                    ctx.record(format!("rule remove {}: {}", idx, ctx.rules[idx]));
+                    ctx.rules.remove(idx);
+                    self.editing = None;
+                    self.selected = self.selected.min(visible.len().saturating_sub(2));
+                    self.message = t!("tui-removed", id = idx);
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn field_mut(&mut self) -> &mut String {
+        match self.field {
+            Field::Condition => &mut self.condition,
+            Field::Output => &mut self.output,
+        }
+    }
+
+    fn save(&mut self, ctx: &mut Context) {
+        let rule = match self.validate() {
+            Ok(rule) => rule,
+            Err(e) => {
+                self.message = t!("tui-not-saved", error = e);
+                return;
+            }
+        };
+        match self.editing {
+            Some(idx) if idx < ctx.rules.len() => {
+                // Only the condition and outputs are edited here, the
+                // metadata of the rule stays
+                let edited = ctx.edit_rule(&idx.to_string(), |builder| {
+                    Ok(builder
+                        .condition(rule.condition().clone())
+                        .clear_outputs()
+                        .outputs(rule.output().iter().cloned()))
+                });
+                self.message = match edited {
+                    Ok(_) => t!("tui-updated", id = idx),
+                    Err(e) => t!("tui-not-saved", error = e),
+                };
+            }
+            _ => {
+                let now = store::now();
+                let rule = match rule.to_builder().created(&now).updated(now).build() {
+                    Ok(rule) => rule,
+                    Err(e) => {
+                        self.message = t!("tui-not-saved", error = e);
+                        return;
+                    }
+                };
+                ctx.record(format!("rule add: {}", rule));
+                ctx.rules.push(rule);
+                self.editing = Some(ctx.rules.len() - 1);
+                self.message = t!("tui-added", id = ctx.rules.len() - 1);
+            }
+        }
+    }
+
+    fn update_input(&self, ctx: &mut Context) {
+        ctx.input = self.facts.split_whitespace().map(str::to_string).collect();
+    }
+
+    fn draw(&self, frame: &mut Frame, ctx: &Context) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)]).areas(main);
+        let [editor, facts, derived] = Layout::vertical([
+            Constraint::Length(5),
+            Constraint::Length(3),
+            Constraint::Min(0),
+        ])
+        .areas(right);
+
+        self.draw_rules(frame, left, ctx);
+        self.draw_editor(frame, editor);
+
+        let block = self.block(&t!("tui-facts"), Focus::Facts);
+        frame.render_widget(Paragraph::new(self.facts.as_str()).block(block), facts);
+        if self.focus == Focus::Facts {
+            let x = facts.x + 1 + self.facts.chars().count() as u16;
+            frame.set_cursor_position(Position::new(x, facts.y + 1));
+        }
+
+        let lines = derived_lines(ctx);
+        frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(t!("tui-derived"))), derived);
+
+        let db = match &ctx.db {
+            Some(_) => t!("tui-db", kb = ctx.kb),
+            None => t!("tui-no-db"),
+        };
+        let text = format!(" {} | {} | {}", t!("tui-rule-count", count = ctx.rules.len()), db, self.message);
+        let style = Style::default().bg(Color::Blue).fg(Color::White);
+        frame.render_widget(Paragraph::new(text).style(style), status);
+    }
+
+    fn draw_rules(&self, frame: &mut Frame, area: Rect, ctx: &Context) {
+        let visible = self.visible(&ctx.rules);
+        let items = visible
+            .iter()
+            .map(|&idx| ListItem::new(format!("#{} {}", idx, rule_text(&ctx.rules[idx]))))
+            .collect::<Vec<_>>();
+        let title = if self.searching || !self.search.is_empty() {
+            format!("{} /{}", t!("tui-rules"), self.search)
+        } else {
+            t!("tui-rules")
+        };
+        let list = List::new(items)
+            .block(self.block(&title, Focus::Rules))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn draw_editor(&self, frame: &mut Frame, area: Rect) {
+        let title = match self.editing {
+            Some(idx) => t!("tui-edit", id = idx),
+            None => t!("tui-new"),
+        };
+        let check = match self.validate() {
+            Ok(_) => Line::styled(t!("tui-valid"), Style::default().fg(Color::Green)),
+            Err(e) => Line::styled(e, Style::default().fg(Color::Red)),
+        };
+        let lines = vec![
+            Line::from(vec![Span::raw("IF   "), Span::raw(self.condition.as_str())]),
+            Line::from(vec![Span::raw("THEN "), Span::raw(self.output.as_str())]),
+            check,
+        ];
+        frame.render_widget(Paragraph::new(lines).block(self.block(&title, Focus::Editor)), area);
+        if self.focus == Focus::Editor {
+            let (row, len) = match self.field {
+                Field::Condition => (0, self.condition.chars().count()),
+                Field::Output => (1, self.output.chars().count()),
+            };
+            frame.set_cursor_position(Position::new(area.x + 6 + len as u16, area.y + 1 + row));
+        }
+    }
+
+    fn block(&self, title: &str, focus: Focus) -> Block<'static> {
+        let style = if self.focus == focus {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        Block::bordered().title(title.to_string()).border_style(style)
+    }
+}
+
+fn rule_text(rule: &Rule) -> String {
+    match rule.name() {
+        Some(name) => format!("{}: {}", name, rule),
+        None => rule.to_string(),
+    }
+}
+
+fn parse_error(e: &ParseError) -> String {
+    t!("tui-parse-error", position = e.position(), error = e.message())
+}
+
+/// The facts each cycle added and the rules that added them.
+fn derived_lines(ctx: &Context) -> Vec<Line<'static>> {
+    let mut facts = Facts::from(ctx.input.clone());
+    facts.deduce_with(&ctx.rules, &ctx.engine);
+    let mut lines = vec![];
+    for cycle in 1..=facts.cycle() {
+        let firings = facts
+            .fired()
+            .iter()
+            .filter(|firing| firing.cycle == cycle)
+            .map(|firing| format!("{} (#{})", firing.added.join(", "), firing.rule))
+            .collect::<Vec<_>>();
+        if !firings.is_empty() {
+            lines.push(Line::from(t!("tui-cycle", cycle = cycle, firings = firings.join("; "))));
+        }
+    }
+    if lines.is_empty() {
+        lines.push(Line::from(t!("tui-nothing-derived")));
+    }
+    lines
+}
+
+/// Runs until the user leaves, the terminal is restored either way.
+pub fn run(ctx: &mut Context) -> std::io::Result<()> {
+    // Log lines would draw over the screen, deduction logs every step
+    tracing::subscriber::with_default(tracing::subscriber::NoSubscriber::default(), || {
+        let mut terminal = ratatui::init();
+        let mut app = App::new(ctx);
+        let result = loop {
+            if let Err(e) = terminal.draw(|frame| app.draw(frame, ctx)) {
+                break Err(e);
+            }
+            match event::read() {
+                Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                    if app.key(key, ctx) {
+                        break Ok(());
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => break Err(e),
+            }
+        };
+        ratatui::restore();
+        result
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(app: &mut App, ctx: &mut Context, keys: &str) {
+        for c in keys.chars() {
+            app.key(KeyEvent::from(KeyCode::Char(c)), ctx);
+        }
+    }
+
+    #[test]
+    fn test_edit_rules() {
+        let mut ctx = Context::new();
+        ctx.add_rule("fur", "mammal").unwrap();
+        let mut app = App::new(&ctx);
+
+        press(&mut app, &mut ctx, "n");
+        press(&mut app, &mut ctx, "mammal & ");
+        assert!(app.validate().is_err());
+        press(&mut app, &mut ctx, "stripes");
+        app.key(KeyEvent::from(KeyCode::Down), &mut ctx);
+        press(&mut app, &mut ctx, "tiger");
+        app.key(KeyEvent::from(KeyCode::Enter), &mut ctx);
+        assert_eq!(ctx.rules[1].to_string(), "IF (mammal & stripes) THEN tiger");
+
+        app.key(KeyEvent::from(KeyCode::Esc), &mut ctx);
+        press(&mut app, &mut ctx, "/strip");
+        assert_eq!(app.visible(&ctx.rules), [1]);
+        app.key(KeyEvent::from(KeyCode::Enter), &mut ctx);
+        app.key(KeyEvent::from(KeyCode::Enter), &mut ctx);
+        assert_eq!(app.editing, Some(1));
+        assert_eq!(app.condition, "(mammal & stripes)");
+    }
+}